@@ -2,17 +2,141 @@ extern crate proc_macro;
 
 use proc_macro::TokenStream;
 use syn::parse::Parser;
-use syn::{parse_macro_input, AttributeArgs, DeriveInput, Fields, ItemStruct};
+use syn::{parse_macro_input, AttributeArgs, Data, DeriveInput, Fields, ItemStruct};
 
 use quote::quote;
 
-#[proc_macro_derive(Atom)]
+/// Returns the last path segment of `ty` and its generic argument
+fn last_segment(ty: &syn::Type) -> Option<(&syn::Ident, Option<&syn::Type>)> {
+    if let syn::Type::Path(type_path) = ty {
+        let seg = type_path.path.segments.last()?;
+        let arg = match &seg.arguments {
+            syn::PathArguments::AngleBracketed(args) => match args.args.first() {
+                Some(syn::GenericArgument::Type(t)) => Some(t),
+                _ => None,
+            },
+            _ => None,
+        };
+        Some((&seg.ident, arg))
+    } else {
+        None
+    }
+}
+
+fn is_atom_type(ty: &syn::Type) -> bool {
+    match last_segment(ty) {
+        Some((ident, None)) => ident.to_string().ends_with("Atom"),
+        _ => false,
+    }
+}
+
+/// Returns a statement which pushes the child atoms held by `field`
+///
+/// `Box<..Atom>`, `Option<Box<..Atom>>` and `Vec<..Atom>` fields are children,
+/// and a field marked with `#[children]` provides them by its `children()`.
+fn push_children(field: &syn::Field) -> Option<proc_macro2::TokenStream> {
+    let ident = field.ident.as_ref()?;
+
+    if field.attrs.iter().any(|a| a.path.is_ident("children")) {
+        return Some(quote! { children.extend(self.#ident.children()); });
+    }
+
+    match last_segment(&field.ty)? {
+        (outer, Some(inner)) if outer == "Box" && is_atom_type(inner) => {
+            Some(quote! { children.push(self.#ident.as_ref()); })
+        }
+        (outer, Some(inner)) if outer == "Vec" && is_atom_type(inner) => {
+            Some(quote! { children.extend(self.#ident.iter().map(|a| a as &dyn Atom)); })
+        }
+        (outer, Some(inner)) if outer == "Option" => match last_segment(inner)? {
+            (boxed, Some(inner)) if boxed == "Box" && is_atom_type(inner) => Some(quote! {
+                if let Some(a) = &self.#ident {
+                    children.push(a.as_ref());
+                }
+            }),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn impl_atom<'a, I: Iterator<Item = &'a syn::Field>>(
+    name: &syn::Ident,
+    fields: I,
+) -> proc_macro2::TokenStream {
+    let pushes = fields.filter_map(push_children).collect::<Vec<_>>();
+
+    quote! {
+        impl Atom for #name {
+            fn head(&self) -> &AtomHead {
+                &self.atom_head
+            }
+
+            fn fourcc(&self) -> String {
+                crate::atom::type_to_string(self.atom_head.atom_type)
+            }
+
+            #[allow(unused_mut)]
+            fn children(&self) -> Vec<&dyn Atom> {
+                let mut children: Vec<&dyn Atom> = Vec::new();
+                #(#pushes)*
+                // fields are declared in the usual order, which files may not follow
+                children.sort_by_key(|a| a.head().atom_offset);
+                children
+            }
+        }
+    }
+}
+
+/// Returns the name and the bit of a flag given as `name = 0x0001`
+fn parse_flag(meta: &syn::NestedMeta) -> (syn::Ident, u32) {
+    if let syn::NestedMeta::Meta(syn::Meta::NameValue(nv)) = meta {
+        if let (Some(ident), syn::Lit::Int(bit)) = (nv.path.get_ident(), &nv.lit) {
+            return (ident.clone(), bit.base10_parse().unwrap());
+        }
+    }
+    panic!("a flag must be given as `name = bit`")
+}
+
+/// Returns the accessors of the flags of a versioned atom
+///
+/// Every flag named in `version(..)` has a constant and an `is_` method.
+fn impl_flags(name: &syn::Ident, flags: &[(syn::Ident, u32)]) -> proc_macro2::TokenStream {
+    let accessors = flags.iter().map(|(flag, bit)| {
+        let constant = syn::Ident::new(&flag.to_string().to_uppercase(), flag.span());
+        let method = syn::Ident::new(&format!("is_{}", flag), flag.span());
+        let doc = format!("Returns whether the `{}` flag is set", flag);
+
+        quote! {
+            pub const #constant: u32 = #bit;
+
+            #[doc = #doc]
+            pub fn #method(&self) -> bool {
+                self.flags() & Self::#constant != 0
+            }
+        }
+    });
+
+    quote! {
+        impl #name {
+            /// Returns the 24-bit flags of the atom
+            pub fn flags(&self) -> u32 {
+                u32::from_be_bytes([0, self.atom_flags[0], self.atom_flags[1], self.atom_flags[2]])
+            }
+
+            #(#accessors)*
+        }
+    }
+}
+
+#[proc_macro_derive(Atom, attributes(children))]
 pub fn atom_macro_derive(input: TokenStream) -> TokenStream {
     let ast = parse_macro_input!(input as DeriveInput);
     let name = &ast.ident;
-    let gen = quote! {
-        impl Atom for #name {
-        }
+
+    let gen = match &ast.data {
+        Data::Struct(data) => impl_atom(name, data.fields.iter()),
+        _ => panic!("Atom can be derived only for structs"),
     };
     gen.into()
 }
@@ -25,18 +149,37 @@ pub fn atom(args: TokenStream, input: TokenStream) -> TokenStream {
     let name = &item_struct.ident;
 
     let mut opt_version = false;
+    let mut flags = Vec::new();
 
     for arg in attr_args {
-        if let syn::NestedMeta::Meta(syn::Meta::Path(path)) = arg {
-            for seg in path.segments {
-                if seg.ident == "version" {
-                    opt_version = true;
+        match arg {
+            syn::NestedMeta::Meta(syn::Meta::Path(path)) => {
+                for seg in path.segments {
+                    if seg.ident == "version" {
+                        opt_version = true;
+                    }
                 }
             }
+            syn::NestedMeta::Meta(syn::Meta::List(list)) if list.path.is_ident("version") => {
+                opt_version = true;
+                flags.extend(list.nested.iter().map(parse_flag));
+            }
+            _ => {}
         }
     }
 
+    let impl_atom = impl_atom(name, item_struct.fields.iter());
+    let impl_flags = if opt_version {
+        impl_flags(name, &flags)
+    } else {
+        quote! {}
+    };
+
     if let Fields::Named(ref mut fields) = item_struct.fields {
+        for field in fields.named.iter_mut() {
+            field.attrs.retain(|a| !a.path.is_ident("children"));
+        }
+
         fields.named.insert(
             0,
             syn::Field::parse_named
@@ -63,7 +206,9 @@ pub fn atom(args: TokenStream, input: TokenStream) -> TokenStream {
     let gen = quote! {
         #item_struct
 
-        impl Atom for #name {}
+        #impl_atom
+
+        #impl_flags
     };
 
     gen.into()
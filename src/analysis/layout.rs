@@ -0,0 +1,286 @@
+use std::fmt;
+
+use crate::qtfile::QtFile;
+
+/// A chunk of a track placed in the file
+#[derive(Debug, PartialEq, Clone)]
+pub struct Chunk {
+    pub track_id: u32,
+    /// The 1-based chunk number
+    pub chunk: u32,
+    pub offset: u64,
+    pub size: u64,
+}
+
+impl Chunk {
+    fn tail(&self) -> u64 {
+        self.offset + self.size
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum LayoutIssue {
+    /// Bytes which two chunks share; the chunks are indexes into `chunks`
+    Overlap {
+        offset: u64,
+        size: u64,
+        chunks: (usize, usize),
+    },
+    /// Bytes in 'mdat' between two chunks which no chunk covers
+    Gap { offset: u64, size: u64 },
+    /// Bytes in 'mdat' before the first or after the last chunk in it
+    Unreferenced { offset: u64, size: u64 },
+}
+
+impl fmt::Display for LayoutIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LayoutIssue::Overlap { offset, size, .. } => {
+                write!(f, "overlap at 0x{:x}: {} bytes", offset, size)
+            }
+            LayoutIssue::Gap { offset, size } => {
+                write!(f, "gap at 0x{:x}: {} bytes", offset, size)
+            }
+            LayoutIssue::Unreferenced { offset, size } => {
+                write!(f, "unreferenced at 0x{:x}: {} bytes", offset, size)
+            }
+        }
+    }
+}
+
+/// The chunks of all tracks sorted by offset
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct Layout {
+    /// The payload ranges (offset, size) of the 'mdat' atoms
+    pub mdat: Vec<(u64, u64)>,
+    pub chunks: Vec<Chunk>,
+    pub issues: Vec<LayoutIssue>,
+}
+
+impl Layout {
+    /// Returns a Layout of `chunks` within the 'mdat' payloads `mdat`, finding
+    /// overlaps, gaps and unreferenced bytes
+    ///
+    /// # Arguments
+    ///
+    /// * `mdat` - the payload ranges (offset, size) of the 'mdat' atoms
+    /// * `chunks` - the chunks of all tracks in any order
+    pub fn new(mut mdat: Vec<(u64, u64)>, mut chunks: Vec<Chunk>) -> Self {
+        mdat.sort_unstable();
+        chunks.sort_by_key(|c| (c.offset, c.size));
+
+        let mut issues = Vec::new();
+
+        // the chunk which reaches furthest so far
+        let mut furthest: Option<usize> = None;
+        for (i, chunk) in chunks.iter().enumerate() {
+            if let Some(f) = furthest {
+                let tail = chunks[f].tail();
+                if chunk.offset < tail {
+                    issues.push(LayoutIssue::Overlap {
+                        offset: chunk.offset,
+                        size: tail.min(chunk.tail()) - chunk.offset,
+                        chunks: (f, i),
+                    });
+                }
+                if chunk.tail() <= tail {
+                    continue;
+                }
+            }
+            furthest = Some(i);
+        }
+
+        for &(head, size) in &mdat {
+            let tail = head + size;
+            let mut covered = head;
+            let mut first = true;
+
+            for chunk in chunks.iter().filter(|c| c.offset < tail && c.tail() > head) {
+                if chunk.offset > covered {
+                    let size = chunk.offset - covered;
+                    issues.push(if first {
+                        LayoutIssue::Unreferenced {
+                            offset: covered,
+                            size,
+                        }
+                    } else {
+                        LayoutIssue::Gap {
+                            offset: covered,
+                            size,
+                        }
+                    });
+                }
+                covered = covered.max(chunk.tail());
+                first = false;
+            }
+
+            if covered < tail {
+                issues.push(LayoutIssue::Unreferenced {
+                    offset: covered,
+                    size: tail - covered,
+                });
+            }
+        }
+
+        issues.sort_by_key(|i| match i {
+            LayoutIssue::Overlap { offset, .. }
+            | LayoutIssue::Gap { offset, .. }
+            | LayoutIssue::Unreferenced { offset, .. } => *offset,
+        });
+
+        Layout {
+            mdat,
+            chunks,
+            issues,
+        }
+    }
+
+    /// Returns one line of `width` cells over the 'mdat' payloads
+    ///
+    /// A cell shows the last digit of the track ID which starts the most bytes
+    /// in it, `.` for bytes which no chunk covers and `!` for an overlap.
+    pub fn ascii_map(&self, width: usize) -> String {
+        let head = self.mdat.iter().map(|m| m.0).min().unwrap_or(0);
+        let tail = self.mdat.iter().map(|m| m.0 + m.1).max().unwrap_or(0);
+
+        if width == 0 || tail <= head {
+            return String::new();
+        }
+
+        let span = tail - head;
+
+        (0..width as u64)
+            .map(|cell| {
+                let cell_head = head + span * cell / width as u64;
+                let cell_tail = (head + span * (cell + 1) / width as u64).max(cell_head + 1);
+                let covered = |offset: u64, size: u64| {
+                    (offset + size)
+                        .min(cell_tail)
+                        .saturating_sub(offset.max(cell_head))
+                };
+
+                let overlapped = self.issues.iter().any(|i| match i {
+                    LayoutIssue::Overlap { offset, size, .. } => covered(*offset, *size) > 0,
+                    _ => false,
+                });
+                if overlapped {
+                    return '!';
+                }
+
+                self.chunks
+                    .iter()
+                    .map(|c| (covered(c.offset, c.size), c.track_id))
+                    .filter(|(bytes, _)| *bytes > 0)
+                    .max_by_key(|(bytes, _)| *bytes)
+                    .map_or('.', |(_, id)| {
+                        std::char::from_digit(id % 10, 10).unwrap_or('?')
+                    })
+            })
+            .collect()
+    }
+
+    /// Returns the layout as a JSON object
+    pub fn to_json(&self) -> String {
+        let mdat = self
+            .mdat
+            .iter()
+            .map(|(offset, size)| format!("{{\"offset\":{},\"size\":{}}}", offset, size))
+            .collect::<Vec<_>>();
+        let chunks = self
+            .chunks
+            .iter()
+            .map(|c| {
+                format!(
+                    "{{\"track_id\":{},\"chunk\":{},\"offset\":{},\"size\":{}}}",
+                    c.track_id, c.chunk, c.offset, c.size
+                )
+            })
+            .collect::<Vec<_>>();
+        let issues = self
+            .issues
+            .iter()
+            .map(|i| match i {
+                LayoutIssue::Overlap {
+                    offset,
+                    size,
+                    chunks,
+                } => format!(
+                    "{{\"kind\":\"overlap\",\"offset\":{},\"size\":{},\"chunks\":[{},{}]}}",
+                    offset, size, chunks.0, chunks.1
+                ),
+                LayoutIssue::Gap { offset, size } => {
+                    format!(
+                        "{{\"kind\":\"gap\",\"offset\":{},\"size\":{}}}",
+                        offset, size
+                    )
+                }
+                LayoutIssue::Unreferenced { offset, size } => format!(
+                    "{{\"kind\":\"unreferenced\",\"offset\":{},\"size\":{}}}",
+                    offset, size
+                ),
+            })
+            .collect::<Vec<_>>();
+
+        format!(
+            "{{\"mdat\":[{}],\"chunks\":[{}],\"issues\":[{}]}}",
+            mdat.join(","),
+            chunks.join(","),
+            issues.join(",")
+        )
+    }
+}
+
+impl fmt::Display for Layout {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for chunk in &self.chunks {
+            writeln!(
+                f,
+                "0x{:016x}-0x{:016x} track {} chunk {} ({} bytes)",
+                chunk.offset,
+                chunk.tail(),
+                chunk.track_id,
+                chunk.chunk,
+                chunk.size
+            )?;
+        }
+
+        for issue in &self.issues {
+            writeln!(f, "{}", issue)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Returns the layout of the chunks of all tracks in `qt`
+pub fn layout(qt: &QtFile) -> Layout {
+    let mdat = qt
+        .mdat()
+        .map(|m| {
+            let (head, tail) = m.data_range();
+            (head, tail - head)
+        })
+        .collect();
+
+    let mut chunks: Vec<Chunk> = Vec::new();
+
+    for trak in qt.moov().iter().flat_map(|moov| moov.trak_atom.iter()) {
+        let track_id = trak.tkhd_atom.track_id;
+
+        for sample in trak.samples() {
+            match chunks.last_mut() {
+                Some(c) if c.track_id == track_id && c.chunk == sample.chunk => {
+                    c.size += sample.size as u64
+                }
+                _ => chunks.push(Chunk {
+                    track_id,
+                    chunk: sample.chunk,
+                    offset: sample.offset,
+                    size: sample.size as u64,
+                }),
+            }
+        }
+    }
+
+    Layout::new(mdat, chunks)
+}
@@ -0,0 +1,301 @@
+pub mod layout;
+pub mod samples;
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use crate::atom::hdlr::ComponentSubType;
+use crate::atom::stbl::Sample;
+use crate::atom::trak::TrakAtom;
+use crate::qtfile::QtFile;
+
+/// Bitrates in bits per second over windows of a fixed length
+#[derive(Debug, PartialEq, Clone)]
+pub struct Bitrate {
+    /// The window length in seconds
+    pub window: f64,
+    pub min: f64,
+    pub avg: f64,
+    pub max: f64,
+}
+
+/// The report on the samples of one track
+#[derive(Debug, PartialEq, Clone)]
+pub struct TrackAnalysis {
+    pub track_id: u32,
+    pub media_type: Option<ComponentSubType>,
+    pub sample_count: usize,
+    /// The number of samples from each sync sample to the next
+    pub gop_lengths: Vec<usize>,
+    pub bitrate: Option<Bitrate>,
+    /// The number of samples per duration in the media time scale
+    pub frame_durations: BTreeMap<u32, usize>,
+    /// Whether any sample is presented before a sample decoded earlier
+    pub has_b_frames: bool,
+}
+
+impl TrackAnalysis {
+    /// Returns whether the samples have more than one duration
+    pub fn is_variable_frame_rate(&self) -> bool {
+        self.frame_durations.len() > 1
+    }
+}
+
+/// How far apart in time the audio and video chunks are laid out
+#[derive(Debug, PartialEq, Clone)]
+pub struct Interleave {
+    pub video_track_id: u32,
+    pub audio_track_id: u32,
+    /// The largest difference in seconds between the latest video and audio
+    /// chunks read so far, walking the chunks in file order
+    pub max_distance: f64,
+}
+
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct Analysis {
+    pub tracks: Vec<TrackAnalysis>,
+    pub interleave: Option<Interleave>,
+}
+
+/// Returns the number of samples from each sync sample to the next
+fn gop_lengths(samples: &[Sample]) -> Vec<usize> {
+    let sync = samples
+        .iter()
+        .filter(|s| s.is_sync)
+        .map(|s| s.index)
+        .collect::<Vec<_>>();
+
+    sync.iter()
+        .zip(sync.iter().skip(1).chain(std::iter::once(&samples.len())))
+        .map(|(head, tail)| tail - head)
+        .collect()
+}
+
+/// Returns the bitrates over windows of `window` seconds starting at each
+/// sample, or over the whole track if it is shorter than a window
+fn bitrate(samples: &[Sample], time_scale: u32, window: f64) -> Option<Bitrate> {
+    let last = samples.last()?;
+    let duration = last.decode_time + last.duration as u64;
+
+    if time_scale == 0 || duration == 0 || window.is_nan() || window <= 0.0 {
+        return None;
+    }
+
+    let bits = |s: &[Sample]| s.iter().map(|s| s.size as f64 * 8.0).sum::<f64>();
+    let avg = bits(samples) * time_scale as f64 / duration as f64;
+    let window_ticks = (window * time_scale as f64) as u64;
+
+    if window_ticks == 0 || window_ticks >= duration {
+        return Some(Bitrate {
+            window,
+            min: avg,
+            avg,
+            max: avg,
+        });
+    }
+
+    let mut min = f64::MAX;
+    let mut max = 0.0_f64;
+    let mut tail = 0;
+
+    for (head, sample) in samples.iter().enumerate() {
+        let window_tail = sample.decode_time + window_ticks;
+        if window_tail > duration {
+            break;
+        }
+
+        tail = tail.max(head);
+        while tail < samples.len() && samples[tail].decode_time < window_tail {
+            tail += 1;
+        }
+
+        let rate = bits(&samples[head..tail]) / window;
+        min = min.min(rate);
+        max = max.max(rate);
+    }
+
+    Some(Bitrate {
+        window,
+        min,
+        avg,
+        max,
+    })
+}
+
+/// Returns the report on the samples of `trak`
+///
+/// # Arguments
+///
+/// * `trak` - the track to analyze
+/// * `window` - the bitrate window in seconds
+pub fn analyze_track(trak: &TrakAtom, window: f64) -> TrackAnalysis {
+    let samples = trak.samples();
+
+    let mut frame_durations = BTreeMap::new();
+    for sample in &samples {
+        *frame_durations.entry(sample.duration).or_insert(0) += 1;
+    }
+
+    let has_b_frames = samples
+        .windows(2)
+        .any(|w| w[1].composition_time < w[0].composition_time);
+
+    TrackAnalysis {
+        track_id: trak.tkhd_atom.track_id,
+        media_type: trak.media_type().cloned(),
+        sample_count: samples.len(),
+        gop_lengths: gop_lengths(&samples),
+        bitrate: bitrate(&samples, trak.media_time_scale(), window),
+        frame_durations,
+        has_b_frames,
+    }
+}
+
+/// Returns the interleave of the chunks of `video` and `audio`, or `None` if
+/// either has no samples
+pub fn interleave(video: &TrakAtom, audio: &TrakAtom) -> Option<Interleave> {
+    // (offset, start time in seconds, whether it is video) of every chunk
+    let mut chunks = Vec::new();
+
+    for (trak, is_video) in [(video, true), (audio, false)] {
+        let time_scale = trak.media_time_scale() as f64;
+        let mut last_chunk = None;
+
+        for sample in trak.samples() {
+            if last_chunk != Some(sample.chunk) {
+                last_chunk = Some(sample.chunk);
+                chunks.push((
+                    sample.offset,
+                    sample.decode_time as f64 / time_scale,
+                    is_video,
+                ));
+            }
+        }
+    }
+
+    chunks.sort_by_key(|c| c.0);
+
+    let mut video_time = None;
+    let mut audio_time = None;
+    let mut max_distance = 0.0_f64;
+
+    for (_, time, is_video) in chunks {
+        if is_video {
+            video_time = Some(time);
+        } else {
+            audio_time = Some(time);
+        }
+
+        if let (Some(v), Some(a)) = (video_time, audio_time) {
+            max_distance = max_distance.max((v - a).abs());
+        }
+    }
+
+    if video_time.is_none() || audio_time.is_none() {
+        return None;
+    }
+
+    Some(Interleave {
+        video_track_id: video.tkhd_atom.track_id,
+        audio_track_id: audio.tkhd_atom.track_id,
+        max_distance,
+    })
+}
+
+/// Returns the report on all the tracks in `qt`
+///
+/// The interleave is measured between the first video and the first audio
+/// track.
+///
+/// # Arguments
+///
+/// * `qt` - the file to analyze
+/// * `window` - the bitrate window in seconds
+pub fn analyze(qt: &QtFile, window: f64) -> Analysis {
+    let moov = match qt.moov() {
+        Some(moov) => moov,
+        None => return Analysis::default(),
+    };
+    let first_of = |media_type: ComponentSubType| {
+        moov.trak_atom
+            .iter()
+            .find(|t| t.media_type() == Some(&media_type))
+    };
+
+    Analysis {
+        tracks: moov
+            .trak_atom
+            .iter()
+            .map(|t| analyze_track(t, window))
+            .collect(),
+        interleave: match (
+            first_of(ComponentSubType::VideoMedia),
+            first_of(ComponentSubType::SoundMedia),
+        ) {
+            (Some(video), Some(audio)) => interleave(video, audio),
+            _ => None,
+        },
+    }
+}
+
+impl fmt::Display for TrackAnalysis {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.media_type {
+            Some(t) => writeln!(f, "track {} ({:?})", self.track_id, t)?,
+            None => writeln!(f, "track {}", self.track_id)?,
+        }
+        writeln!(f, "  samples: {}", self.sample_count)?;
+
+        let gop_lengths = self
+            .gop_lengths
+            .iter()
+            .map(|l| l.to_string())
+            .collect::<Vec<_>>();
+        writeln!(f, "  gop lengths: {}", gop_lengths.join(" "))?;
+
+        if let Some(b) = &self.bitrate {
+            writeln!(
+                f,
+                "  bitrate ({}s windows): min {:.0} / avg {:.0} / max {:.0} bps",
+                b.window, b.min, b.avg, b.max
+            )?;
+        }
+
+        writeln!(
+            f,
+            "  frame durations{}:",
+            if self.is_variable_frame_rate() {
+                " (variable)"
+            } else {
+                ""
+            }
+        )?;
+        for (duration, count) in &self.frame_durations {
+            writeln!(f, "    {}: {}", duration, count)?;
+        }
+
+        writeln!(
+            f,
+            "  b-frames: {}",
+            if self.has_b_frames { "yes" } else { "no" }
+        )
+    }
+}
+
+impl fmt::Display for Analysis {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for track in &self.tracks {
+            write!(f, "{}", track)?;
+        }
+
+        if let Some(i) = &self.interleave {
+            writeln!(
+                f,
+                "interleave (track {} / track {}): {:.3}s at most",
+                i.video_track_id, i.audio_track_id, i.max_distance
+            )?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,102 @@
+use std::io::{self, Write};
+use std::str::FromStr;
+
+use crate::atom::stbl::Sample;
+
+/// The unit of the times in a sample listing
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum TimeUnit {
+    /// The media time scale
+    Ticks,
+    Seconds,
+}
+
+impl FromStr for TimeUnit {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "ticks" => Ok(TimeUnit::Ticks),
+            "seconds" => Ok(TimeUnit::Seconds),
+            _ => Err(format!("invalid time unit `{}'", s)),
+        }
+    }
+}
+
+impl TimeUnit {
+    /// Returns `ticks` in the media time scale `time_scale` in this unit
+    fn format(self, ticks: i64, time_scale: u32) -> String {
+        match self {
+            TimeUnit::Ticks => ticks.to_string(),
+            TimeUnit::Seconds => format!("{:.6}", ticks as f64 / time_scale as f64),
+        }
+    }
+}
+
+/// Writes `samples` as comma-separated values with a header row
+///
+/// # Arguments
+///
+/// * `time_scale` - the media time scale of the samples
+/// * `unit` - the unit of DTS, CTS and duration
+pub fn write_csv<W: Write>(
+    w: &mut W,
+    samples: &[Sample],
+    time_scale: u32,
+    unit: TimeUnit,
+) -> io::Result<()> {
+    writeln!(w, "index,chunk,offset,size,dts,cts,duration,sync")?;
+
+    for sample in samples {
+        writeln!(
+            w,
+            "{},{},{},{},{},{},{},{}",
+            sample.index,
+            sample.chunk,
+            sample.offset,
+            sample.size,
+            unit.format(sample.decode_time as i64, time_scale),
+            unit.format(sample.composition_time, time_scale),
+            unit.format(sample.duration as i64, time_scale),
+            sample.is_sync as u8
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Writes `samples` as a table with a header row
+///
+/// # Arguments
+///
+/// * `time_scale` - the media time scale of the samples
+/// * `unit` - the unit of DTS, CTS and duration
+pub fn write_table<W: Write>(
+    w: &mut W,
+    samples: &[Sample],
+    time_scale: u32,
+    unit: TimeUnit,
+) -> io::Result<()> {
+    writeln!(
+        w,
+        "{:>8} {:>6} {:>12} {:>10} {:>14} {:>14} {:>12} sync",
+        "index", "chunk", "offset", "size", "dts", "cts", "duration"
+    )?;
+
+    for sample in samples {
+        writeln!(
+            w,
+            "{:>8} {:>6} {:>12} {:>10} {:>14} {:>14} {:>12} {}",
+            sample.index,
+            sample.chunk,
+            format!("0x{:x}", sample.offset),
+            sample.size,
+            unit.format(sample.decode_time as i64, time_scale),
+            unit.format(sample.composition_time, time_scale),
+            unit.format(sample.duration as i64, time_scale),
+            if sample.is_sync { "yes" } else { "no" }
+        )?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,226 @@
+use std::fmt;
+
+use crate::atom::ilst::DataAtom;
+use crate::atom::meta::MetaAtom;
+use crate::atom::pict::{self, PictAtom};
+use crate::atom::udta::{self, UdtaAtom, UserData};
+use crate::metadata;
+use crate::qtfile::{QtFile, TopLevelAtom};
+
+const COVER_KEYS: [&str; 2] = ["covr", "com.apple.quicktime.artwork"];
+
+/// The format of an image detected from its signature
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum ImageFormat {
+    Jpeg,
+    Png,
+    Bmp,
+    /// A QuickDraw picture
+    Pict,
+    Unknown,
+}
+
+impl ImageFormat {
+    /// Returns the format of the image in `data`
+    pub fn detect(data: &[u8]) -> Self {
+        if data.starts_with(&[0xff, 0xd8, 0xff]) {
+            ImageFormat::Jpeg
+        } else if data.starts_with(b"\x89PNG\r\n\x1a\n") {
+            ImageFormat::Png
+        } else if data.len() >= 14 && data.starts_with(b"BM") {
+            ImageFormat::Bmp
+        } else if matches!(data.get(10..14), Some([0x00, 0x11, 0x02, 0xff]))
+            || matches!(data.get(10..12), Some([0x11, 0x01]))
+        {
+            // the version opcode after the picture size and frame
+            ImageFormat::Pict
+        } else {
+            ImageFormat::Unknown
+        }
+    }
+
+    /// Returns the file name extension for the format
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ImageFormat::Jpeg => "jpg",
+            ImageFormat::Png => "png",
+            ImageFormat::Bmp => "bmp",
+            ImageFormat::Pict => "pict",
+            ImageFormat::Unknown => "bin",
+        }
+    }
+}
+
+impl fmt::Display for ImageFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            ImageFormat::Jpeg => "JPEG",
+            ImageFormat::Png => "PNG",
+            ImageFormat::Bmp => "BMP",
+            ImageFormat::Pict => "PICT",
+            ImageFormat::Unknown => "unknown",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// The atom an image was found in
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum ArtworkSource {
+    /// A 'covr' or `com.apple.quicktime.artwork` item in 'ilst'
+    Cover,
+    /// A 'thmb' entry in 'udta'
+    Thumbnail,
+    /// A top-level 'PICT' atom
+    Preview,
+}
+
+impl fmt::Display for ArtworkSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            ArtworkSource::Cover => "cover",
+            ArtworkSource::Thumbnail => "thumbnail",
+            ArtworkSource::Preview => "preview",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// An embedded image
+#[derive(Debug, PartialEq, Clone)]
+pub struct Artwork {
+    pub source: ArtworkSource,
+    pub format: ImageFormat,
+    /// The file offset of the image
+    pub offset: u64,
+    pub data: Vec<u8>,
+}
+
+impl fmt::Display for Artwork {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {} ({} bytes at 0x{:x})",
+            self.source,
+            self.format,
+            self.data.len(),
+            self.offset
+        )
+    }
+}
+
+/// Returns the image in a cover 'data' atom
+fn cover(data: &DataAtom) -> Artwork {
+    let format = match (ImageFormat::detect(&data.value), data.well_known_type()) {
+        (ImageFormat::Unknown, Some(13)) => ImageFormat::Jpeg,
+        (ImageFormat::Unknown, Some(14)) => ImageFormat::Png,
+        (ImageFormat::Unknown, Some(27)) => ImageFormat::Bmp,
+        (format, _) => format,
+    };
+
+    Artwork {
+        source: ArtworkSource::Cover,
+        format,
+        offset: data.atom_head.atom_offset + data.atom_head.atom_size - data.value.len() as u64,
+        data: data.value.clone(),
+    }
+}
+
+/// Returns the images in the cover items of `meta`
+fn covers(meta: &MetaAtom) -> Vec<Artwork> {
+    let ilst = match &meta.ilst_atom {
+        Some(ilst) => ilst,
+        None => return Vec::new(),
+    };
+
+    ilst.item_atom
+        .iter()
+        .filter(|item| COVER_KEYS.contains(&metadata::item_key(meta, item).as_str()))
+        .flat_map(|item| item.data_atom.iter().map(cover))
+        .collect()
+}
+
+/// Returns the images in the 'thmb' entries of `udta`
+///
+/// The image may follow the version and flags of a full box.
+fn thumbnails(udta: &UdtaAtom) -> Vec<Artwork> {
+    udta.user_data_atom
+        .iter()
+        .filter(|a| a.atom_head.atom_type == udta::THUMBNAIL)
+        .filter_map(|a| match &a.user_data {
+            UserData::Raw(data) => Some((a, data)),
+            UserData::Text(_) => None,
+        })
+        .map(|(a, data)| {
+            let is_full_box = ImageFormat::detect(data) == ImageFormat::Unknown
+                && ImageFormat::detect(data.get(4..).unwrap_or(&[])) != ImageFormat::Unknown;
+            let data = if is_full_box { &data[4..] } else { &data[..] };
+
+            Artwork {
+                source: ArtworkSource::Thumbnail,
+                format: ImageFormat::detect(data),
+                offset: a.atom_head.atom_offset + a.atom_head.atom_size - data.len() as u64,
+                data: data.to_vec(),
+            }
+        })
+        .collect()
+}
+
+/// Returns the image in a top-level 'PICT' atom
+fn preview(pict: &PictAtom) -> Artwork {
+    Artwork {
+        source: ArtworkSource::Preview,
+        format: ImageFormat::detect(&pict.data),
+        offset: pict.atom_head.atom_offset + pict.atom_head.atom_size - pict.data.len() as u64,
+        data: pict.data.clone(),
+    }
+}
+
+/// Returns the embedded images in the order of covers, thumbnails and previews
+///
+/// Previews are the 'PICT' atoms which 'pnot' refers to, or all of them
+/// without 'pnot'.
+pub fn artwork(qt: &QtFile) -> Vec<Artwork> {
+    let mut artwork = Vec::new();
+
+    if let Some(moov) = qt.moov() {
+        let udta = moov.udta_atom.as_deref();
+
+        for meta in moov
+            .meta_atom
+            .iter()
+            .chain(udta.and_then(|u| u.meta_atom.as_ref()))
+        {
+            artwork.extend(covers(meta));
+        }
+
+        let track_udta = moov.trak_atom.iter().filter_map(|t| t.udta_atom.as_deref());
+        for udta in udta.into_iter().chain(track_udta) {
+            artwork.extend(thumbnails(udta));
+        }
+    }
+
+    let picts = qt
+        .iter()
+        .filter_map(|a| match a {
+            TopLevelAtom::Pict(a) => Some(a),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    let references = qt
+        .iter()
+        .filter_map(|a| match a {
+            TopLevelAtom::Pnot(a) if a.preview_type == pict::ATOM_ID => Some(a.atom_index),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    let has_pnot = qt.iter().any(|a| matches!(a, TopLevelAtom::Pnot(_)));
+
+    for (i, pict) in picts.into_iter().enumerate() {
+        if !has_pnot || references.contains(&(i as u16 + 1)) {
+            artwork.push(preview(pict));
+        }
+    }
+
+    artwork
+}
@@ -0,0 +1,70 @@
+use std::fmt::Debug;
+use std::io::{Read, Seek, SeekFrom};
+
+use byteorder::{BigEndian, ReadBytesExt};
+
+use crate::atom::{Atom, AtomHead, AtomParseError};
+use atom_derive::atom;
+
+pub const ATOM_ID: u32 = 0x6368_706c; // 'chpl'
+
+/// The time scale of chapter start times
+pub const TIME_SCALE: u32 = 10_000_000;
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct ChapterListEntry {
+    /// The start in units of 100 nanoseconds
+    pub start_time: u64,
+    pub title: String,
+}
+
+/// A Nero chapter list in 'udta'
+#[atom(version)]
+#[derive(Debug, PartialEq)]
+pub struct ChplAtom {
+    pub chapter_list_table: Vec<ChapterListEntry>,
+}
+
+pub fn parse<R: Read + Seek>(r: &mut R, atom_head: AtomHead) -> Result<ChplAtom, AtomParseError> {
+    let atom_version = r.read_u8()?;
+    let mut atom_flags = [0_u8; 3];
+    r.read_exact(&mut atom_flags)?;
+
+    // version 1 has 4 more reserved bytes
+    if atom_version > 0 {
+        r.read_u32::<BigEndian>()?;
+    }
+
+    let atom_tail = atom_head.atom_offset + atom_head.atom_size;
+    let number_of_entries = r.read_u8()?;
+    let mut chapter_list_table = Vec::new();
+
+    for _ in 0..number_of_entries {
+        // an entry is the start time, the size of the title and the title
+        let entry_offset = r.seek(SeekFrom::Current(0))?;
+        if entry_offset + 9 > atom_tail {
+            return Err(AtomParseError::UnexpectedError(entry_offset));
+        }
+        let start_time = r.read_u64::<BigEndian>()?;
+        let size = r.read_u8()?;
+        if entry_offset + 9 + size as u64 > atom_tail {
+            return Err(AtomParseError::UnexpectedError(entry_offset));
+        }
+        let mut title = vec![0; size as usize];
+        r.read_exact(&mut title)?;
+
+        chapter_list_table.push(ChapterListEntry {
+            start_time,
+            title: String::from_utf8_lossy(&title).into_owned(),
+        });
+    }
+
+    r.seek(SeekFrom::Start(atom_head.atom_offset + atom_head.atom_size))?;
+
+    Ok(ChplAtom {
+        atom_head,
+        atom_version,
+        atom_flags,
+        chapter_list_table,
+    })
+}
@@ -0,0 +1,34 @@
+use std::fmt::Debug;
+use std::io::{Read, Seek, SeekFrom};
+
+use crate::atom::{self, Atom, AtomHead, AtomParseError};
+use atom_derive::atom;
+
+pub const ATOM_ID: u32 = 0x636c_6970; // 'clip'
+
+/// The clipping of a track
+#[atom]
+#[derive(Debug, PartialEq)]
+pub struct ClipAtom {
+    pub crgn_atom: Option<Box<atom::crgn::CrgnAtom>>,
+}
+
+pub fn parse<R: Read + Seek>(r: &mut R, atom_head: AtomHead) -> Result<ClipAtom, AtomParseError> {
+    let mut crgn_atom = None;
+
+    let atom_tail = atom_head.atom_offset + atom_head.atom_size;
+
+    while r.seek(SeekFrom::Current(0))? + 8 <= atom_tail {
+        let atom = atom::parse(r)?;
+        if let Ok(a) = atom.downcast::<atom::crgn::CrgnAtom>() {
+            crgn_atom = Some(a);
+        }
+    }
+
+    r.seek(SeekFrom::Start(atom_tail))?;
+
+    Ok(ClipAtom {
+        atom_head,
+        crgn_atom,
+    })
+}
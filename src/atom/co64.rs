@@ -0,0 +1,38 @@
+use std::fmt::Debug;
+use std::io::Read;
+
+use byteorder::{BigEndian, ReadBytesExt};
+
+use crate::atom::{Atom, AtomHead, AtomParseError};
+use atom_derive::atom;
+
+pub const ATOM_ID: u32 = 0x636f_3634; // 'co64'
+
+#[atom(version)]
+#[derive(Debug, PartialEq)]
+pub struct Co64Atom {
+    pub number_of_entries: u32,
+    pub chunk_offset_table: Vec<u64>,
+}
+
+pub fn parse<R: Read>(r: &mut R, atom_head: AtomHead) -> Result<Co64Atom, AtomParseError> {
+    let atom_version = r.read_u8()?;
+    let mut atom_flags = [0_u8; 3];
+    r.read_exact(&mut atom_flags)?;
+
+    let number_of_entries = r.read_u32::<BigEndian>()?;
+
+    let mut chunk_offset_table = Vec::new();
+
+    for _ in 0..number_of_entries {
+        chunk_offset_table.push(r.read_u64::<BigEndian>()?);
+    }
+
+    Ok(Co64Atom {
+        atom_head,
+        atom_version,
+        atom_flags,
+        number_of_entries,
+        chunk_offset_table,
+    })
+}
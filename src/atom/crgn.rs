@@ -0,0 +1,49 @@
+use std::fmt::Debug;
+use std::io::{Read, Seek, SeekFrom};
+
+use byteorder::{BigEndian, ReadBytesExt};
+
+use crate::atom::{Atom, AtomHead, AtomParseError};
+use crate::element;
+use atom_derive::atom;
+
+pub const ATOM_ID: u32 = 0x6372_676e; // 'crgn'
+
+/// The clipping region of a movie or a track as a QuickDraw region
+#[atom]
+#[derive(Debug, PartialEq)]
+pub struct CrgnAtom {
+    /// The size of the region including this field and the bounding box
+    pub region_size: u16,
+    pub region_bounding_box: element::qtfile_rect::QtFileRect,
+    /// The scan lines of a non-rectangular region
+    pub clipping_region_data: Vec<u8>,
+}
+
+impl CrgnAtom {
+    /// Returns whether the region is its bounding box
+    pub fn is_rectangular(&self) -> bool {
+        self.clipping_region_data.is_empty()
+    }
+}
+
+pub fn parse<R: Read + Seek>(r: &mut R, atom_head: AtomHead) -> Result<CrgnAtom, AtomParseError> {
+    let atom_tail = atom_head.atom_offset + atom_head.atom_size;
+
+    let region_size = r.read_u16::<BigEndian>()?;
+    let region_bounding_box = element::qtfile_rect::QtFileRect::parse(r)?;
+
+    let data_size = atom_tail.saturating_sub(r.seek(SeekFrom::Current(0))?);
+    let mut clipping_region_data = Vec::new();
+    r.take(data_size.min(region_size.saturating_sub(10) as u64))
+        .read_to_end(&mut clipping_region_data)?;
+
+    r.seek(SeekFrom::Start(atom_tail))?;
+
+    Ok(CrgnAtom {
+        atom_head,
+        region_size,
+        region_bounding_box,
+        clipping_region_data,
+    })
+}
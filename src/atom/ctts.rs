@@ -30,6 +30,16 @@ impl CompositionOffsetTableEntry {
     }
 }
 
+impl CttsAtom {
+    /// Returns the total number of samples described by the table
+    pub fn sample_count(&self) -> u64 {
+        self.composition_offset_table
+            .iter()
+            .map(|e| e.sample_count as u64)
+            .sum()
+    }
+}
+
 pub fn parse<R: Read>(r: &mut R, atom_head: AtomHead) -> Result<CttsAtom, AtomParseError> {
     let atom_version = r.read_u8()?;
     let mut atom_flags = [0_u8; 3];
@@ -8,31 +8,75 @@ use atom_derive::atom;
 
 pub const ATOM_ID: u32 = 0x6472_6566; // 'dref'
 
+const ALIS_ATOM_ID: u32 = 0x616c_6973; // 'alis'
+const RSRC_ATOM_ID: u32 = 0x7273_7263; // 'rsrc'
+const URL_ATOM_ID: u32 = 0x7572_6c20; // 'url '
+
 #[atom(version)]
 #[derive(Debug, PartialEq)]
 pub struct DrefAtom {
     pub number_of_entries: u32,
-    pub data_references: Vec<DataReferenceType>,
+    pub data_references: Vec<DataReferenceAtom>,
 }
 
 #[derive(Debug, PartialEq)]
 pub enum DataReferenceType {
-    MacintoshAlias {
-        atom_head: AtomHead,
-        information: String,
-    },
-    MacintoshAliasResource {
-        atom_head: AtomHead,
-        resource_type: i32,
-        resorce_id: i16,
-    },
-    Url {
-        atom_head: AtomHead,
-        url: String,
-    },
-    Unknown {
-        atom_head: AtomHead,
-    },
+    MacintoshAlias { information: String },
+    MacintoshAliasResource { resource_type: i32, resorce_id: i16 },
+    Url { url: String },
+    Unknown,
+}
+
+/// An entry in 'dref'
+///
+/// A self-contained entry refers to the file holding the movie and has no
+/// data of its own.
+#[atom(version(self_contained = 0x0001))]
+#[derive(Debug, PartialEq)]
+pub struct DataReferenceAtom {
+    pub data_reference: DataReferenceType,
+}
+
+fn parse_entry<R: Read + Seek>(
+    r: &mut R,
+    atom_head: AtomHead,
+) -> Result<DataReferenceAtom, AtomParseError> {
+    let atom_tail = atom_head.atom_offset + atom_head.atom_size;
+
+    let atom_version = r.read_u8()?;
+    let mut atom_flags = [0_u8; 3];
+    r.read_exact(&mut atom_flags)?;
+
+    let mut data = Vec::new();
+    let data_size = atom_tail.saturating_sub(r.seek(SeekFrom::Current(0))?);
+    r.take(data_size).read_to_end(&mut data)?;
+
+    let text = |data: &[u8]| {
+        String::from_utf8_lossy(data)
+            .trim_end_matches('\0')
+            .to_string()
+    };
+
+    let data_reference = match atom_head.atom_type {
+        ALIS_ATOM_ID => DataReferenceType::MacintoshAlias {
+            information: text(&data),
+        },
+        RSRC_ATOM_ID if data.len() >= 6 => DataReferenceType::MacintoshAliasResource {
+            resource_type: i32::from_be_bytes([data[0], data[1], data[2], data[3]]),
+            resorce_id: i16::from_be_bytes([data[4], data[5]]),
+        },
+        URL_ATOM_ID => DataReferenceType::Url { url: text(&data) },
+        _ => DataReferenceType::Unknown,
+    };
+
+    r.seek(SeekFrom::Start(atom_tail))?;
+
+    Ok(DataReferenceAtom {
+        atom_head,
+        atom_version,
+        atom_flags,
+        data_reference,
+    })
 }
 
 pub fn parse<R: Read + Seek>(r: &mut R, atom_head: AtomHead) -> Result<DrefAtom, AtomParseError> {
@@ -47,17 +91,7 @@ pub fn parse<R: Read + Seek>(r: &mut R, atom_head: AtomHead) -> Result<DrefAtom,
 
     for _ in 0..number_of_entries {
         let atom_head = atom::parse_atom_head(r)?;
-        match atom_head.atom_type {
-            0x7572_6c20 => {
-                let mut url = String::new();
-                r.take(atom_head.atom_size - 8).read_to_string(&mut url)?;
-                data_references.push(DataReferenceType::Url { atom_head, url })
-            }
-            _ => {
-                r.seek(SeekFrom::Start(atom_head.atom_offset + atom_head.atom_size))?;
-                data_references.push(DataReferenceType::Unknown { atom_head });
-            }
-        }
+        data_references.push(parse_entry(r, atom_head)?);
     }
 
     r.seek(SeekFrom::Start(atom_head.atom_offset + atom_head.atom_size))?;
@@ -2,7 +2,7 @@ use std::fmt::Debug;
 use std::io::{Read, Seek};
 
 use byteorder::{BigEndian, ReadBytesExt};
-use fixed::{types::extra::U16, FixedU32};
+use fixed::{types::extra::U16, FixedI32};
 
 use crate::atom::{Atom, AtomHead, AtomParseError};
 use atom_derive::atom;
@@ -18,9 +18,12 @@ pub struct ElstAtom {
 
 #[derive(Debug, PartialEq)]
 pub struct EditListTableEntry {
-    pub track_duration: u32,
-    pub media_time: u32,
-    pub media_rate: FixedU32<U16>,
+    /// The duration in the movie time scale, which is 64-bit in version 1
+    pub track_duration: u64,
+    /// The start time in the media, or -1 for an empty edit
+    pub media_time: i64,
+    /// The playback rate, which is negative to play backwards
+    pub media_rate: FixedI32<U16>,
 }
 
 pub fn parse<R: Read + Seek>(r: &mut R, atom_head: AtomHead) -> Result<ElstAtom, AtomParseError> {
@@ -33,9 +36,15 @@ pub fn parse<R: Read + Seek>(r: &mut R, atom_head: AtomHead) -> Result<ElstAtom,
     let mut edit_list_table = Vec::new();
 
     for _ in 0..number_of_entries {
-        let track_duration = r.read_u32::<BigEndian>()?;
-        let media_time = r.read_u32::<BigEndian>()?;
-        let media_rate = FixedU32::<U16>::from_bits(r.read_u32::<BigEndian>()?);
+        let (track_duration, media_time) = if atom_version == 1 {
+            (r.read_u64::<BigEndian>()?, r.read_i64::<BigEndian>()?)
+        } else {
+            (
+                r.read_u32::<BigEndian>()? as u64,
+                r.read_i32::<BigEndian>()? as i64,
+            )
+        };
+        let media_rate = FixedI32::<U16>::from_bits(r.read_i32::<BigEndian>()?);
 
         edit_list_table.push(EditListTableEntry {
             track_duration,
@@ -0,0 +1,39 @@
+use std::fmt::Debug;
+use std::io::{Read, Seek, SeekFrom};
+
+use crate::atom::{self, Atom, AtomHead, AtomParseError};
+use atom_derive::atom;
+
+pub const ATOM_ID: u32 = 0x676d_6864; // 'gmhd'
+
+#[atom]
+#[derive(Debug, PartialEq)]
+pub struct GmhdAtom {
+    pub gmin_atom: Option<Box<atom::gmin::GminAtom>>,
+    pub tmcd_atom: Option<Box<atom::tmcd::TmcdAtom>>,
+}
+
+pub fn parse<R: Read + Seek>(r: &mut R, atom_head: AtomHead) -> Result<GmhdAtom, AtomParseError> {
+    let mut gmin_atom: Option<Box<atom::gmin::GminAtom>> = None;
+    let mut tmcd_atom: Option<Box<atom::tmcd::TmcdAtom>> = None;
+
+    let atom_tail = atom_head.atom_offset + atom_head.atom_size;
+
+    while r.seek(SeekFrom::Current(0))? + 8 <= atom_tail {
+        let atom = atom::parse(r)?;
+
+        if atom.is::<atom::gmin::GminAtom>() {
+            gmin_atom = Some(atom.downcast::<atom::gmin::GminAtom>().unwrap()); // @todo
+        } else if atom.is::<atom::tmcd::TmcdAtom>() {
+            tmcd_atom = Some(atom.downcast::<atom::tmcd::TmcdAtom>().unwrap()); // @todo
+        }
+    }
+
+    r.seek(SeekFrom::Start(atom_tail))?;
+
+    Ok(GmhdAtom {
+        atom_head,
+        gmin_atom,
+        tmcd_atom,
+    })
+}
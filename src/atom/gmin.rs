@@ -0,0 +1,41 @@
+use std::fmt::Debug;
+use std::io::{Read, Seek};
+
+use byteorder::{BigEndian, ReadBytesExt};
+use fixed::{types::extra::U8, FixedI16};
+
+use crate::atom::{Atom, AtomHead, AtomParseError};
+use atom_derive::atom;
+
+pub const ATOM_ID: u32 = 0x676d_696e; // 'gmin'
+
+#[atom(version)]
+#[derive(Debug, PartialEq)]
+pub struct GminAtom {
+    pub graphics_mode: u16,
+    pub opcolor: [u16; 3],
+    pub balance: FixedI16<U8>,
+    pub reserved: u16,
+}
+
+pub fn parse<R: Read + Seek>(r: &mut R, atom_head: AtomHead) -> Result<GminAtom, AtomParseError> {
+    let atom_version = r.read_u8()?;
+    let mut atom_flags = [0_u8; 3];
+    r.read_exact(&mut atom_flags)?;
+
+    let graphics_mode = r.read_u16::<BigEndian>()?;
+    let mut opcolor = [0_u16; 3];
+    r.read_u16_into::<BigEndian>(&mut opcolor)?;
+    let balance = FixedI16::<U8>::from_bits(r.read_i16::<BigEndian>()?);
+    let reserved = r.read_u16::<BigEndian>()?;
+
+    Ok(GminAtom {
+        atom_head,
+        atom_version,
+        atom_flags,
+        graphics_mode,
+        opcolor,
+        balance,
+        reserved,
+    })
+}
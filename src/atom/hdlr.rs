@@ -36,7 +36,7 @@ impl ComponentType {
     }
 }
 
-#[derive(PartialEq, Debug)]
+#[derive(PartialEq, Debug, Clone)]
 pub enum ComponentSubType {
     VideoMedia,
     SoundMedia,
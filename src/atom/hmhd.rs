@@ -0,0 +1,42 @@
+use std::fmt::Debug;
+use std::io::{Read, Seek};
+
+use byteorder::{BigEndian, ReadBytesExt};
+
+use crate::atom::{Atom, AtomHead, AtomParseError};
+use atom_derive::atom;
+
+pub const ATOM_ID: u32 = 0x686d_6864; // 'hmhd'
+
+#[atom(version)]
+#[derive(Debug, PartialEq)]
+pub struct HmhdAtom {
+    pub max_pdu_size: u16,
+    pub avg_pdu_size: u16,
+    pub max_bitrate: u32,
+    pub avg_bitrate: u32,
+    pub reserved: u32,
+}
+
+pub fn parse<R: Read + Seek>(r: &mut R, atom_head: AtomHead) -> Result<HmhdAtom, AtomParseError> {
+    let atom_version = r.read_u8()?;
+    let mut atom_flags = [0_u8; 3];
+    r.read_exact(&mut atom_flags)?;
+
+    let max_pdu_size = r.read_u16::<BigEndian>()?;
+    let avg_pdu_size = r.read_u16::<BigEndian>()?;
+    let max_bitrate = r.read_u32::<BigEndian>()?;
+    let avg_bitrate = r.read_u32::<BigEndian>()?;
+    let reserved = r.read_u32::<BigEndian>()?;
+
+    Ok(HmhdAtom {
+        atom_head,
+        atom_version,
+        atom_flags,
+        max_pdu_size,
+        avg_pdu_size,
+        max_bitrate,
+        avg_bitrate,
+        reserved,
+    })
+}
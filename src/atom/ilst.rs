@@ -0,0 +1,131 @@
+use std::fmt::Debug;
+use std::io::{Read, Seek, SeekFrom};
+
+use byteorder::{BigEndian, ReadBytesExt};
+
+use crate::atom::{self, Atom, AtomHead, AtomParseError};
+use atom_derive::atom;
+
+pub const ATOM_ID: u32 = 0x696c_7374; // 'ilst'
+
+const DATA_ATOM_ID: u32 = 0x6461_7461; // 'data'
+const MEAN_ATOM_ID: u32 = 0x6d65_616e; // 'mean'
+const NAME_ATOM_ID: u32 = 0x6e61_6d65; // 'name'
+
+/// The type of an item holding its key in 'mean' and 'name'
+pub const FREEFORM: u32 = 0x2d2d_2d2d; // '----'
+
+/// A value in 'ilst'
+#[atom]
+#[derive(Debug, PartialEq)]
+pub struct DataAtom {
+    /// The type set in the high byte and the type in the others
+    pub type_indicator: u32,
+    pub locale: u32,
+    pub value: Vec<u8>,
+}
+
+impl DataAtom {
+    /// Returns the type if it is in the well-known type set
+    pub fn well_known_type(&self) -> Option<u32> {
+        if self.type_indicator >> 24 == 0 {
+            Some(self.type_indicator & 0x00ff_ffff)
+        } else {
+            None
+        }
+    }
+}
+
+/// An item whose type is a 1-based index into 'keys' or a FourCC key
+#[atom]
+#[derive(Debug, PartialEq)]
+pub struct ItemAtom {
+    /// The reverse DNS domain of a [`FREEFORM`] item
+    pub mean: Option<String>,
+    /// The name of a [`FREEFORM`] item
+    pub name: Option<String>,
+    pub data_atom: Vec<DataAtom>,
+}
+
+#[atom]
+#[derive(Debug, PartialEq)]
+pub struct IlstAtom {
+    pub item_atom: Vec<ItemAtom>,
+}
+
+/// Returns the string after the version and flags of 'mean' or 'name'
+fn read_full_box_string<R: Read>(r: &mut R, size: u64) -> Result<String, AtomParseError> {
+    let mut data = Vec::new();
+    r.take(size).read_to_end(&mut data)?;
+
+    Ok(String::from_utf8_lossy(data.get(4..).unwrap_or(&[])).into_owned())
+}
+
+fn parse_item<R: Read + Seek>(r: &mut R, atom_head: AtomHead) -> Result<ItemAtom, AtomParseError> {
+    let mut mean = None;
+    let mut name = None;
+    let mut data_atom = Vec::new();
+
+    let atom_tail = atom_head.atom_offset + atom_head.atom_size;
+
+    while r.seek(SeekFrom::Current(0))? + 8 <= atom_tail {
+        let head = atom::parse_atom_head(r)?;
+        let head_tail = head.atom_offset + head.atom_size;
+        if head.atom_size < 8 || head_tail > atom_tail {
+            return Err(AtomParseError::UnexpectedError(head.atom_offset));
+        }
+        let remain = head_tail - r.seek(SeekFrom::Current(0))?;
+
+        match head.atom_type {
+            DATA_ATOM_ID if remain >= 8 => {
+                let type_indicator = r.read_u32::<BigEndian>()?;
+                let locale = r.read_u32::<BigEndian>()?;
+                let mut value = Vec::new();
+                r.take(remain - 8).read_to_end(&mut value)?;
+
+                data_atom.push(DataAtom {
+                    atom_head: head,
+                    type_indicator,
+                    locale,
+                    value,
+                });
+            }
+            MEAN_ATOM_ID => mean = Some(read_full_box_string(r, remain)?),
+            NAME_ATOM_ID => name = Some(read_full_box_string(r, remain)?),
+            _ => {}
+        }
+
+        r.seek(SeekFrom::Start(head_tail))?;
+    }
+
+    Ok(ItemAtom {
+        atom_head,
+        mean,
+        name,
+        data_atom,
+    })
+}
+
+pub fn parse<R: Read + Seek>(r: &mut R, atom_head: AtomHead) -> Result<IlstAtom, AtomParseError> {
+    let mut item_atom = Vec::new();
+
+    let atom_tail = atom_head.atom_offset + atom_head.atom_size;
+
+    while r.seek(SeekFrom::Current(0))? + 8 <= atom_tail {
+        let head = atom::parse_atom_head(r)?;
+        let head_tail = head.atom_offset + head.atom_size;
+        if head.atom_size < 8 || head_tail > atom_tail {
+            return Err(AtomParseError::UnexpectedError(head.atom_offset));
+        }
+
+        item_atom.push(parse_item(r, head)?);
+        r.seek(SeekFrom::Start(head_tail))?;
+    }
+
+    r.seek(SeekFrom::Start(atom_tail))?;
+
+    Ok(IlstAtom {
+        atom_head,
+        item_atom,
+    })
+}
@@ -0,0 +1,146 @@
+use std::fmt::Debug;
+use std::io::{Read, Seek, SeekFrom};
+
+use byteorder::{BigEndian, ReadBytesExt};
+
+use crate::atom::{self, Atom, AtomHead, AtomParseError};
+use atom_derive::atom;
+
+pub const ATOM_ID: u32 = 0x696d_6170; // 'imap'
+
+const TRACK_INPUT_ATOM_ID: u32 = 0x0000_696e; // '\0\0in'
+const INPUT_TYPE_ATOM_ID: u32 = 0x0000_7479; // '\0\0ty'
+const OBJECT_ID_ATOM_ID: u32 = 0x6f62_6964; // 'obid'
+
+/// The size of the atom ID, the child count and the reserved fields which
+/// follow the head of a QT atom
+const QT_ATOM_HEADER_SIZE: u64 = 12;
+
+/// What a modifier track changes in the track receiving its input
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum InputType {
+    Matrix,
+    Clip,
+    Volume,
+    Balance,
+    GraphicsMode,
+    /// The matrix of an object in a sprite or 3D track
+    ObjectMatrix,
+    /// The graphics mode of an object in a sprite or 3D track
+    ObjectGraphicsMode,
+    /// The image of a sprite
+    Image,
+    Unknown(u32),
+}
+
+impl InputType {
+    pub fn new(t: u32) -> Self {
+        match t {
+            1 => InputType::Matrix,
+            2 => InputType::Clip,
+            3 => InputType::Volume,
+            4 => InputType::Balance,
+            5 => InputType::GraphicsMode,
+            6 => InputType::ObjectMatrix,
+            7 => InputType::ObjectGraphicsMode,
+            0x7669_6465 => InputType::Image, // 'vide'
+            _ => InputType::Unknown(t),
+        }
+    }
+}
+
+/// An input which a track receives from a modifier track
+#[atom]
+#[derive(Debug, PartialEq)]
+pub struct TrackInputAtom {
+    /// The 1-based index of the modifier track reference in 'tref'
+    pub atom_id: u32,
+    pub input_type: Option<InputType>,
+    /// The sprite or 3D object the input applies to
+    pub object_id: Option<u32>,
+}
+
+/// The inputs of a track from its modifier tracks
+#[atom]
+#[derive(Debug, PartialEq)]
+pub struct ImapAtom {
+    pub track_input_atom: Vec<TrackInputAtom>,
+}
+
+/// Returns the head of the QT atom at the current position of `r` and its ID,
+/// or an error if the atom does not fit in `parent_tail`
+fn parse_qt_atom_head<R: Read + Seek>(
+    r: &mut R,
+    parent_tail: u64,
+) -> Result<(AtomHead, u32), AtomParseError> {
+    let head = atom::parse_atom_head(r)?;
+    if head.atom_size < 8 + QT_ATOM_HEADER_SIZE || head.atom_offset + head.atom_size > parent_tail {
+        return Err(AtomParseError::UnexpectedError(head.atom_offset));
+    }
+
+    let atom_id = r.read_u32::<BigEndian>()?;
+    let _reserved = r.read_u16::<BigEndian>()?;
+    let _child_count = r.read_u16::<BigEndian>()?;
+    let _reserved = r.read_u32::<BigEndian>()?;
+
+    Ok((head, atom_id))
+}
+
+fn parse_track_input<R: Read + Seek>(
+    r: &mut R,
+    atom_head: AtomHead,
+    atom_id: u32,
+) -> Result<TrackInputAtom, AtomParseError> {
+    let mut input_type = None;
+    let mut object_id = None;
+
+    let atom_tail = atom_head.atom_offset + atom_head.atom_size;
+
+    while r.seek(SeekFrom::Current(0))? + 8 + QT_ATOM_HEADER_SIZE <= atom_tail {
+        let (head, _) = parse_qt_atom_head(r, atom_tail)?;
+        let has_value = head.atom_size >= 8 + QT_ATOM_HEADER_SIZE + 4;
+
+        match head.atom_type {
+            INPUT_TYPE_ATOM_ID if has_value => {
+                input_type = Some(InputType::new(r.read_u32::<BigEndian>()?))
+            }
+            OBJECT_ID_ATOM_ID if has_value => object_id = Some(r.read_u32::<BigEndian>()?),
+            _ => {}
+        }
+
+        r.seek(SeekFrom::Start(head.atom_offset + head.atom_size))?;
+    }
+
+    r.seek(SeekFrom::Start(atom_tail))?;
+
+    Ok(TrackInputAtom {
+        atom_head,
+        atom_id,
+        input_type,
+        object_id,
+    })
+}
+
+pub fn parse<R: Read + Seek>(r: &mut R, atom_head: AtomHead) -> Result<ImapAtom, AtomParseError> {
+    let mut track_input_atom = Vec::new();
+
+    let atom_tail = atom_head.atom_offset + atom_head.atom_size;
+
+    while r.seek(SeekFrom::Current(0))? + 8 + QT_ATOM_HEADER_SIZE <= atom_tail {
+        let (head, atom_id) = parse_qt_atom_head(r, atom_tail)?;
+        let head_tail = head.atom_offset + head.atom_size;
+
+        if head.atom_type == TRACK_INPUT_ATOM_ID {
+            track_input_atom.push(parse_track_input(r, head, atom_id)?);
+        }
+
+        r.seek(SeekFrom::Start(head_tail))?;
+    }
+
+    r.seek(SeekFrom::Start(atom_tail))?;
+
+    Ok(ImapAtom {
+        atom_head,
+        track_input_atom,
+    })
+}
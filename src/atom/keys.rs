@@ -0,0 +1,68 @@
+use std::fmt::Debug;
+use std::io::{Read, Seek, SeekFrom};
+
+use byteorder::{BigEndian, ReadBytesExt};
+
+use crate::atom::{Atom, AtomHead, AtomParseError};
+use atom_derive::atom;
+
+pub const ATOM_ID: u32 = 0x6b65_7973; // 'keys'
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Key {
+    /// Such as 'mdta' for reverse DNS keys
+    pub key_namespace: u32,
+    pub key_value: String,
+}
+
+#[atom(version)]
+#[derive(Debug, PartialEq)]
+pub struct KeysAtom {
+    pub entry_count: u32,
+    pub key_table: Vec<Key>,
+}
+
+impl KeysAtom {
+    /// Returns the key which 'ilst' refers to by the 1-based `index`
+    pub fn key(&self, index: u32) -> Option<&Key> {
+        self.key_table.get((index as usize).checked_sub(1)?)
+    }
+}
+
+pub fn parse<R: Read + Seek>(r: &mut R, atom_head: AtomHead) -> Result<KeysAtom, AtomParseError> {
+    let atom_version = r.read_u8()?;
+    let mut atom_flags = [0_u8; 3];
+    r.read_exact(&mut atom_flags)?;
+
+    let entry_count = r.read_u32::<BigEndian>()?;
+
+    let atom_tail = atom_head.atom_offset + atom_head.atom_size;
+    let mut key_table = Vec::new();
+
+    for _ in 0..entry_count {
+        let offset = r.seek(SeekFrom::Current(0))?;
+        let key_size = r.read_u32::<BigEndian>()? as u64;
+        if key_size < 8 || offset + key_size > atom_tail {
+            return Err(AtomParseError::UnexpectedError(offset));
+        }
+        let key_namespace = r.read_u32::<BigEndian>()?;
+
+        let mut key_value = Vec::new();
+        r.take(key_size - 8).read_to_end(&mut key_value)?;
+
+        key_table.push(Key {
+            key_namespace,
+            key_value: String::from_utf8_lossy(&key_value).into_owned(),
+        });
+    }
+
+    r.seek(SeekFrom::Start(atom_tail))?;
+
+    Ok(KeysAtom {
+        atom_head,
+        atom_version,
+        atom_flags,
+        entry_count,
+        key_table,
+    })
+}
@@ -0,0 +1,43 @@
+use std::fmt::Debug;
+use std::io::{Read, Seek, SeekFrom};
+
+use byteorder::ReadBytesExt;
+
+use crate::atom::stsd::SampleDescription;
+use crate::atom::{Atom, AtomHead, AtomParseError};
+use atom_derive::atom;
+
+pub const ATOM_ID: u32 = 0x6b6d_6174; // 'kmat'
+
+/// The matte of a track, which blends the track into the movie
+#[atom(version)]
+#[derive(Debug, PartialEq)]
+pub struct KmatAtom {
+    /// The image description of `matte_data`
+    pub matte_image_description: SampleDescription,
+    pub matte_data: Vec<u8>,
+}
+
+pub fn parse<R: Read + Seek>(r: &mut R, atom_head: AtomHead) -> Result<KmatAtom, AtomParseError> {
+    let atom_tail = atom_head.atom_offset + atom_head.atom_size;
+
+    let atom_version = r.read_u8()?;
+    let mut atom_flags = [0_u8; 3];
+    r.read_exact(&mut atom_flags)?;
+
+    let matte_image_description = SampleDescription::parse(r)?;
+
+    let data_size = atom_tail.saturating_sub(r.seek(SeekFrom::Current(0))?);
+    let mut matte_data = Vec::new();
+    r.take(data_size).read_to_end(&mut matte_data)?;
+
+    r.seek(SeekFrom::Start(atom_tail))?;
+
+    Ok(KmatAtom {
+        atom_head,
+        atom_version,
+        atom_flags,
+        matte_image_description,
+        matte_data,
+    })
+}
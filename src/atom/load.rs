@@ -0,0 +1,68 @@
+use std::fmt::Debug;
+use std::io::{Read, Seek, SeekFrom};
+
+use byteorder::{BigEndian, ReadBytesExt};
+
+use crate::atom::{Atom, AtomHead, AtomParseError};
+use atom_derive::atom;
+
+pub const ATOM_ID: u32 = 0x6c6f_6164; // 'load'
+
+/// The preload settings of a track
+#[atom]
+#[derive(Debug, PartialEq)]
+pub struct LoadAtom {
+    /// The start of the span to preload in the movie time scale
+    pub preload_start_time: u32,
+    /// The duration of the span to preload, or -1 for the whole track
+    pub preload_duration: i32,
+    pub preload_flags: u32,
+    /// The playback hints of the track
+    pub default_hints: u32,
+}
+
+impl LoadAtom {
+    pub const PRELOAD_ALWAYS: u32 = 0x0001;
+    pub const TRACK_ENABLED_PRELOAD: u32 = 0x0002;
+
+    pub const DOUBLE_BUFFER: u32 = 0x0020;
+    pub const HIGH_QUALITY: u32 = 0x0100;
+
+    /// Returns whether the track is preloaded regardless of whether it is
+    /// enabled
+    pub fn is_preload_always(&self) -> bool {
+        self.preload_flags & Self::PRELOAD_ALWAYS != 0
+    }
+
+    /// Returns whether the track is preloaded only if it is enabled
+    pub fn is_track_enabled_preload(&self) -> bool {
+        self.preload_flags & Self::TRACK_ENABLED_PRELOAD != 0
+    }
+
+    /// Returns whether the track is played with double buffering
+    pub fn is_double_buffer(&self) -> bool {
+        self.default_hints & Self::DOUBLE_BUFFER != 0
+    }
+
+    /// Returns whether the track is played at high quality
+    pub fn is_high_quality(&self) -> bool {
+        self.default_hints & Self::HIGH_QUALITY != 0
+    }
+}
+
+pub fn parse<R: Read + Seek>(r: &mut R, atom_head: AtomHead) -> Result<LoadAtom, AtomParseError> {
+    let preload_start_time = r.read_u32::<BigEndian>()?;
+    let preload_duration = r.read_i32::<BigEndian>()?;
+    let preload_flags = r.read_u32::<BigEndian>()?;
+    let default_hints = r.read_u32::<BigEndian>()?;
+
+    r.seek(SeekFrom::Start(atom_head.atom_offset + atom_head.atom_size))?;
+
+    Ok(LoadAtom {
+        atom_head,
+        preload_start_time,
+        preload_duration,
+        preload_flags,
+        default_hints,
+    })
+}
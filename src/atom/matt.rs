@@ -0,0 +1,33 @@
+use std::fmt::Debug;
+use std::io::{Read, Seek, SeekFrom};
+
+use crate::atom::{self, Atom, AtomHead, AtomParseError};
+use atom_derive::atom;
+
+pub const ATOM_ID: u32 = 0x6d61_7474; // 'matt'
+
+#[atom]
+#[derive(Debug, PartialEq)]
+pub struct MattAtom {
+    pub kmat_atom: Option<Box<atom::kmat::KmatAtom>>,
+}
+
+pub fn parse<R: Read + Seek>(r: &mut R, atom_head: AtomHead) -> Result<MattAtom, AtomParseError> {
+    let mut kmat_atom = None;
+
+    let atom_tail = atom_head.atom_offset + atom_head.atom_size;
+
+    while r.seek(SeekFrom::Current(0))? + 8 <= atom_tail {
+        let atom = atom::parse(r)?;
+        if let Ok(a) = atom.downcast::<atom::kmat::KmatAtom>() {
+            kmat_atom = Some(a);
+        }
+    }
+
+    r.seek(SeekFrom::Start(atom_tail))?;
+
+    Ok(MattAtom {
+        atom_head,
+        kmat_atom,
+    })
+}
@@ -8,9 +8,27 @@ pub const ATOM_ID: u32 = 0x6d64_6174; // 'mdat'
 
 #[atom]
 #[derive(Debug, PartialEq)]
-pub struct MdatAtom {}
+pub struct MdatAtom {
+    /// The size of the header, which is 16 with the extended size field
+    pub header_size: u64,
+}
+
+impl MdatAtom {
+    /// Returns the range (head, tail) of the payload in the file
+    pub fn data_range(&self) -> (u64, u64) {
+        let head = &self.atom_head;
+        let tail = head.atom_offset + head.atom_size;
+
+        ((head.atom_offset + self.header_size).min(tail), tail)
+    }
+}
 
 pub fn parse<R: Read + Seek>(r: &mut R, atom_head: AtomHead) -> Result<MdatAtom, AtomParseError> {
+    let header_size = r.seek(SeekFrom::Current(0))? - atom_head.atom_offset;
     r.seek(SeekFrom::Start(atom_head.atom_offset + atom_head.atom_size))?;
-    Ok(MdatAtom { atom_head })
+
+    Ok(MdatAtom {
+        atom_head,
+        header_size,
+    })
 }
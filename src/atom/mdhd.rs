@@ -1,17 +1,60 @@
 use std::fmt::Debug;
 use std::io::{Read, Seek, SeekFrom};
 
+use byteorder::{BigEndian, ReadBytesExt};
+
 use crate::atom::{Atom, AtomHead, AtomParseError};
+use crate::element;
 use atom_derive::atom;
 
 pub const ATOM_ID: u32 = 0x6d64_6864; // 'mdhd'
 
-#[atom]
+#[atom(version)]
 #[derive(Debug, PartialEq)]
-pub struct MdhdAtom {}
+pub struct MdhdAtom {
+    pub creation_time: element::qtfile_datetime::QtFileDateTime,
+    pub modification_time: element::qtfile_datetime::QtFileDateTime,
+    pub time_scale: u32,
+    /// The duration in `time_scale`, which is 64-bit in version 1
+    pub duration: u64,
+    pub language: u16,
+    pub quality: u16,
+}
 
 pub fn parse<R: Read + Seek>(r: &mut R, atom_head: AtomHead) -> Result<MdhdAtom, AtomParseError> {
+    let atom_version = r.read_u8()?;
+    let mut atom_flags = [0_u8; 3];
+    r.read_exact(&mut atom_flags)?;
+
+    let (creation_time, modification_time, time_scale, duration) = if atom_version == 1 {
+        (
+            element::qtfile_datetime::QtFileDateTime::from_u64(r.read_u64::<BigEndian>()?),
+            element::qtfile_datetime::QtFileDateTime::from_u64(r.read_u64::<BigEndian>()?),
+            r.read_u32::<BigEndian>()?,
+            r.read_u64::<BigEndian>()?,
+        )
+    } else {
+        (
+            element::qtfile_datetime::QtFileDateTime::new(r.read_u32::<BigEndian>()?),
+            element::qtfile_datetime::QtFileDateTime::new(r.read_u32::<BigEndian>()?),
+            r.read_u32::<BigEndian>()?,
+            r.read_u32::<BigEndian>()? as u64,
+        )
+    };
+    let language = r.read_u16::<BigEndian>()?;
+    let quality = r.read_u16::<BigEndian>()?;
+
     r.seek(SeekFrom::Start(atom_head.atom_offset + atom_head.atom_size))?;
 
-    Ok(MdhdAtom { atom_head })
+    Ok(MdhdAtom {
+        atom_head,
+        atom_version,
+        atom_flags,
+        creation_time,
+        modification_time,
+        time_scale,
+        duration,
+        language,
+        quality,
+    })
 }
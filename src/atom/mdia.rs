@@ -21,7 +21,9 @@ pub fn parse<R: Read + Seek>(r: &mut R, atom_head: AtomHead) -> Result<MdiaAtom,
 
     let atom_tail = atom_head.atom_offset + atom_head.atom_size;
 
-    while let Ok(atom) = atom::parse(r) {
+    while r.seek(SeekFrom::Current(0))? + 8 <= atom_tail {
+        let atom = atom::parse(r)?;
+
         if atom.is::<atom::mdhd::MdhdAtom>() {
             mdhd_atom = Some(atom.downcast::<atom::mdhd::MdhdAtom>().unwrap()); // @todo
         } else if atom.is::<atom::hdlr::HdlrAtom>() {
@@ -31,10 +33,6 @@ pub fn parse<R: Read + Seek>(r: &mut R, atom_head: AtomHead) -> Result<MdiaAtom,
         } else {
             eprintln!("{:?}", atom);
         }
-
-        if r.seek(SeekFrom::Current(0))? >= atom_tail {
-            break;
-        }
     }
 
     let mdhd_atom = match mdhd_atom {
@@ -0,0 +1,80 @@
+use std::fmt::Debug;
+use std::io::{Read, Seek, SeekFrom};
+
+use byteorder::{BigEndian, ReadBytesExt};
+
+use crate::atom::{self, Atom, AtomHead, AtomParseError};
+use atom_derive::atom;
+
+pub const ATOM_ID: u32 = 0x6d65_7461; // 'meta'
+
+/// Metadata with 'keys' in QuickTime files, or with FourCC keys in 'udta'
+#[atom]
+#[derive(Debug, PartialEq)]
+pub struct MetaAtom {
+    /// The version and flags of the ISO full box form, which QuickTime omits
+    pub atom_version_and_flags: Option<u32>,
+    pub hdlr_atom: Option<Box<atom::hdlr::HdlrAtom>>,
+    pub keys_atom: Option<Box<atom::keys::KeysAtom>>,
+    pub ilst_atom: Option<Box<atom::ilst::IlstAtom>>,
+}
+
+/// Returns the version and flags of the full box form at the current
+/// position of `r`, leaving `r` at the first child
+///
+/// # Arguments
+///
+/// * `atom_tail` - the end of the 'meta' atom
+pub(crate) fn parse_version_and_flags<R: Read + Seek>(
+    r: &mut R,
+    atom_tail: u64,
+) -> Result<Option<u32>, AtomParseError> {
+    let body = r.seek(SeekFrom::Current(0))?;
+
+    // the full box form starts with version and flags instead of the size
+    // of 'hdlr'
+    if body + 8 <= atom_tail {
+        let first = r.read_u32::<BigEndian>()?;
+        let second = r.read_u32::<BigEndian>()?;
+
+        if second == atom::hdlr::ATOM_ID {
+            r.seek(SeekFrom::Start(body))?;
+        } else {
+            r.seek(SeekFrom::Start(body + 4))?;
+            return Ok(Some(first));
+        }
+    }
+
+    Ok(None)
+}
+
+pub fn parse<R: Read + Seek>(r: &mut R, atom_head: AtomHead) -> Result<MetaAtom, AtomParseError> {
+    let mut hdlr_atom: Option<Box<atom::hdlr::HdlrAtom>> = None;
+    let mut keys_atom: Option<Box<atom::keys::KeysAtom>> = None;
+    let mut ilst_atom: Option<Box<atom::ilst::IlstAtom>> = None;
+
+    let atom_tail = atom_head.atom_offset + atom_head.atom_size;
+    let atom_version_and_flags = parse_version_and_flags(r, atom_tail)?;
+
+    while r.seek(SeekFrom::Current(0))? + 8 <= atom_tail {
+        let atom = atom::parse(r)?;
+
+        if atom.is::<atom::hdlr::HdlrAtom>() {
+            hdlr_atom = Some(atom.downcast::<atom::hdlr::HdlrAtom>().unwrap()); // @todo
+        } else if atom.is::<atom::keys::KeysAtom>() {
+            keys_atom = Some(atom.downcast::<atom::keys::KeysAtom>().unwrap()); // @todo
+        } else if atom.is::<atom::ilst::IlstAtom>() {
+            ilst_atom = Some(atom.downcast::<atom::ilst::IlstAtom>().unwrap()); // @todo
+        }
+    }
+
+    r.seek(SeekFrom::Start(atom_tail))?;
+
+    Ok(MetaAtom {
+        atom_head,
+        atom_version_and_flags,
+        hdlr_atom,
+        keys_atom,
+        ilst_atom,
+    })
+}
@@ -10,79 +10,274 @@ pub const ATOM_ID: u32 = 0x6d69_6e66; // 'minf'
 #[atom]
 #[derive(Debug, PartialEq)]
 pub struct MinfAtom {
+    #[children]
     pub media_info: MediaInfo,
+    /// Children other than the media header, 'hdlr', 'dinf' and 'stbl'
+    pub unknown_atoms: Vec<atom::UnimplementedAtom>,
 }
 
 #[derive(Debug, PartialEq)]
 pub enum MediaInfo {
     VideoMediaInfo {
         vmhd_atom: Box<atom::vmhd::VmhdAtom>,
-        hdlr_atom: Box<atom::hdlr::HdlrAtom>,
-        dinf_atom: Option<Box<atom::dinf::DinfAtom>>,
-        stbl_atom: Option<Box<atom::stbl::StblAtom>>,
+        hdlr_atom: Option<Box<atom::hdlr::HdlrAtom>>,
+        dinf_atom: Box<atom::dinf::DinfAtom>,
+        stbl_atom: Box<atom::stbl::StblAtom>,
     },
     SoundMediaInfo {
         smhd_atom: Box<atom::smhd::SmhdAtom>,
+        hdlr_atom: Option<Box<atom::hdlr::HdlrAtom>>,
+        dinf_atom: Box<atom::dinf::DinfAtom>,
+        stbl_atom: Box<atom::stbl::StblAtom>,
+    },
+    /// Text, timecode, metadata and other QuickTime media with 'gmhd'
+    BaseMediaInfo {
+        gmhd_atom: Box<atom::gmhd::GmhdAtom>,
+        hdlr_atom: Option<Box<atom::hdlr::HdlrAtom>>,
+        dinf_atom: Box<atom::dinf::DinfAtom>,
+        stbl_atom: Box<atom::stbl::StblAtom>,
+    },
+    NullMediaInfo {
+        nmhd_atom: Box<atom::nmhd::NmhdAtom>,
+        hdlr_atom: Option<Box<atom::hdlr::HdlrAtom>>,
+        dinf_atom: Box<atom::dinf::DinfAtom>,
+        stbl_atom: Box<atom::stbl::StblAtom>,
+    },
+    HintMediaInfo {
+        hmhd_atom: Box<atom::hmhd::HmhdAtom>,
+        hdlr_atom: Option<Box<atom::hdlr::HdlrAtom>>,
+        dinf_atom: Box<atom::dinf::DinfAtom>,
+        stbl_atom: Box<atom::stbl::StblAtom>,
+    },
+    SubtitleMediaInfo {
+        sthd_atom: Box<atom::sthd::SthdAtom>,
+        hdlr_atom: Option<Box<atom::hdlr::HdlrAtom>>,
+        dinf_atom: Box<atom::dinf::DinfAtom>,
+        stbl_atom: Box<atom::stbl::StblAtom>,
+    },
+    /// Media whose header is not recognized
+    Unknown {
+        hdlr_atom: Option<Box<atom::hdlr::HdlrAtom>>,
+        dinf_atom: Box<atom::dinf::DinfAtom>,
+        stbl_atom: Box<atom::stbl::StblAtom>,
     },
-    Unknown,
 }
 
-pub fn parse<R: Read + Seek>(r: &mut R, atom_head: AtomHead) -> Result<MinfAtom, AtomParseError> {
-    let atom_tail = atom_head.atom_offset + atom_head.atom_size;
-
-    let media_info = if let Ok(atom) = atom::parse(r) {
-        if atom.is::<atom::vmhd::VmhdAtom>() {
-            let vmhd_atom = atom.downcast::<atom::vmhd::VmhdAtom>().unwrap(); // @todo
-            let hdlr_atom = if let Ok(atom) = atom::parse(r) {
-                if atom.is::<atom::hdlr::HdlrAtom>() {
-                    atom.downcast::<atom::hdlr::HdlrAtom>().unwrap() // @todo
-                } else {
-                    return Err(AtomParseError::RequiredAtomNotFound(atom::hdlr::ATOM_ID));
-                }
-            } else {
-                return Err(AtomParseError::RequiredAtomNotFound(atom::hdlr::ATOM_ID));
-            };
-
-            let mut dinf_atom: Option<Box<atom::dinf::DinfAtom>> = None;
-            let mut stbl_atom: Option<Box<atom::stbl::StblAtom>> = None;
-
-            while let Ok(atom) = atom::parse(r) {
-                if atom.is::<atom::dinf::DinfAtom>() {
-                    // @todo
-                    dinf_atom = Some(atom.downcast::<atom::dinf::DinfAtom>().unwrap());
-                } else if atom.is::<atom::stbl::StblAtom>() {
-                    // @todo
-                    stbl_atom = Some(atom.downcast::<atom::stbl::StblAtom>().unwrap());
-                } else {
-                    dbg!(&atom);
-                }
-
-                if r.seek(SeekFrom::Current(0))? >= atom_tail {
-                    break;
-                }
-            }
-
+impl MediaInfo {
+    /// Returns the media header, and the 'hdlr', 'dinf' and 'stbl' atoms
+    #[allow(clippy::type_complexity)]
+    fn parts(
+        &self,
+    ) -> (
+        Option<&dyn Atom>,
+        &Option<Box<atom::hdlr::HdlrAtom>>,
+        &atom::dinf::DinfAtom,
+        &atom::stbl::StblAtom,
+    ) {
+        match self {
             MediaInfo::VideoMediaInfo {
-                vmhd_atom,
+                vmhd_atom: header,
+                hdlr_atom,
+                dinf_atom,
+                stbl_atom,
+            } => (Some(header.as_ref()), hdlr_atom, dinf_atom, stbl_atom),
+            MediaInfo::SoundMediaInfo {
+                smhd_atom: header,
+                hdlr_atom,
+                dinf_atom,
+                stbl_atom,
+            } => (Some(header.as_ref()), hdlr_atom, dinf_atom, stbl_atom),
+            MediaInfo::BaseMediaInfo {
+                gmhd_atom: header,
+                hdlr_atom,
+                dinf_atom,
+                stbl_atom,
+            } => (Some(header.as_ref()), hdlr_atom, dinf_atom, stbl_atom),
+            MediaInfo::NullMediaInfo {
+                nmhd_atom: header,
+                hdlr_atom,
+                dinf_atom,
+                stbl_atom,
+            } => (Some(header.as_ref()), hdlr_atom, dinf_atom, stbl_atom),
+            MediaInfo::HintMediaInfo {
+                hmhd_atom: header,
+                hdlr_atom,
+                dinf_atom,
+                stbl_atom,
+            } => (Some(header.as_ref()), hdlr_atom, dinf_atom, stbl_atom),
+            MediaInfo::SubtitleMediaInfo {
+                sthd_atom: header,
+                hdlr_atom,
+                dinf_atom,
+                stbl_atom,
+            } => (Some(header.as_ref()), hdlr_atom, dinf_atom, stbl_atom),
+            MediaInfo::Unknown {
                 hdlr_atom,
                 dinf_atom,
                 stbl_atom,
-            }
-        } else if atom.is::<atom::smhd::SmhdAtom>() {
-            let smhd_atom = atom.downcast::<atom::smhd::SmhdAtom>().unwrap(); // @todo
+            } => (None, hdlr_atom, dinf_atom, stbl_atom),
+        }
+    }
+
+    /// Returns the media header atom
+    pub fn header(&self) -> Option<&dyn Atom> {
+        self.parts().0
+    }
+
+    /// Returns the data handler atom
+    pub fn hdlr_atom(&self) -> Option<&atom::hdlr::HdlrAtom> {
+        self.parts().1.as_deref()
+    }
+
+    /// Returns the data information atom
+    pub fn dinf_atom(&self) -> &atom::dinf::DinfAtom {
+        self.parts().2
+    }
+
+    /// Returns the sample table atom
+    pub fn stbl_atom(&self) -> &atom::stbl::StblAtom {
+        self.parts().3
+    }
 
-            MediaInfo::SoundMediaInfo { smhd_atom }
+    /// Returns the child atoms of 'minf'
+    pub fn children(&self) -> Vec<&dyn Atom> {
+        let mut children: Vec<&dyn Atom> = Vec::new();
+
+        children.extend(self.header());
+        if let Some(a) = self.hdlr_atom() {
+            children.push(a);
+        }
+        children.push(self.dinf_atom());
+        children.push(self.stbl_atom());
+
+        children
+    }
+}
+
+impl MinfAtom {
+    /// Returns the sample table atom
+    pub fn stbl_atom(&self) -> &atom::stbl::StblAtom {
+        self.media_info.stbl_atom()
+    }
+}
+
+/// Returns the media info classified by `header`, or [`MediaInfo::Unknown`]
+/// without a recognized one
+fn classify(
+    header: Option<Box<dyn Atom>>,
+    hdlr_atom: Option<Box<atom::hdlr::HdlrAtom>>,
+    dinf_atom: Box<atom::dinf::DinfAtom>,
+    stbl_atom: Box<atom::stbl::StblAtom>,
+) -> MediaInfo {
+    match header {
+        Some(header) if header.is::<atom::vmhd::VmhdAtom>() => MediaInfo::VideoMediaInfo {
+            vmhd_atom: header.downcast().unwrap(),
+            hdlr_atom,
+            dinf_atom,
+            stbl_atom,
+        },
+        Some(header) if header.is::<atom::smhd::SmhdAtom>() => MediaInfo::SoundMediaInfo {
+            smhd_atom: header.downcast().unwrap(),
+            hdlr_atom,
+            dinf_atom,
+            stbl_atom,
+        },
+        Some(header) if header.is::<atom::gmhd::GmhdAtom>() => MediaInfo::BaseMediaInfo {
+            gmhd_atom: header.downcast().unwrap(),
+            hdlr_atom,
+            dinf_atom,
+            stbl_atom,
+        },
+        Some(header) if header.is::<atom::nmhd::NmhdAtom>() => MediaInfo::NullMediaInfo {
+            nmhd_atom: header.downcast().unwrap(),
+            hdlr_atom,
+            dinf_atom,
+            stbl_atom,
+        },
+        Some(header) if header.is::<atom::hmhd::HmhdAtom>() => MediaInfo::HintMediaInfo {
+            hmhd_atom: header.downcast().unwrap(),
+            hdlr_atom,
+            dinf_atom,
+            stbl_atom,
+        },
+        Some(header) if header.is::<atom::sthd::SthdAtom>() => MediaInfo::SubtitleMediaInfo {
+            sthd_atom: header.downcast().unwrap(),
+            hdlr_atom,
+            dinf_atom,
+            stbl_atom,
+        },
+        _ => MediaInfo::Unknown {
+            hdlr_atom,
+            dinf_atom,
+            stbl_atom,
+        },
+    }
+}
+
+/// Returns whether `atom` is one of the media headers which [`MediaInfo`]
+/// distinguishes
+fn is_media_header(atom: &dyn Atom) -> bool {
+    atom.is::<atom::vmhd::VmhdAtom>()
+        || atom.is::<atom::smhd::SmhdAtom>()
+        || atom.is::<atom::gmhd::GmhdAtom>()
+        || atom.is::<atom::nmhd::NmhdAtom>()
+        || atom.is::<atom::hmhd::HmhdAtom>()
+        || atom.is::<atom::sthd::SthdAtom>()
+}
+
+/// Returns a MinfAtom from its children in any order
+///
+/// The media type is classified by the first recognized media header, or is
+/// [`MediaInfo::Unknown`] if there is none but some other child. Every other
+/// child is kept in `unknown_atoms`. 'dinf' and 'stbl' are required and the
+/// parse fails without them, while 'hdlr' is absent from ISO base media files.
+pub fn parse<R: Read + Seek>(r: &mut R, atom_head: AtomHead) -> Result<MinfAtom, AtomParseError> {
+    let atom_tail = atom_head.atom_offset + atom_head.atom_size;
+
+    let mut hdlr_atom: Option<Box<atom::hdlr::HdlrAtom>> = None;
+    let mut dinf_atom: Option<Box<atom::dinf::DinfAtom>> = None;
+    let mut stbl_atom: Option<Box<atom::stbl::StblAtom>> = None;
+    let mut others = Vec::new();
+
+    while r.seek(SeekFrom::Current(0))? + 8 <= atom_tail {
+        let atom = atom::parse(r)?;
+
+        if atom.is::<atom::hdlr::HdlrAtom>() {
+            hdlr_atom = Some(atom.downcast::<atom::hdlr::HdlrAtom>().unwrap()); // @todo
+        } else if atom.is::<atom::dinf::DinfAtom>() {
+            dinf_atom = Some(atom.downcast::<atom::dinf::DinfAtom>().unwrap()); // @todo
+        } else if atom.is::<atom::stbl::StblAtom>() {
+            stbl_atom = Some(atom.downcast::<atom::stbl::StblAtom>().unwrap()); // @todo
         } else {
-            dbg!(atom);
-            MediaInfo::Unknown
+            others.push(atom);
         }
-    } else {
-        todo!()
-    };
+    }
+
+    if others.is_empty() {
+        return Err(AtomParseError::MediaHeaderNotFound(atom_head.atom_offset));
+    }
+    let header = others
+        .iter()
+        .position(|a| is_media_header(a.as_ref()))
+        .map(|i| others.remove(i));
+    let dinf_atom = dinf_atom.ok_or(AtomParseError::RequiredAtomNotFound(atom::dinf::ATOM_ID))?;
+    let stbl_atom = stbl_atom.ok_or(AtomParseError::RequiredAtomNotFound(atom::stbl::ATOM_ID))?;
+
+    let media_info = classify(header, hdlr_atom, dinf_atom, stbl_atom);
+    let unknown_atoms = others
+        .into_iter()
+        .map(|a| match a.downcast::<atom::UnimplementedAtom>() {
+            Ok(a) => *a,
+            Err(a) => atom::UnimplementedAtom {
+                atom_head: a.head().clone(),
+            },
+        })
+        .collect();
 
-    r.seek(SeekFrom::Start(atom_head.atom_offset + atom_head.atom_size))?;
+    r.seek(SeekFrom::Start(atom_tail))?;
     Ok(MinfAtom {
         atom_head,
         media_info,
+        unknown_atoms,
     })
 }
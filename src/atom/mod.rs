@@ -1,4 +1,8 @@
 #![allow(clippy::transmute_ptr_to_ref)] // for mopa
+pub mod chpl;
+pub mod clip;
+pub mod co64;
+pub mod crgn;
 pub mod ctts;
 pub mod dinf;
 pub mod dref;
@@ -6,23 +10,41 @@ pub mod edts;
 pub mod elst;
 pub mod free;
 pub mod ftyp;
+pub mod gmhd;
+pub mod gmin;
 pub mod hdlr;
+pub mod hmhd;
+pub mod ilst;
+pub mod imap;
+pub mod keys;
+pub mod kmat;
+pub mod load;
+pub mod matt;
 pub mod mdat;
 pub mod mdhd;
 pub mod mdia;
+pub mod meta;
 pub mod minf;
 pub mod moov;
 pub mod mvhd;
+pub mod nmhd;
+pub mod pict;
+pub mod pnot;
 pub mod smhd;
 pub mod stbl;
 pub mod stco;
+pub mod sthd;
 pub mod stsc;
 pub mod stsd;
 pub mod stss;
 pub mod stsz;
 pub mod stts;
+pub mod tcmi;
 pub mod tkhd;
+pub mod tmcd;
 pub mod trak;
+pub mod tref;
+pub mod udta;
 pub mod vmhd;
 pub mod wide;
 
@@ -31,11 +53,24 @@ use std::io::{Read, Seek, SeekFrom};
 
 use byteorder::{BigEndian, ByteOrder, ReadBytesExt};
 use thiserror::Error;
+#[cfg(feature = "async")]
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncSeek, AsyncSeekExt};
 
 use crate::element::ElementParseError;
+#[cfg(feature = "async")]
+use crate::qtfile::stream::OffsetCursor;
 use atom_derive::{atom, Atom};
 
-pub trait Atom: mopa::Any + std::fmt::Debug {}
+pub trait Atom: mopa::Any + std::fmt::Debug + Send + Sync {
+    /// Returns the head of the atom
+    fn head(&self) -> &AtomHead;
+
+    /// Returns the four characters of the atom type
+    fn fourcc(&self) -> String;
+
+    /// Returns the child atoms in file order
+    fn children(&self) -> Vec<&dyn Atom>;
+}
 
 mopafy!(Atom);
 
@@ -54,6 +89,8 @@ pub enum AtomParseError {
     TypeError(u64),
     #[error("required atom {0} was not found")]
     RequiredAtomNotFound(u32),
+    #[error("media header was not found in 'minf' at {0}")]
+    MediaHeaderNotFound(u64),
     #[error("unexpected error at {0}")]
     UnexpectedError(u64),
 
@@ -67,14 +104,11 @@ pub enum AtomParseError {
 }
 
 #[atom]
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
 pub struct UnimplementedAtom {}
 
 impl fmt::Debug for AtomHead {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let mut buf = [0; 4];
-        BigEndian::write_u32(&mut buf, self.atom_type);
-
         f.debug_struct("AtomHead")
             .field("offset", &format_args!("0x{:016x}", self.atom_offset))
             .field("size", &format_args!("0x{:016x}", self.atom_size))
@@ -82,7 +116,7 @@ impl fmt::Debug for AtomHead {
                 "type",
                 &format_args!(
                     "{:?} (0x{:08x})",
-                    buf.iter().map(|c| char::from(*c)).collect::<String>(),
+                    type_to_string(self.atom_type),
                     self.atom_type
                 ),
             )
@@ -90,6 +124,78 @@ impl fmt::Debug for AtomHead {
     }
 }
 
+/// Returns the four characters of an atom type
+///
+/// # Arguments
+///
+/// * `atom_type` - A 32-bit atom type
+///
+/// # Examples
+///
+/// ```
+/// use atom_analyzer::atom::type_to_string;
+///
+/// assert_eq!(type_to_string(0x6d6f_6f76), "moov");
+/// ```
+pub fn type_to_string(atom_type: u32) -> String {
+    let mut buf = [0; 4];
+    BigEndian::write_u32(&mut buf, atom_type);
+
+    buf.iter().map(|c| char::from(*c)).collect()
+}
+
+/// Visits atoms in a depth-first traversal by [`walk`]
+pub trait AtomVisitor {
+    /// Called for `atom` before its children
+    ///
+    /// # Arguments
+    ///
+    /// * `atom` - the visited atom
+    /// * `depth` - 0 for the atom given to `walk`, 1 for its children, ...
+    fn visit(&mut self, atom: &dyn Atom, depth: usize);
+
+    /// Called for `atom` after its children
+    fn leave(&mut self, _atom: &dyn Atom, _depth: usize) {}
+}
+
+impl<F: FnMut(&dyn Atom, usize)> AtomVisitor for F {
+    fn visit(&mut self, atom: &dyn Atom, depth: usize) {
+        self(atom, depth)
+    }
+}
+
+/// Traverses `atom` and its descendants in depth-first order
+///
+/// # Examples
+///
+/// ```
+/// use atom_analyzer::atom::{self, AtomHead, Atom};
+///
+/// let free = atom::free::FreeAtom {
+///     atom_head: AtomHead {
+///         atom_offset: 0,
+///         atom_size: 8,
+///         atom_type: atom::free::ATOM_ID,
+///     },
+/// };
+///
+/// let mut names = Vec::new();
+/// atom::walk(&free, &mut |a: &dyn Atom, _depth: usize| names.push(a.fourcc()));
+///
+/// assert_eq!(names, vec!["free"]);
+/// ```
+pub fn walk<V: AtomVisitor + ?Sized>(atom: &dyn Atom, visitor: &mut V) {
+    fn walk_at<V: AtomVisitor + ?Sized>(atom: &dyn Atom, depth: usize, visitor: &mut V) {
+        visitor.visit(atom, depth);
+        for child in atom.children() {
+            walk_at(child, depth + 1, visitor);
+        }
+        visitor.leave(atom, depth);
+    }
+
+    walk_at(atom, 0, visitor)
+}
+
 pub fn parse<R: Read + Seek>(r: &mut R) -> Result<Box<dyn Atom>, AtomParseError> {
     let atom_head = parse_atom_head(r)?;
 
@@ -112,6 +218,13 @@ pub fn parse<R: Read + Seek>(r: &mut R) -> Result<Box<dyn Atom>, AtomParseError>
         dinf::ATOM_ID => Box::new(dinf::parse(r, atom_head)?),
         dref::ATOM_ID => Box::new(dref::parse(r, atom_head)?),
         smhd::ATOM_ID => Box::new(smhd::parse(r, atom_head)?),
+        gmhd::ATOM_ID => Box::new(gmhd::parse(r, atom_head)?),
+        gmin::ATOM_ID => Box::new(gmin::parse(r, atom_head)?),
+        nmhd::ATOM_ID => Box::new(nmhd::parse(r, atom_head)?),
+        hmhd::ATOM_ID => Box::new(hmhd::parse(r, atom_head)?),
+        sthd::ATOM_ID => Box::new(sthd::parse(r, atom_head)?),
+        tmcd::ATOM_ID => Box::new(tmcd::parse(r, atom_head)?),
+        tcmi::ATOM_ID => Box::new(tcmi::parse(r, atom_head)?),
         stbl::ATOM_ID => Box::new(stbl::parse(r, atom_head)?),
         stsd::ATOM_ID => Box::new(stsd::parse(r, atom_head)?),
         stts::ATOM_ID => Box::new(stts::parse(r, atom_head)?),
@@ -120,6 +233,21 @@ pub fn parse<R: Read + Seek>(r: &mut R) -> Result<Box<dyn Atom>, AtomParseError>
         stsc::ATOM_ID => Box::new(stsc::parse(r, atom_head)?),
         stsz::ATOM_ID => Box::new(stsz::parse(r, atom_head)?),
         stco::ATOM_ID => Box::new(stco::parse(r, atom_head)?),
+        co64::ATOM_ID => Box::new(co64::parse(r, atom_head)?),
+        udta::ATOM_ID => Box::new(udta::parse(r, atom_head)?),
+        meta::ATOM_ID => Box::new(meta::parse(r, atom_head)?),
+        keys::ATOM_ID => Box::new(keys::parse(r, atom_head)?),
+        ilst::ATOM_ID => Box::new(ilst::parse(r, atom_head)?),
+        pnot::ATOM_ID => Box::new(pnot::parse(r, atom_head)?),
+        pict::ATOM_ID => Box::new(pict::parse(r, atom_head)?),
+        tref::ATOM_ID => Box::new(tref::parse(r, atom_head)?),
+        chpl::ATOM_ID => Box::new(chpl::parse(r, atom_head)?),
+        clip::ATOM_ID => Box::new(clip::parse(r, atom_head)?),
+        crgn::ATOM_ID => Box::new(crgn::parse(r, atom_head)?),
+        matt::ATOM_ID => Box::new(matt::parse(r, atom_head)?),
+        kmat::ATOM_ID => Box::new(kmat::parse(r, atom_head)?),
+        load::ATOM_ID => Box::new(load::parse(r, atom_head)?),
+        imap::ATOM_ID => Box::new(imap::parse(r, atom_head)?),
         _ => {
             r.seek(SeekFrom::Start(atom_head.atom_offset + atom_head.atom_size))?;
             Box::new(UnimplementedAtom { atom_head })
@@ -189,3 +317,98 @@ pub fn parse_atom_head<R: Read + Seek>(r: &mut R) -> Result<AtomHead, AtomParseE
         atom_type,
     })
 }
+
+/// Returns an AtomHead from `r` asynchronously
+///
+/// # Arguments
+///
+/// * `r` - input data
+#[cfg(feature = "async")]
+pub async fn parse_atom_head_async<R: AsyncRead + AsyncSeek + Unpin>(
+    r: &mut R,
+) -> Result<AtomHead, AtomParseError> {
+    let atom_offset = r.stream_position().await?;
+
+    let atom_size = match r.read_u32().await {
+        Ok(val) => val as u64,
+        Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => {
+            return Err(AtomParseError::NoMoreAtom)
+        }
+        Err(e) => return Err(e.into()),
+    };
+
+    let atom_type = r.read_u32().await?;
+
+    let atom_size = match atom_size {
+        0 => {
+            let atom_tail = r.seek(SeekFrom::End(0)).await?;
+            r.seek(SeekFrom::Start(atom_offset + 8)).await?;
+            atom_tail - atom_offset
+        }
+        1 => r.read_u64().await?, // extended size
+        s => s,
+    };
+
+    Ok(AtomHead {
+        atom_offset,
+        atom_size,
+        atom_type,
+    })
+}
+
+/// Returns the head of the atom at the current position of `r`, the size of the
+/// header and, unless the atom is 'mdat', an in-memory copy of the whole atom
+///
+/// `r` is left at the end of the atom.
+#[cfg(feature = "async")]
+pub(crate) async fn read_atom_async<R: AsyncRead + AsyncSeek + Unpin>(
+    r: &mut R,
+) -> Result<(AtomHead, u64, Option<OffsetCursor>), AtomParseError> {
+    let atom_head = parse_atom_head_async(r).await?;
+    let atom_tail = atom_head.atom_offset + atom_head.atom_size;
+    let header_size = r.stream_position().await? - atom_head.atom_offset;
+
+    if atom_tail < atom_head.atom_offset + header_size {
+        return Err(AtomParseError::UnexpectedError(atom_head.atom_offset));
+    }
+
+    let cursor = if atom_head.atom_type == mdat::ATOM_ID {
+        None
+    } else {
+        let mut data = Vec::new();
+        r.seek(SeekFrom::Start(atom_head.atom_offset)).await?;
+        (&mut *r)
+            .take(atom_head.atom_size)
+            .read_to_end(&mut data)
+            .await?;
+        if (data.len() as u64) < atom_head.atom_size {
+            return Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof).into());
+        }
+        Some(OffsetCursor::new(atom_head.atom_offset, data))
+    };
+
+    r.seek(SeekFrom::Start(atom_tail)).await?;
+
+    Ok((atom_head, header_size, cursor))
+}
+
+/// Returns an atom parsed from `r` asynchronously
+///
+/// The atom is read into memory as a whole and then decoded by [`parse`], except
+/// for the payload of 'mdat', which is skipped.
+///
+/// # Arguments
+///
+/// * `r` - input data
+#[cfg(feature = "async")]
+pub async fn parse_async<R: AsyncRead + AsyncSeek + Unpin>(
+    r: &mut R,
+) -> Result<Box<dyn Atom>, AtomParseError> {
+    match read_atom_async(r).await? {
+        (atom_head, header_size, None) => Ok(Box::new(mdat::MdatAtom {
+            atom_head,
+            header_size,
+        })),
+        (_, _, Some(mut cursor)) => parse(&mut cursor),
+    }
+}
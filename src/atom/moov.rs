@@ -1,6 +1,8 @@
 use std::fmt::Debug;
 use std::io::{Read, Seek, SeekFrom};
 
+use crate::atom::trak::TrakAtom;
+use crate::atom::tref::{TrackReferenceError, TrackReferenceType};
 use crate::atom::{self, Atom, AtomHead, AtomParseError};
 use atom_derive::atom;
 
@@ -11,11 +13,87 @@ pub const ATOM_ID: u32 = 0x6d6f_6f76; // 'moov'
 pub struct MoovAtom {
     pub mvhd_atom: Option<Box<atom::mvhd::MvhdAtom>>,
     pub trak_atom: Vec<atom::trak::TrakAtom>,
+    pub udta_atom: Option<Box<atom::udta::UdtaAtom>>,
+    pub meta_atom: Option<Box<atom::meta::MetaAtom>>,
+}
+
+/// A track which another track refers to in 'tref'
+#[derive(Debug, PartialEq)]
+pub struct TrackReference<'a> {
+    pub reference_type: TrackReferenceType,
+    pub track: &'a TrakAtom,
+}
+
+impl MoovAtom {
+    /// Returns the time scale of the movie, or 0 without 'mvhd'
+    pub fn time_scale(&self) -> u32 {
+        self.mvhd_atom.as_ref().map_or(0, |mvhd| mvhd.time_scale)
+    }
+
+    /// Returns the track whose 'tkhd' has `track_id`
+    pub fn track(&self, track_id: u32) -> Option<&atom::trak::TrakAtom> {
+        self.trak_atom
+            .iter()
+            .find(|trak| trak.tkhd_atom.track_id == track_id)
+    }
+
+    /// Returns the tracks which `trak` refers to in 'tref' in reference order,
+    /// or an error for each track ID which is not in this movie
+    fn resolve_references<'a: 't, 't>(
+        &'a self,
+        trak: &'t TrakAtom,
+    ) -> impl Iterator<Item = Result<TrackReference<'a>, TrackReferenceError>> + 't {
+        trak.tref_atom
+            .iter()
+            .flat_map(|tref| tref.track_reference_type_atom.iter())
+            .flat_map(|r| r.track_ids.iter().map(move |id| (r, *id)))
+            .map(move |(r, id)| match self.track(id) {
+                Some(track) => Ok(TrackReference {
+                    reference_type: r.reference_type,
+                    track,
+                }),
+                None => Err(TrackReferenceError::DanglingReference {
+                    atom_offset: r.atom_head.atom_offset,
+                    track_id: trak.tkhd_atom.track_id,
+                    reference_type: r.reference_type,
+                    referenced_track_id: id,
+                }),
+            })
+    }
+
+    /// Returns the tracks which `trak` refers to in 'tref' in reference order
+    ///
+    /// Returns an error for the first track ID which is not in this movie.
+    pub fn track_references(
+        &self,
+        trak: &TrakAtom,
+    ) -> Result<Vec<TrackReference<'_>>, TrackReferenceError> {
+        self.resolve_references(trak).collect()
+    }
+
+    /// Returns an error for every reference in 'tref' of every track to a
+    /// track which is not in this movie
+    pub fn dangling_references(&self) -> Vec<TrackReferenceError> {
+        self.trak_atom
+            .iter()
+            .flat_map(|trak| self.resolve_references(trak))
+            .filter_map(Result::err)
+            .collect()
+    }
+
+    /// Returns the first string of the movie's '©' user data of `atom_type`
+    pub fn user_text(&self, atom_type: u32) -> Option<&str> {
+        self.udta_atom
+            .as_ref()
+            .and_then(|udta| udta.text(atom_type))
+    }
 }
 
 pub fn parse<R: Read + Seek>(r: &mut R, atom_head: AtomHead) -> Result<MoovAtom, AtomParseError> {
     let mut mvhd_atom = None;
     let mut trak_atom = Vec::new();
+    let mut udta_atom = None;
+    let mut meta_atom = None;
 
     let atom_tail = atom_head.atom_offset + atom_head.atom_size;
 
@@ -23,18 +101,20 @@ pub fn parse<R: Read + Seek>(r: &mut R, atom_head: AtomHead) -> Result<MoovAtom,
         return Err(AtomParseError::TypeError(atom_head.atom_offset));
     }
 
-    while let Ok(atom) = atom::parse(r) {
+    while r.seek(SeekFrom::Current(0))? + 8 <= atom_tail {
+        let atom = atom::parse(r)?;
+
         if atom.is::<atom::mvhd::MvhdAtom>() {
             mvhd_atom = Some(atom.downcast::<atom::mvhd::MvhdAtom>().unwrap()); // @todo
         } else if atom.is::<atom::trak::TrakAtom>() {
             trak_atom.push(*atom.downcast::<atom::trak::TrakAtom>().unwrap());
+        } else if atom.is::<atom::udta::UdtaAtom>() {
+            udta_atom = Some(atom.downcast::<atom::udta::UdtaAtom>().unwrap()); // @todo
+        } else if atom.is::<atom::meta::MetaAtom>() {
+            meta_atom = Some(atom.downcast::<atom::meta::MetaAtom>().unwrap()); // @todo
         } else {
             eprintln!("{:?}", atom);
         }
-
-        if r.seek(SeekFrom::Current(0))? >= atom_tail {
-            break;
-        }
     }
 
     r.seek(SeekFrom::Start(atom_head.atom_offset + atom_head.atom_size))?;
@@ -43,5 +123,7 @@ pub fn parse<R: Read + Seek>(r: &mut R, atom_head: AtomHead) -> Result<MoovAtom,
         atom_head,
         mvhd_atom,
         trak_atom,
+        udta_atom,
+        meta_atom,
     })
 }
@@ -0,0 +1,28 @@
+use std::fmt::Debug;
+use std::io::{Read, Seek, SeekFrom};
+
+use crate::atom::{Atom, AtomHead, AtomParseError};
+use atom_derive::atom;
+
+pub const ATOM_ID: u32 = 0x5049_4354; // 'PICT'
+
+/// A preview image referred to by 'pnot'
+#[atom]
+#[derive(Debug, PartialEq)]
+pub struct PictAtom {
+    /// A QuickDraw picture, or an image in another format
+    pub data: Vec<u8>,
+}
+
+pub fn parse<R: Read + Seek>(r: &mut R, atom_head: AtomHead) -> Result<PictAtom, AtomParseError> {
+    let atom_tail = atom_head.atom_offset + atom_head.atom_size;
+    let header_size = r.seek(SeekFrom::Current(0))? - atom_head.atom_offset;
+
+    let mut data = Vec::new();
+    r.take(atom_head.atom_size - header_size)
+        .read_to_end(&mut data)?;
+
+    r.seek(SeekFrom::Start(atom_tail))?;
+
+    Ok(PictAtom { atom_head, data })
+}
@@ -0,0 +1,39 @@
+use std::fmt::Debug;
+use std::io::{Read, Seek, SeekFrom};
+
+use byteorder::{BigEndian, ReadBytesExt};
+
+use crate::atom::{Atom, AtomHead, AtomParseError};
+use crate::element;
+use atom_derive::atom;
+
+pub const ATOM_ID: u32 = 0x706e_6f74; // 'pnot'
+
+/// A reference to the top-level atom holding the preview of the movie
+#[atom]
+#[derive(Debug, PartialEq)]
+pub struct PnotAtom {
+    pub modification_date: element::qtfile_datetime::QtFileDateTime,
+    pub version_number: u16,
+    /// The type of the preview atom, usually 'PICT'
+    pub preview_type: u32,
+    /// The 1-based index among the top-level atoms of `preview_type`
+    pub atom_index: u16,
+}
+
+pub fn parse<R: Read + Seek>(r: &mut R, atom_head: AtomHead) -> Result<PnotAtom, AtomParseError> {
+    let modification_date = element::qtfile_datetime::QtFileDateTime::parse(r)?;
+    let version_number = r.read_u16::<BigEndian>()?;
+    let preview_type = r.read_u32::<BigEndian>()?;
+    let atom_index = r.read_u16::<BigEndian>()?;
+
+    r.seek(SeekFrom::Start(atom_head.atom_offset + atom_head.atom_size))?;
+
+    Ok(PnotAtom {
+        atom_head,
+        modification_date,
+        version_number,
+        preview_type,
+        atom_index,
+    })
+}
@@ -9,7 +9,7 @@ use atom_derive::atom;
 
 pub const ATOM_ID: u32 = 0x736d_6864; // 'smhd'
 
-#[atom]
+#[atom(version)]
 #[derive(Debug, PartialEq)]
 pub struct SmhdAtom {
     pub balance: FixedI16<U8>,
@@ -17,11 +17,17 @@ pub struct SmhdAtom {
 }
 
 pub fn parse<R: Read + Seek>(r: &mut R, atom_head: AtomHead) -> Result<SmhdAtom, AtomParseError> {
+    let atom_version = r.read_u8()?;
+    let mut atom_flags = [0_u8; 3];
+    r.read_exact(&mut atom_flags)?;
+
     let balance = FixedI16::<U8>::from_bits(r.read_i16::<BigEndian>()?);
     let reserved = r.read_u16::<BigEndian>()?;
 
     Ok(SmhdAtom {
         atom_head,
+        atom_version,
+        atom_flags,
         balance,
         reserved,
     })
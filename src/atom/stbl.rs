@@ -1,5 +1,6 @@
 use std::fmt::Debug;
 use std::io::{Read, Seek, SeekFrom};
+use std::iter;
 
 use crate::atom::{self, Atom, AtomHead, AtomParseError};
 use atom_derive::atom;
@@ -16,6 +17,120 @@ pub struct StblAtom {
     pub stsc_atom: Option<Box<atom::stsc::StscAtom>>,
     pub stsz_atom: Option<Box<atom::stsz::StszAtom>>,
     pub stco_atom: Option<Box<atom::stco::StcoAtom>>,
+    pub co64_atom: Option<Box<atom::co64::Co64Atom>>,
+}
+
+/// A sample resolved from the sample table
+#[derive(Debug, PartialEq, Clone)]
+pub struct Sample {
+    /// The 0-based index in decoding order
+    pub index: usize,
+    /// The 1-based chunk number
+    pub chunk: u32,
+    pub offset: u64,
+    pub size: u32,
+    pub decode_time: u64,
+    pub composition_time: i64,
+    pub duration: u32,
+    pub is_sync: bool,
+    pub sample_description_id: u32,
+}
+
+impl StblAtom {
+    /// Returns the chunk offsets in 'stco', or in 'co64' for large files
+    pub fn chunk_offsets(&self) -> Vec<u64> {
+        match (&self.stco_atom, &self.co64_atom) {
+            (Some(stco), _) => stco
+                .chunk_offset_table
+                .iter()
+                .map(|offset| *offset as u64)
+                .collect(),
+            (None, Some(co64)) => co64.chunk_offset_table.clone(),
+            (None, None) => Vec::new(),
+        }
+    }
+
+    /// Returns the samples in decoding order
+    ///
+    /// Times are in the media time scale. Samples which no chunk holds are
+    /// omitted, and every sample is a sync sample when 'stss' is absent.
+    pub fn samples(&self) -> Vec<Sample> {
+        let sample_count = match (&self.stsz_atom, &self.stts_atom) {
+            (Some(stsz), _) => stsz.number_of_entries as usize,
+            (None, Some(stts)) => stts.sample_count() as usize,
+            _ => 0,
+        };
+        let chunk_offsets = self.chunk_offsets();
+        let sample_to_chunk = self
+            .stsc_atom
+            .as_ref()
+            .map_or(&[][..], |a| &a.sample_to_chunk_table[..]);
+
+        let mut durations = self
+            .stts_atom
+            .iter()
+            .flat_map(|a| a.time_to_sample_table.iter())
+            .flat_map(|e| iter::repeat(e.sample_duration).take(e.sample_count as usize));
+        let mut composition_offsets = self
+            .ctts_atom
+            .iter()
+            .flat_map(|a| a.composition_offset_table.iter())
+            .flat_map(|e| iter::repeat(e.composition_offset as i32).take(e.sample_count as usize));
+
+        let mut samples = Vec::with_capacity(sample_count);
+        let mut decode_time = 0;
+
+        for (i, entry) in sample_to_chunk.iter().enumerate() {
+            let last_chunk = match sample_to_chunk.get(i + 1) {
+                Some(next) => next.first_chunk.saturating_sub(1),
+                None => chunk_offsets.len() as u32,
+            }
+            .min(chunk_offsets.len() as u32);
+
+            for chunk in entry.first_chunk.max(1)..=last_chunk {
+                let mut offset = chunk_offsets[chunk as usize - 1];
+
+                for _ in 0..entry.samples_per_chunk {
+                    if samples.len() == sample_count {
+                        return samples;
+                    }
+
+                    let index = samples.len();
+                    let size = self
+                        .stsz_atom
+                        .as_ref()
+                        .and_then(|a| a.sample_size(index))
+                        .unwrap_or(0);
+                    let duration = durations.next().unwrap_or(0);
+                    let is_sync = match &self.stss_atom {
+                        Some(stss) => stss
+                            .sync_sample_table
+                            .binary_search(&(index as u32 + 1))
+                            .is_ok(),
+                        None => true,
+                    };
+
+                    samples.push(Sample {
+                        index,
+                        chunk,
+                        offset,
+                        size,
+                        decode_time,
+                        composition_time: decode_time as i64
+                            + composition_offsets.next().unwrap_or(0) as i64,
+                        duration,
+                        is_sync,
+                        sample_description_id: entry.sample_description_id,
+                    });
+
+                    offset = offset.saturating_add(size as u64);
+                    decode_time += duration as u64;
+                }
+            }
+        }
+
+        samples
+    }
 }
 
 pub fn parse<R: Read + Seek>(r: &mut R, atom_head: AtomHead) -> Result<StblAtom, AtomParseError> {
@@ -26,6 +141,7 @@ pub fn parse<R: Read + Seek>(r: &mut R, atom_head: AtomHead) -> Result<StblAtom,
     let mut stsc_atom: Option<Box<atom::stsc::StscAtom>> = None;
     let mut stsz_atom: Option<Box<atom::stsz::StszAtom>> = None;
     let mut stco_atom: Option<Box<atom::stco::StcoAtom>> = None;
+    let mut co64_atom: Option<Box<atom::co64::Co64Atom>> = None;
 
     let atom_tail = atom_head.atom_offset + atom_head.atom_size;
 
@@ -44,6 +160,8 @@ pub fn parse<R: Read + Seek>(r: &mut R, atom_head: AtomHead) -> Result<StblAtom,
             stsz_atom = Some(atom.downcast::<atom::stsz::StszAtom>().unwrap()) // @todo
         } else if atom.is::<atom::stco::StcoAtom>() {
             stco_atom = Some(atom.downcast::<atom::stco::StcoAtom>().unwrap()) // @todo
+        } else if atom.is::<atom::co64::Co64Atom>() {
+            co64_atom = Some(atom.downcast::<atom::co64::Co64Atom>().unwrap()) // @todo
         } else {
             eprintln!("{:?}", atom);
         }
@@ -63,5 +181,6 @@ pub fn parse<R: Read + Seek>(r: &mut R, atom_head: AtomHead) -> Result<StblAtom,
         stsc_atom,
         stsz_atom,
         stco_atom,
+        co64_atom,
     })
 }
@@ -0,0 +1,25 @@
+use std::fmt::Debug;
+use std::io::{Read, Seek};
+
+use byteorder::ReadBytesExt;
+
+use crate::atom::{Atom, AtomHead, AtomParseError};
+use atom_derive::atom;
+
+pub const ATOM_ID: u32 = 0x7374_6864; // 'sthd'
+
+#[atom(version)]
+#[derive(Debug, PartialEq)]
+pub struct SthdAtom {}
+
+pub fn parse<R: Read + Seek>(r: &mut R, atom_head: AtomHead) -> Result<SthdAtom, AtomParseError> {
+    let atom_version = r.read_u8()?;
+    let mut atom_flags = [0_u8; 3];
+    r.read_exact(&mut atom_flags)?;
+
+    Ok(SthdAtom {
+        atom_head,
+        atom_version,
+        atom_flags,
+    })
+}
@@ -32,6 +32,31 @@ impl SampleToChunk {
     }
 }
 
+impl StscAtom {
+    /// Returns the total number of samples in `chunk_count` chunks
+    ///
+    /// # Arguments
+    ///
+    /// * `chunk_count` - the number of chunks in the chunk offset table
+    pub fn sample_count(&self, chunk_count: u32) -> u64 {
+        let mut count = 0;
+
+        for (i, entry) in self.sample_to_chunk_table.iter().enumerate() {
+            let last_chunk = match self.sample_to_chunk_table.get(i + 1) {
+                Some(next) => next.first_chunk.saturating_sub(1).min(chunk_count),
+                None => chunk_count,
+            };
+
+            if last_chunk >= entry.first_chunk {
+                count +=
+                    (last_chunk - entry.first_chunk + 1) as u64 * entry.samples_per_chunk as u64;
+            }
+        }
+
+        count
+    }
+}
+
 pub fn parse<R: Read>(r: &mut R, atom_head: AtomHead) -> Result<StscAtom, AtomParseError> {
     let atom_version = r.read_u8()?;
     let mut atom_flags = [0_u8; 3];
@@ -1,5 +1,5 @@
 use std::fmt::Debug;
-use std::io::{Read, Seek, SeekFrom};
+use std::io::{Read, Seek};
 
 use byteorder::{BigEndian, ReadBytesExt};
 
@@ -17,6 +17,29 @@ pub struct SampleDescription {
     pub data: Vec<u8>,
 }
 
+impl SampleDescription {
+    /// Returns a sample description read from `r`
+    pub(crate) fn parse<R: Read>(r: &mut R) -> Result<Self, AtomParseError> {
+        let sample_description_size = r.read_u32::<BigEndian>()?;
+        let data_format = r.read_u32::<BigEndian>()?;
+        let mut reserved = [0_u8; 6];
+        r.read_exact(&mut reserved)?;
+        let data_reference_index = r.read_u16::<BigEndian>()?;
+
+        let mut data = Vec::new();
+        r.take(sample_description_size.saturating_sub(16) as u64)
+            .read_to_end(&mut data)?;
+
+        Ok(SampleDescription {
+            sample_description_size,
+            data_format,
+            reserved,
+            data_reference_index,
+            data,
+        })
+    }
+}
+
 #[atom(version)]
 #[derive(Debug, PartialEq)]
 pub struct StsdAtom {
@@ -34,26 +57,7 @@ pub fn parse<R: Read + Seek>(r: &mut R, atom_head: AtomHead) -> Result<StsdAtom,
     let mut sample_description_table = Vec::new();
 
     for _ in 0..number_of_entries {
-        let sample_description_size = r.read_u32::<BigEndian>()?;
-        let data_format = r.read_u32::<BigEndian>()?;
-        let mut reserved = [0_u8; 6];
-        r.read_exact(&mut reserved)?;
-        let data_reference_index = r.read_u16::<BigEndian>()?;
-
-        let data = Vec::new();
-
-        // @todo
-        // r.take((sample_description_size - 16) as u64)
-        //    .read_to_end(&mut data)?;
-        r.seek(SeekFrom::Current((sample_description_size - 16) as i64))?;
-
-        sample_description_table.push(SampleDescription {
-            sample_description_size,
-            data_format,
-            reserved,
-            data_reference_index,
-            data,
-        });
+        sample_description_table.push(SampleDescription::parse(r)?);
     }
 
     Ok(StsdAtom {
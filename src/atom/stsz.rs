@@ -16,6 +16,17 @@ pub struct StszAtom {
     pub sample_size_table: Vec<u32>,
 }
 
+impl StszAtom {
+    /// Returns the size of the sample at `index` (0-based)
+    pub fn sample_size(&self, index: usize) -> Option<u32> {
+        if self.sample_size != 0 {
+            Some(self.sample_size).filter(|_| index < self.number_of_entries as usize)
+        } else {
+            self.sample_size_table.get(index).copied()
+        }
+    }
+}
+
 pub fn parse<R: Read>(r: &mut R, atom_head: AtomHead) -> Result<StszAtom, AtomParseError> {
     let atom_version = r.read_u8()?;
     let mut atom_flags = [0_u8; 3];
@@ -21,6 +21,16 @@ pub struct SttsAtom {
     pub time_to_sample_table: Vec<TimeToSampleEntry>,
 }
 
+impl SttsAtom {
+    /// Returns the total number of samples described by the table
+    pub fn sample_count(&self) -> u64 {
+        self.time_to_sample_table
+            .iter()
+            .map(|e| e.sample_count as u64)
+            .sum()
+    }
+}
+
 pub fn parse<R: Read + Seek>(r: &mut R, atom_head: AtomHead) -> Result<SttsAtom, AtomParseError> {
     let atom_version = r.read_u8()?;
     let mut atom_flags = [0_u8; 3];
@@ -0,0 +1,54 @@
+use std::fmt::Debug;
+use std::io::{Read, Seek};
+
+use byteorder::{BigEndian, ReadBytesExt};
+
+use crate::atom::{Atom, AtomHead, AtomParseError};
+use atom_derive::atom;
+
+pub const ATOM_ID: u32 = 0x7463_6d69; // 'tcmi'
+
+#[atom(version)]
+#[derive(Debug, PartialEq)]
+pub struct TcmiAtom {
+    pub text_font: u16,
+    pub text_face: u16,
+    pub text_size: u16,
+    pub reserved: u16,
+    pub text_color: [u16; 3],
+    pub background_color: [u16; 3],
+    pub font_name: String,
+}
+
+pub fn parse<R: Read + Seek>(r: &mut R, atom_head: AtomHead) -> Result<TcmiAtom, AtomParseError> {
+    let atom_version = r.read_u8()?;
+    let mut atom_flags = [0_u8; 3];
+    r.read_exact(&mut atom_flags)?;
+
+    let text_font = r.read_u16::<BigEndian>()?;
+    let text_face = r.read_u16::<BigEndian>()?;
+    let text_size = r.read_u16::<BigEndian>()?;
+    let reserved = r.read_u16::<BigEndian>()?;
+    let mut text_color = [0_u16; 3];
+    r.read_u16_into::<BigEndian>(&mut text_color)?;
+    let mut background_color = [0_u16; 3];
+    r.read_u16_into::<BigEndian>(&mut background_color)?;
+
+    // Pascal string
+    let length = r.read_u8()?;
+    let mut font_name = Vec::new();
+    r.take(length as u64).read_to_end(&mut font_name)?;
+
+    Ok(TcmiAtom {
+        atom_head,
+        atom_version,
+        atom_flags,
+        text_font,
+        text_face,
+        text_size,
+        reserved,
+        text_color,
+        background_color,
+        font_name: String::from_utf8_lossy(&font_name).into_owned(),
+    })
+}
@@ -11,7 +11,12 @@ use crate::atom::{Atom, AtomHead, AtomParseError};
 use crate::element;
 use atom_derive::atom;
 
-#[atom(version)]
+#[atom(version(
+    enabled = 0x0001,
+    in_movie = 0x0002,
+    in_preview = 0x0004,
+    in_poster = 0x0008
+))]
 #[derive(Debug, PartialEq)]
 pub struct TkhdAtom {
     pub creation_time: element::qtfile_datetime::QtFileDateTime,
@@ -0,0 +1,181 @@
+use std::fmt::{self, Debug};
+use std::io::{Cursor, Read, Seek, SeekFrom};
+
+use byteorder::{BigEndian, ReadBytesExt};
+
+use crate::atom::{self, stsd::SampleDescription, Atom, AtomHead, AtomParseError};
+use atom_derive::atom;
+
+pub const ATOM_ID: u32 = 0x746d_6364; // 'tmcd'
+
+const NAME_ATOM_ID: u32 = 0x6e61_6d65; // 'name'
+
+/// Timecode media information in 'gmhd'
+#[atom]
+#[derive(Debug, PartialEq)]
+pub struct TmcdAtom {
+    pub tcmi_atom: Option<Box<atom::tcmi::TcmiAtom>>,
+}
+
+/// A timecode sample description from 'stsd'
+#[derive(Debug, PartialEq, Clone)]
+pub struct TimecodeDescription {
+    pub reserved: u32,
+    pub flags: u32,
+    pub time_scale: u32,
+    pub frame_duration: u32,
+    pub number_of_frames: u8,
+    /// The source name from the 'name' child
+    pub name: Option<String>,
+}
+
+impl TimecodeDescription {
+    pub const DROP_FRAME: u32 = 0x0001;
+    pub const MAX_24_HOUR: u32 = 0x0002;
+    pub const NEGATIVE_TIMES_OK: u32 = 0x0004;
+    pub const COUNTER: u32 = 0x0008;
+
+    /// Returns the timecode description held in `description`
+    ///
+    /// # Arguments
+    ///
+    /// * `description` - A sample description whose data format is 'tmcd'
+    pub fn new(description: &SampleDescription) -> Result<Self, AtomParseError> {
+        let mut r = Cursor::new(&description.data[..]);
+
+        let reserved = r.read_u32::<BigEndian>()?;
+        let flags = r.read_u32::<BigEndian>()?;
+        let time_scale = r.read_u32::<BigEndian>()?;
+        let frame_duration = r.read_u32::<BigEndian>()?;
+        let number_of_frames = r.read_u8()?;
+        let _reserved = r.read_u8()?;
+
+        let mut name = None;
+        let data_size = description.data.len() as u64;
+
+        while r.position() + 8 <= data_size {
+            let offset = r.position();
+            let size = r.read_u32::<BigEndian>()? as u64;
+            let atom_type = r.read_u32::<BigEndian>()?;
+
+            if size < 8 {
+                break;
+            }
+            if atom_type == NAME_ATOM_ID {
+                let length = r.read_u16::<BigEndian>()?;
+                let _language = r.read_u16::<BigEndian>()?;
+                let mut text = Vec::new();
+                r.by_ref().take(length as u64).read_to_end(&mut text)?;
+                name = Some(String::from_utf8_lossy(&text).into_owned());
+            }
+            r.seek(SeekFrom::Start(offset + size))?;
+        }
+
+        Ok(TimecodeDescription {
+            reserved,
+            flags,
+            time_scale,
+            frame_duration,
+            number_of_frames,
+            name,
+        })
+    }
+
+    /// Returns whether the timecode counts drop frames
+    pub fn is_drop_frame(&self) -> bool {
+        self.flags & Self::DROP_FRAME != 0
+    }
+
+    /// Returns whether the timecode wraps at 24 hours
+    pub fn is_max_24_hour(&self) -> bool {
+        self.flags & Self::MAX_24_HOUR != 0
+    }
+
+    /// Returns whether the timecode may be negative
+    pub fn is_negative_times_ok(&self) -> bool {
+        self.flags & Self::NEGATIVE_TIMES_OK != 0
+    }
+
+    /// Returns whether the samples are counters rather than frame numbers
+    pub fn is_counter(&self) -> bool {
+        self.flags & Self::COUNTER != 0
+    }
+
+    /// Returns the timecode of the `frame_number`th frame counted from 00:00:00:00
+    ///
+    /// Drop frame timecode skips the first frame numbers of every minute
+    /// except each tenth minute, by 2 frames per 30 frames per second.
+    pub fn timecode(&self, frame_number: u64) -> Timecode {
+        let fps = self.number_of_frames.max(1) as u64;
+        let mut frame_number = frame_number;
+
+        if self.is_drop_frame() && fps % 30 == 0 {
+            let drop = fps / 15;
+            let frames_per_minute = fps * 60 - drop;
+            let frames_per_10_minutes = fps * 600 - drop * 9;
+
+            let d = frame_number / frames_per_10_minutes;
+            let m = frame_number % frames_per_10_minutes;
+            frame_number += drop * 9 * d + drop * (m.saturating_sub(drop) / frames_per_minute);
+        }
+
+        let mut hours = frame_number / (fps * 3600);
+        if self.is_max_24_hour() {
+            hours %= 24;
+        }
+
+        Timecode {
+            hours: hours as u32,
+            minutes: (frame_number / (fps * 60) % 60) as u8,
+            seconds: (frame_number / fps % 60) as u8,
+            frames: (frame_number % fps) as u8,
+            drop_frame: self.is_drop_frame(),
+        }
+    }
+}
+
+/// An SMPTE timecode
+#[derive(Debug, PartialEq, Clone)]
+pub struct Timecode {
+    pub hours: u32,
+    pub minutes: u8,
+    pub seconds: u8,
+    pub frames: u8,
+    pub drop_frame: bool,
+}
+
+impl fmt::Display for Timecode {
+    /// Formats as `HH:MM:SS:FF`, or `HH:MM:SS;FF` for drop frame timecode
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:02}:{:02}:{:02}{}{:02}",
+            self.hours,
+            self.minutes,
+            self.seconds,
+            if self.drop_frame { ';' } else { ':' },
+            self.frames
+        )
+    }
+}
+
+pub fn parse<R: Read + Seek>(r: &mut R, atom_head: AtomHead) -> Result<TmcdAtom, AtomParseError> {
+    let mut tcmi_atom: Option<Box<atom::tcmi::TcmiAtom>> = None;
+
+    let atom_tail = atom_head.atom_offset + atom_head.atom_size;
+
+    while r.seek(SeekFrom::Current(0))? + 8 <= atom_tail {
+        let atom = atom::parse(r)?;
+
+        if atom.is::<atom::tcmi::TcmiAtom>() {
+            tcmi_atom = Some(atom.downcast::<atom::tcmi::TcmiAtom>().unwrap()); // @todo
+        }
+    }
+
+    r.seek(SeekFrom::Start(atom_tail))?;
+
+    Ok(TmcdAtom {
+        atom_head,
+        tcmi_atom,
+    })
+}
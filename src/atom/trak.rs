@@ -1,6 +1,14 @@
+use std::convert::TryFrom;
 use std::fmt::Debug;
 use std::io::{Read, Seek, SeekFrom};
 
+use byteorder::{BigEndian, ReadBytesExt};
+use fixed::{types::extra::U16, FixedU32};
+
+use crate::atom::stbl::Sample;
+use crate::atom::stsd::SampleDescription;
+use crate::atom::tmcd::{self, Timecode, TimecodeDescription};
+use crate::atom::tref::TrackReferenceType;
 use crate::atom::{self, Atom, AtomHead, AtomParseError};
 use atom_derive::atom;
 
@@ -10,31 +18,397 @@ pub const ATOM_ID: u32 = 0x7472_616b; // 'trak'
 #[derive(Debug, PartialEq)]
 pub struct TrakAtom {
     pub tkhd_atom: Box<atom::tkhd::TkhdAtom>,
+    pub clip_atom: Option<Box<atom::clip::ClipAtom>>,
+    pub matt_atom: Option<Box<atom::matt::MattAtom>>,
     pub edts_atom: Option<Box<atom::edts::EdtsAtom>>,
+    pub tref_atom: Option<Box<atom::tref::TrefAtom>>,
+    pub load_atom: Option<Box<atom::load::LoadAtom>>,
+    pub imap_atom: Option<Box<atom::imap::ImapAtom>>,
     pub mdia_atom: Box<atom::mdia::MdiaAtom>,
+    pub udta_atom: Option<Box<atom::udta::UdtaAtom>>,
+}
+
+/// A span of the movie timeline resolved from an edit list entry
+#[derive(Debug, PartialEq, Clone)]
+pub struct Edit {
+    /// The start in the movie time scale
+    pub movie_time: u64,
+    /// The duration in the movie time scale
+    pub movie_duration: u64,
+    /// The start in the media time scale, or `None` for an empty edit
+    pub media_time: Option<u64>,
+    /// The playback rate, where a backward rate is held at 0
+    pub media_rate: FixedU32<U16>,
+}
+
+impl Edit {
+    /// Returns whether the edit holds a single media time for its duration
+    pub fn is_dwell(&self) -> bool {
+        self.media_time.is_some() && self.media_rate == 0
+    }
+
+    /// Returns the media time shown at `movie_time`, which must be in the edit
+    fn media_time_at(
+        &self,
+        movie_time: u64,
+        movie_time_scale: u32,
+        media_time_scale: u32,
+    ) -> Option<u64> {
+        let elapsed = rescale(
+            movie_time - self.movie_time,
+            movie_time_scale,
+            media_time_scale,
+        );
+
+        Some(
+            self.media_time? + ((elapsed as u128 * self.media_rate.to_bits() as u128) >> 16) as u64,
+        )
+    }
+}
+
+/// A sample placed on the movie timeline
+#[derive(Debug, PartialEq, Clone)]
+pub struct PresentedSample {
+    /// The index of the sample in decoding order
+    pub index: usize,
+    /// The presentation time in the movie time scale
+    pub movie_time: u64,
+    /// The presentation duration in the movie time scale
+    pub movie_duration: u64,
+}
+
+/// Returns `value` converted from time scale `from` to time scale `to`
+fn rescale(value: u64, from: u32, to: u32) -> u64 {
+    if from == 0 {
+        0
+    } else {
+        (value as u128 * to as u128 / from as u128) as u64
+    }
+}
+
+impl TrakAtom {
+    /// Returns the sample table atom of this track if it has one
+    pub fn stbl_atom(&self) -> Option<&atom::stbl::StblAtom> {
+        self.mdia_atom
+            .minf_atom
+            .as_ref()
+            .map(|minf| minf.stbl_atom())
+    }
+
+    /// Returns the media type given by the handler in 'mdia'
+    pub fn media_type(&self) -> Option<&atom::hdlr::ComponentSubType> {
+        self.mdia_atom
+            .hdlr_atom
+            .as_ref()
+            .map(|hdlr| &hdlr.component_sub_type)
+    }
+
+    /// Returns the first string of the track's '©' user data of `atom_type`
+    pub fn user_text(&self, atom_type: u32) -> Option<&str> {
+        self.udta_atom
+            .as_ref()
+            .and_then(|udta| udta.text(atom_type))
+    }
+
+    /// Returns the IDs of the tracks referred to in 'tref' by `reference_type`
+    pub fn referenced_track_ids(&self, reference_type: TrackReferenceType) -> Vec<u32> {
+        self.tref_atom
+            .as_ref()
+            .map_or_else(Vec::new, |tref| tref.track_ids(reference_type))
+    }
+
+    /// Returns the time scale of the media
+    pub fn media_time_scale(&self) -> u32 {
+        self.mdia_atom.mdhd_atom.time_scale
+    }
+
+    /// Returns the samples of this track in decoding order
+    pub fn samples(&self) -> Vec<Sample> {
+        self.stbl_atom()
+            .map_or_else(Vec::new, |stbl| stbl.samples())
+    }
+
+    /// Returns the edits of this track on the movie timeline
+    ///
+    /// A track without an edit list plays its whole media once from the start.
+    ///
+    /// # Arguments
+    ///
+    /// * `movie_time_scale` - the time scale in 'mvhd'
+    pub fn edits(&self, movie_time_scale: u32) -> Vec<Edit> {
+        let elst = match self.edts_atom.as_ref().and_then(|e| e.elst_atom.as_ref()) {
+            Some(elst) => elst,
+            None => {
+                let mdhd = &self.mdia_atom.mdhd_atom;
+                return vec![Edit {
+                    movie_time: 0,
+                    movie_duration: rescale(
+                        mdhd.duration as u64,
+                        mdhd.time_scale,
+                        movie_time_scale,
+                    ),
+                    media_time: Some(0),
+                    media_rate: FixedU32::<U16>::from_num(1),
+                }];
+            }
+        };
+
+        let mut movie_time = 0;
+
+        elst.edit_list_table
+            .iter()
+            .map(|entry| {
+                let edit = Edit {
+                    movie_time,
+                    movie_duration: entry.track_duration,
+                    media_time: u64::try_from(entry.media_time).ok(),
+                    media_rate: FixedU32::<U16>::from_bits(entry.media_rate.to_bits().max(0) as u32),
+                };
+                movie_time += entry.track_duration;
+                edit
+            })
+            .collect()
+    }
+
+    /// Returns the samples of this track in presentation order on the movie
+    /// timeline
+    ///
+    /// A sample appears once for each edit which shows it, and not at all if no
+    /// edit does.
+    ///
+    /// # Arguments
+    ///
+    /// * `movie_time_scale` - the time scale in 'mvhd'
+    pub fn presentation(&self, movie_time_scale: u32) -> Vec<PresentedSample> {
+        let media_time_scale = self.media_time_scale();
+        let samples = self.samples();
+        let mut presented = Vec::new();
+
+        for edit in self.edits(movie_time_scale) {
+            let media_time = match edit.media_time {
+                Some(t) => t as i64,
+                None => continue,
+            };
+
+            if edit.is_dwell() {
+                if let Some(sample) = find_sample(&samples, media_time) {
+                    presented.push(PresentedSample {
+                        index: sample.index,
+                        movie_time: edit.movie_time,
+                        movie_duration: edit.movie_duration,
+                    });
+                }
+                continue;
+            }
+
+            let rate = edit.media_rate.to_bits() as u128;
+            let edit_tail = edit.movie_time + edit.movie_duration;
+            // the movie time at which `t` in the media is shown
+            let movie_time_of = |t: i64| {
+                let elapsed = ((t - media_time) as u128) << 16;
+                (edit.movie_time
+                    + rescale((elapsed / rate) as u64, media_time_scale, movie_time_scale))
+                .min(edit_tail)
+            };
+            let media_tail = edit
+                .media_time_at(edit_tail, movie_time_scale, media_time_scale)
+                .unwrap_or(0) as i64;
+
+            for sample in &samples {
+                let head = sample.composition_time.max(media_time);
+                let tail = (sample.composition_time + sample.duration as i64).min(media_tail);
+
+                if head < tail {
+                    let movie_head = movie_time_of(head);
+                    presented.push(PresentedSample {
+                        index: sample.index,
+                        movie_time: movie_head,
+                        movie_duration: movie_time_of(tail) - movie_head,
+                    });
+                }
+            }
+        }
+
+        presented.sort_by_key(|s| s.movie_time);
+        presented
+    }
+
+    /// Returns the sample displayed at `movie_time`, or `None` during an empty
+    /// edit or outside the track
+    ///
+    /// # Arguments
+    ///
+    /// * `movie_time_scale` - the time scale in 'mvhd'
+    /// * `movie_time` - a time in the movie time scale
+    pub fn sample_at(&self, movie_time_scale: u32, movie_time: u64) -> Option<Sample> {
+        let edit = self
+            .edits(movie_time_scale)
+            .into_iter()
+            .find(|e| e.movie_time <= movie_time && movie_time < e.movie_time + e.movie_duration)?;
+        let media_time =
+            edit.media_time_at(movie_time, movie_time_scale, self.media_time_scale())?;
+
+        find_sample(&self.samples(), media_time as i64).cloned()
+    }
+
+    /// Returns the sync sample at or before the sample displayed at `seconds`
+    ///
+    /// Every sample is a sync sample when the track has no 'stss'. Returns
+    /// `None` during an empty edit or outside the track.
+    ///
+    /// # Arguments
+    ///
+    /// * `movie_time_scale` - the time scale in 'mvhd'
+    /// * `seconds` - a time on the movie timeline
+    pub fn seek(&self, movie_time_scale: u32, seconds: f64) -> Option<Sample> {
+        if seconds.is_nan() || seconds < 0.0 {
+            return None;
+        }
+
+        let movie_time = (seconds * movie_time_scale as f64) as u64;
+        let target = self.sample_at(movie_time_scale, movie_time)?;
+
+        self.samples()
+            .into_iter()
+            .take(target.index + 1)
+            .rev()
+            .find(|s| s.is_sync)
+    }
+
+    /// Returns the `sample_description_id`th entry in 'stsd'
+    ///
+    /// # Arguments
+    ///
+    /// * `sample_description_id` - a 1-based index into 'stsd'
+    pub fn sample_description(&self, sample_description_id: u32) -> Option<&SampleDescription> {
+        self.stbl_atom()
+            .and_then(|stbl| stbl.stsd_atom.as_ref())
+            .and_then(|stsd| {
+                stsd.sample_description_table
+                    .get((sample_description_id as usize).checked_sub(1)?)
+            })
+    }
+
+    /// Returns the timecode description of the `sample_description_id`th
+    /// entry in 'stsd', or `None` if it is not a 'tmcd' entry
+    ///
+    /// # Arguments
+    ///
+    /// * `sample_description_id` - a 1-based index into 'stsd'
+    pub fn timecode_description(
+        &self,
+        sample_description_id: u32,
+    ) -> Result<Option<TimecodeDescription>, AtomParseError> {
+        match self.sample_description(sample_description_id) {
+            Some(d) if d.data_format == tmcd::ATOM_ID => Ok(Some(TimecodeDescription::new(d)?)),
+            _ => Ok(None),
+        }
+    }
+
+    /// Returns the timecode at `media_time` from the timecode sample in `r`
+    ///
+    /// The frame number stored in the sample is advanced by the frames elapsed
+    /// since the sample starts. Returns `None` if no 'tmcd' sample covers
+    /// `media_time`.
+    ///
+    /// # Arguments
+    ///
+    /// * `r` - the file the track was parsed from
+    /// * `media_time` - a time in the media time scale
+    pub fn timecode_at<R: Read + Seek>(
+        &self,
+        r: &mut R,
+        media_time: u64,
+    ) -> Result<Option<Timecode>, AtomParseError> {
+        let samples = self.samples();
+        let sample = match samples
+            .iter()
+            .find(|s| s.decode_time <= media_time && media_time < s.decode_time + s.duration as u64)
+        {
+            Some(sample) => sample,
+            None => return Ok(None),
+        };
+        let description = match self.timecode_description(sample.sample_description_id)? {
+            Some(description) => description,
+            None => return Ok(None),
+        };
+
+        r.seek(SeekFrom::Start(sample.offset))?;
+        let frame_number = r.read_u32::<BigEndian>()? as u64;
+
+        let elapsed = rescale(
+            media_time - sample.decode_time,
+            self.media_time_scale(),
+            description.time_scale,
+        ) / description.frame_duration.max(1) as u64;
+
+        Ok(Some(description.timecode(frame_number + elapsed)))
+    }
+
+    /// Returns the timecode at the start of the first non-empty edit
+    ///
+    /// # Arguments
+    ///
+    /// * `r` - the file the track was parsed from
+    pub fn start_timecode<R: Read + Seek>(
+        &self,
+        r: &mut R,
+    ) -> Result<Option<Timecode>, AtomParseError> {
+        // media times of edits do not depend on the movie time scale
+        let media_time = self
+            .edits(self.media_time_scale())
+            .iter()
+            .find_map(|e| e.media_time)
+            .unwrap_or(0);
+
+        self.timecode_at(r, media_time)
+    }
+}
+
+/// Returns the sample whose composition interval contains `media_time`
+fn find_sample(samples: &[Sample], media_time: i64) -> Option<&Sample> {
+    samples.iter().find(|s| {
+        s.composition_time <= media_time && media_time < s.composition_time + s.duration as i64
+    })
 }
 
 pub fn parse<R: Read + Seek>(r: &mut R, atom_head: AtomHead) -> Result<TrakAtom, AtomParseError> {
     let mut tkhd_atom: Option<Box<atom::tkhd::TkhdAtom>> = None;
+    let mut clip_atom: Option<Box<atom::clip::ClipAtom>> = None;
+    let mut matt_atom: Option<Box<atom::matt::MattAtom>> = None;
     let mut edts_atom: Option<Box<atom::edts::EdtsAtom>> = None;
+    let mut tref_atom: Option<Box<atom::tref::TrefAtom>> = None;
+    let mut load_atom: Option<Box<atom::load::LoadAtom>> = None;
+    let mut imap_atom: Option<Box<atom::imap::ImapAtom>> = None;
     let mut mdia_atom: Option<Box<atom::mdia::MdiaAtom>> = None;
+    let mut udta_atom: Option<Box<atom::udta::UdtaAtom>> = None;
 
     let atom_tail = atom_head.atom_offset + atom_head.atom_size;
 
-    while let Ok(atom) = atom::parse(r) {
+    while r.seek(SeekFrom::Current(0))? + 8 <= atom_tail {
+        let atom = atom::parse(r)?;
+
         if atom.is::<atom::tkhd::TkhdAtom>() {
             tkhd_atom = Some(atom.downcast::<atom::tkhd::TkhdAtom>().unwrap()); // @todo
+        } else if atom.is::<atom::clip::ClipAtom>() {
+            clip_atom = Some(atom.downcast::<atom::clip::ClipAtom>().unwrap()); // @todo
+        } else if atom.is::<atom::matt::MattAtom>() {
+            matt_atom = Some(atom.downcast::<atom::matt::MattAtom>().unwrap()); // @todo
         } else if atom.is::<atom::edts::EdtsAtom>() {
             edts_atom = Some(atom.downcast::<atom::edts::EdtsAtom>().unwrap()); // @todo
+        } else if atom.is::<atom::tref::TrefAtom>() {
+            tref_atom = Some(atom.downcast::<atom::tref::TrefAtom>().unwrap()); // @todo
+        } else if atom.is::<atom::load::LoadAtom>() {
+            load_atom = Some(atom.downcast::<atom::load::LoadAtom>().unwrap()); // @todo
+        } else if atom.is::<atom::imap::ImapAtom>() {
+            imap_atom = Some(atom.downcast::<atom::imap::ImapAtom>().unwrap()); // @todo
         } else if atom.is::<atom::mdia::MdiaAtom>() {
             mdia_atom = Some(atom.downcast::<atom::mdia::MdiaAtom>().unwrap()); // @todo
+        } else if atom.is::<atom::udta::UdtaAtom>() {
+            udta_atom = Some(atom.downcast::<atom::udta::UdtaAtom>().unwrap()); // @todo
         } else {
             eprintln!("{:?}", atom);
         }
-
-        if r.seek(SeekFrom::Current(0))? >= atom_tail {
-            break;
-        }
     }
 
     let tkhd_atom = match tkhd_atom {
@@ -60,7 +434,13 @@ pub fn parse<R: Read + Seek>(r: &mut R, atom_head: AtomHead) -> Result<TrakAtom,
     Ok(TrakAtom {
         atom_head,
         tkhd_atom,
+        clip_atom,
+        matt_atom,
         edts_atom,
+        tref_atom,
+        load_atom,
+        imap_atom,
         mdia_atom,
+        udta_atom,
     })
 }
@@ -0,0 +1,140 @@
+use std::fmt::{self, Debug};
+use std::io::{Read, Seek, SeekFrom};
+
+use byteorder::{BigEndian, ReadBytesExt};
+use thiserror::Error;
+
+use crate::atom::{self, Atom, AtomHead, AtomParseError};
+use atom_derive::atom;
+
+pub const ATOM_ID: u32 = 0x7472_6566; // 'tref'
+
+/// The reason a track refers to other tracks
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum TrackReferenceType {
+    /// The chapter text tracks of the track
+    Chapter,
+    /// The timecode tracks of the track
+    Timecode,
+    /// The media tracks which a hint track describes
+    Hint,
+    /// The tracks which a timed metadata track describes
+    ContentDescription,
+    /// The tracks to synchronize with
+    Synchronization,
+    /// The font tracks of a text track
+    Font,
+    /// The depth map of a video track
+    AuxiliaryDepthVideo,
+    /// The parallax map of a video track
+    AuxiliaryParallaxVideo,
+    Unknown(u32),
+}
+
+impl TrackReferenceType {
+    pub fn new(t: u32) -> Self {
+        match t {
+            0x6368_6170 => TrackReferenceType::Chapter,  // 'chap'
+            0x746d_6364 => TrackReferenceType::Timecode, // 'tmcd'
+            0x6869_6e74 => TrackReferenceType::Hint,     // 'hint'
+            0x6364_7363 => TrackReferenceType::ContentDescription, // 'cdsc'
+            0x7379_6e63 => TrackReferenceType::Synchronization, // 'sync'
+            0x666f_6e74 => TrackReferenceType::Font,     // 'font'
+            0x7664_6570 => TrackReferenceType::AuxiliaryDepthVideo, // 'vdep'
+            0x7670_6c78 => TrackReferenceType::AuxiliaryParallaxVideo, // 'vplx'
+            _ => TrackReferenceType::Unknown(t),
+        }
+    }
+
+    /// Returns the atom type of the reference
+    pub fn to_u32(self) -> u32 {
+        match self {
+            TrackReferenceType::Chapter => 0x6368_6170,
+            TrackReferenceType::Timecode => 0x746d_6364,
+            TrackReferenceType::Hint => 0x6869_6e74,
+            TrackReferenceType::ContentDescription => 0x6364_7363,
+            TrackReferenceType::Synchronization => 0x7379_6e63,
+            TrackReferenceType::Font => 0x666f_6e74,
+            TrackReferenceType::AuxiliaryDepthVideo => 0x7664_6570,
+            TrackReferenceType::AuxiliaryParallaxVideo => 0x7670_6c78,
+            TrackReferenceType::Unknown(t) => t,
+        }
+    }
+}
+
+impl fmt::Display for TrackReferenceType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", atom::type_to_string(self.to_u32()))
+    }
+}
+
+#[derive(Debug, Error, PartialEq)]
+pub enum TrackReferenceError {
+    #[error("track {track_id} refers to missing track {referenced_track_id} by '{reference_type}' at {atom_offset}")]
+    DanglingReference {
+        /// The offset of the reference type atom
+        atom_offset: u64,
+        track_id: u32,
+        reference_type: TrackReferenceType,
+        referenced_track_id: u32,
+    },
+}
+
+/// The tracks which a track refers to for one reason
+#[atom]
+#[derive(Debug, PartialEq)]
+pub struct TrackReferenceTypeAtom {
+    pub reference_type: TrackReferenceType,
+    pub track_ids: Vec<u32>,
+}
+
+#[atom]
+#[derive(Debug, PartialEq)]
+pub struct TrefAtom {
+    pub track_reference_type_atom: Vec<TrackReferenceTypeAtom>,
+}
+
+impl TrefAtom {
+    /// Returns the IDs of the tracks referred to by the references of
+    /// `reference_type`
+    pub fn track_ids(&self, reference_type: TrackReferenceType) -> Vec<u32> {
+        self.track_reference_type_atom
+            .iter()
+            .filter(|a| a.reference_type == reference_type)
+            .flat_map(|a| a.track_ids.iter().copied())
+            .collect()
+    }
+}
+
+pub fn parse<R: Read + Seek>(r: &mut R, atom_head: AtomHead) -> Result<TrefAtom, AtomParseError> {
+    let mut track_reference_type_atom = Vec::new();
+
+    let atom_tail = atom_head.atom_offset + atom_head.atom_size;
+
+    while r.seek(SeekFrom::Current(0))? + 8 <= atom_tail {
+        let head = atom::parse_atom_head(r)?;
+        let head_tail = head.atom_offset + head.atom_size;
+        if head.atom_size < 8 || head_tail > atom_tail {
+            return Err(AtomParseError::UnexpectedError(head.atom_offset));
+        }
+
+        let mut track_ids = Vec::new();
+        while r.seek(SeekFrom::Current(0))? + 4 <= head_tail {
+            track_ids.push(r.read_u32::<BigEndian>()?);
+        }
+        r.seek(SeekFrom::Start(head_tail))?;
+
+        track_reference_type_atom.push(TrackReferenceTypeAtom {
+            reference_type: TrackReferenceType::new(head.atom_type),
+            atom_head: head,
+            track_ids,
+        });
+    }
+
+    r.seek(SeekFrom::Start(atom_tail))?;
+
+    Ok(TrefAtom {
+        atom_head,
+        track_reference_type_atom,
+    })
+}
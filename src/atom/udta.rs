@@ -0,0 +1,187 @@
+use std::fmt::Debug;
+use std::io::{Cursor, Read, Seek, SeekFrom};
+
+use byteorder::{BigEndian, ReadBytesExt};
+
+use crate::atom::{self, Atom, AtomHead, AtomParseError};
+use atom_derive::atom;
+
+pub const ATOM_ID: u32 = 0x7564_7461; // 'udta'
+
+pub const TITLE: u32 = 0xa96e_616d; // '©nam'
+pub const DATE: u32 = 0xa964_6179; // '©day'
+pub const LOCATION: u32 = 0xa978_797a; // '©xyz'
+pub const MAKE: u32 = 0xa96d_616b; // '©mak'
+pub const MODEL: u32 = 0xa96d_6f64; // '©mod'
+pub const SOFTWARE: u32 = 0xa973_7772; // '©swr'
+pub const AUTHOR: u32 = 0xa961_7574; // '©aut'
+pub const COMMENT: u32 = 0xa963_6d74; // '©cmt'
+pub const COPYRIGHT: u32 = 0xa963_7079; // '©cpy'
+pub const DESCRIPTION: u32 = 0xa964_6573; // '©des'
+pub const INFORMATION: u32 = 0xa969_6e66; // '©inf'
+
+/// A thumbnail image written by some cameras
+pub const THUMBNAIL: u32 = 0x7468_6d62; // 'thmb'
+
+/// A string with its language
+#[derive(Debug, PartialEq, Clone)]
+pub struct InternationalText {
+    /// A Macintosh language code, or a packed ISO 639-2/T code from 0x400
+    pub language: u16,
+    pub text: String,
+}
+
+impl InternationalText {
+    /// Returns the ISO 639-2/T code such as "eng", or `None` for a
+    /// Macintosh language code
+    pub fn language_code(&self) -> Option<String> {
+        if self.language < 0x400 {
+            return None;
+        }
+
+        Some(
+            [10, 5, 0]
+                .iter()
+                .map(|shift| char::from(((self.language >> shift) & 0x1f) as u8 + 0x60))
+                .collect(),
+        )
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum UserData {
+    /// The strings of a '©' atom
+    Text(Vec<InternationalText>),
+    /// The payload of any other or malformed atom
+    Raw(Vec<u8>),
+}
+
+/// An entry in 'udta'
+#[atom]
+#[derive(Debug, PartialEq)]
+pub struct UserDataAtom {
+    pub user_data: UserData,
+}
+
+impl UserDataAtom {
+    /// Returns the first string of a '©' atom
+    pub fn text(&self) -> Option<&str> {
+        match &self.user_data {
+            UserData::Text(texts) => texts.first().map(|t| t.text.as_str()),
+            UserData::Raw(_) => None,
+        }
+    }
+}
+
+#[atom]
+#[derive(Debug, PartialEq)]
+pub struct UdtaAtom {
+    pub user_data_atom: Vec<UserDataAtom>,
+    /// iTunes-style metadata
+    pub meta_atom: Option<Box<atom::meta::MetaAtom>>,
+    pub chpl_atom: Option<Box<atom::chpl::ChplAtom>>,
+}
+
+impl UdtaAtom {
+    /// Returns the first entry of `atom_type`
+    pub fn get(&self, atom_type: u32) -> Option<&UserDataAtom> {
+        self.user_data_atom
+            .iter()
+            .find(|a| a.atom_head.atom_type == atom_type)
+    }
+
+    /// Returns the first string of the '©' atom of `atom_type`
+    ///
+    /// # Arguments
+    ///
+    /// * `atom_type` - such as [`TITLE`] or [`LOCATION`]
+    pub fn text(&self, atom_type: u32) -> Option<&str> {
+        self.get(atom_type).and_then(|a| a.text())
+    }
+}
+
+/// Returns the strings in `data`, or `None` unless they fill it exactly
+fn parse_international_text(data: &[u8]) -> Option<Vec<InternationalText>> {
+    let mut r = Cursor::new(data);
+    let mut texts = Vec::new();
+
+    while (r.position() as usize) < data.len() {
+        let size = r.read_u16::<BigEndian>().ok()?;
+        let language = r.read_u16::<BigEndian>().ok()?;
+        let start = r.position() as usize;
+        let text = data.get(start..start + size as usize)?;
+
+        texts.push(InternationalText {
+            language,
+            text: String::from_utf8_lossy(text).into_owned(),
+        });
+        r.set_position((start + size as usize) as u64);
+    }
+
+    Some(texts)
+}
+
+pub fn parse<R: Read + Seek>(r: &mut R, atom_head: AtomHead) -> Result<UdtaAtom, AtomParseError> {
+    let mut user_data_atom = Vec::new();
+    let mut meta_atom = None;
+    let mut chpl_atom = None;
+
+    let atom_tail = atom_head.atom_offset + atom_head.atom_size;
+
+    while r.seek(SeekFrom::Current(0))? + 8 <= atom_tail {
+        let head = atom::parse_atom_head(r)?;
+
+        // a 32-bit zero may terminate the list
+        if head.atom_size < 8 || head.atom_offset + head.atom_size > atom_tail {
+            break;
+        }
+
+        if head.atom_type == atom::meta::ATOM_ID {
+            let head_tail = head.atom_offset + head.atom_size;
+            meta_atom = Some(Box::new(atom::meta::parse(r, head)?));
+            r.seek(SeekFrom::Start(head_tail))?;
+            continue;
+        }
+        if head.atom_type == atom::chpl::ATOM_ID {
+            let payload_offset = r.seek(SeekFrom::Current(0))?;
+            match atom::chpl::parse(r, head.clone()) {
+                Ok(chpl) => {
+                    chpl_atom = Some(Box::new(chpl));
+                    continue;
+                }
+                // a malformed chapter list is kept as raw user data
+                Err(_) => {
+                    r.seek(SeekFrom::Start(payload_offset))?;
+                }
+            }
+        }
+
+        let header_size = r.seek(SeekFrom::Current(0))? - head.atom_offset;
+        let mut data = Vec::new();
+        r.take(head.atom_size - header_size)
+            .read_to_end(&mut data)?;
+
+        let user_data = if head.atom_type >> 24 == 0xa9 {
+            match parse_international_text(&data) {
+                Some(texts) => UserData::Text(texts),
+                None => UserData::Raw(data),
+            }
+        } else {
+            UserData::Raw(data)
+        };
+
+        user_data_atom.push(UserDataAtom {
+            atom_head: head,
+            user_data,
+        });
+    }
+
+    r.seek(SeekFrom::Start(atom_tail))?;
+
+    Ok(UdtaAtom {
+        atom_head,
+        user_data_atom,
+        meta_atom,
+        chpl_atom,
+    })
+}
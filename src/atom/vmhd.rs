@@ -1,16 +1,40 @@
 use std::fmt::Debug;
 use std::io::{Read, Seek, SeekFrom};
 
+use byteorder::{BigEndian, ReadBytesExt};
+
 use crate::atom::{Atom, AtomHead, AtomParseError};
 use atom_derive::atom;
 
 pub const ATOM_ID: u32 = 0x766d_6864; // 'vmhd'
 
-#[atom]
+#[atom(version(no_lean_ahead = 0x0001))]
 #[derive(Debug, PartialEq)]
-pub struct VmhdAtom {}
+pub struct VmhdAtom {
+    /// The transfer mode of the video
+    pub graphics_mode: u16,
+    /// The red, green and blue of the transfer mode
+    pub opcolor: [u16; 3],
+}
 
 pub fn parse<R: Read + Seek>(r: &mut R, atom_head: AtomHead) -> Result<VmhdAtom, AtomParseError> {
+    let atom_version = r.read_u8()?;
+    let mut atom_flags = [0_u8; 3];
+    r.read_exact(&mut atom_flags)?;
+
+    let graphics_mode = r.read_u16::<BigEndian>()?;
+    let mut opcolor = [0_u16; 3];
+    for color in &mut opcolor {
+        *color = r.read_u16::<BigEndian>()?;
+    }
+
     r.seek(SeekFrom::Start(atom_head.atom_offset + atom_head.atom_size))?;
-    Ok(VmhdAtom { atom_head })
+
+    Ok(VmhdAtom {
+        atom_head,
+        atom_version,
+        atom_flags,
+        graphics_mode,
+        opcolor,
+    })
 }
@@ -1,21 +1,422 @@
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Write};
 use std::path::PathBuf;
+use std::str::FromStr;
 
 use clap::Clap;
 
-use atom_analyzer::qtfile::{self, QtFileError};
+use atom_analyzer::analysis::samples::{self, TimeUnit};
+use atom_analyzer::analysis::{self, layout};
+use atom_analyzer::atom::moov::MoovAtom;
+use atom_analyzer::atom::trak::TrakAtom;
+use atom_analyzer::metadata::write::MetadataWriter;
+use atom_analyzer::qtfile::{self, QtFile, QtFileError};
+use atom_analyzer::subtitles;
+use atom_analyzer::validate::{self, Severity};
 
 #[derive(Clap)]
 #[clap(name=env!("CARGO_PKG_NAME"))]
 struct Opts {
+    #[clap(name = "INPUT")]
+    input: Option<PathBuf>,
+
+    #[clap(subcommand)]
+    subcmd: Option<SubCommand>,
+}
+
+#[derive(Clap)]
+enum SubCommand {
+    /// Checks the structure of a file against the QuickTime and ISO BMFF specs
+    Validate(Validate),
+    /// Compares two files atom by atom
+    Diff(Diff),
+    /// Prints the atoms selected by a path such as `moov/trak[0]/mdia/hdlr`
+    Get(Get),
+    /// Prints the sync sample to start decoding from to show a time in seconds
+    Seek(Seek),
+    /// Prints the sample table of a track
+    Samples(Samples),
+    /// Reports GOP lengths, bitrates, frame durations and interleave per track
+    Analyze(Analyze),
+    /// Maps the chunks of all tracks in 'mdat'
+    Layout(Layout),
+    /// Prints the metadata in 'keys' and 'ilst'
+    Metadata(Metadata),
+    /// Writes a copy without location, serial number or the given metadata
+    StripMetadata(StripMetadata),
+    /// Lists the embedded cover art, thumbnails and previews
+    Artwork(Artwork),
+    /// Prints the start times and titles of the chapters
+    Chapters(Chapters),
+    /// Exports the cues of a subtitle or closed caption track
+    Subtitles(Subtitles),
+}
+
+#[derive(Clap)]
+struct Validate {
+    #[clap(name = "INPUT")]
+    input: PathBuf,
+}
+
+#[derive(Clap)]
+struct Diff {
+    #[clap(name = "LEFT")]
+    left: PathBuf,
+    #[clap(name = "RIGHT")]
+    right: PathBuf,
+}
+
+#[derive(Clap)]
+struct Get {
+    #[clap(name = "INPUT")]
+    input: PathBuf,
+    #[clap(name = "PATH")]
+    path: String,
+}
+
+#[derive(Clap)]
+struct Seek {
+    #[clap(name = "INPUT")]
+    input: PathBuf,
+    #[clap(name = "SECONDS")]
+    seconds: f64,
+    /// The track ID (default: the first track)
+    #[clap(long)]
+    track: Option<u32>,
+}
+
+#[derive(Clap)]
+struct Samples {
+    #[clap(name = "INPUT")]
+    input: PathBuf,
+    /// The track ID (default: the first track)
+    #[clap(long)]
+    track: Option<u32>,
+    /// Prints comma-separated values
+    #[clap(long)]
+    csv: bool,
+    /// The unit of DTS, CTS and duration
+    #[clap(long, default_value = "ticks", possible_values = &["ticks", "seconds"])]
+    time: TimeUnit,
+}
+
+#[derive(Clap)]
+struct Analyze {
+    #[clap(name = "INPUT")]
+    input: PathBuf,
+    /// The bitrate window in seconds
+    #[clap(long, default_value = "1")]
+    window: f64,
+}
+
+#[derive(Clap)]
+struct Layout {
+    #[clap(name = "INPUT")]
+    input: PathBuf,
+    /// Prints JSON
+    #[clap(long)]
+    json: bool,
+    /// The number of cells in the map
+    #[clap(long, default_value = "64")]
+    width: usize,
+}
+
+#[derive(Clap)]
+struct Metadata {
+    #[clap(name = "INPUT")]
+    input: PathBuf,
+}
+
+#[derive(Clap)]
+struct StripMetadata {
     #[clap(name = "INPUT")]
     input: PathBuf,
+    #[clap(name = "OUTPUT")]
+    output: PathBuf,
+    /// A key to remove instead of location and serial numbers
+    #[clap(long)]
+    key: Vec<String>,
+    /// Removes all metadata and '©' user data
+    #[clap(long)]
+    all: bool,
+}
+
+#[derive(Clap)]
+struct Artwork {
+    #[clap(name = "INPUT")]
+    input: PathBuf,
+    /// A directory to write the images to as `artwork-N.<ext>`
+    #[clap(long)]
+    extract: Option<PathBuf>,
+}
+
+#[derive(Clap)]
+struct Chapters {
+    #[clap(name = "INPUT")]
+    input: PathBuf,
+}
+
+#[derive(Clap)]
+struct Subtitles {
+    #[clap(name = "INPUT")]
+    input: PathBuf,
+    /// The track ID (default: the first subtitle track)
+    #[clap(long)]
+    track: Option<u32>,
+    /// The output format
+    #[clap(long, default_value = "srt", possible_values = &["srt", "webvtt"])]
+    format: SubtitleExport,
+}
+
+enum SubtitleExport {
+    Srt,
+    WebVtt,
+}
+
+impl FromStr for SubtitleExport {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "srt" => Ok(SubtitleExport::Srt),
+            "webvtt" => Ok(SubtitleExport::WebVtt),
+            _ => Err(format!("invalid subtitle format `{}'", s)),
+        }
+    }
+}
+
+/// Returns 'moov' and the track with `track_id` or the first track, or exits
+fn select_track(qt: &QtFile, track_id: Option<u32>) -> (&MoovAtom, &TrakAtom) {
+    let moov = match qt.moov() {
+        Some(moov) => moov,
+        None => {
+            eprintln!("error: no 'moov' atom");
+            std::process::exit(2);
+        }
+    };
+    let trak = match track_id {
+        Some(id) => moov.track(id),
+        None => moov.trak_atom.first(),
+    };
+
+    match trak {
+        Some(trak) => (moov, trak),
+        None => {
+            eprintln!("error: no such track");
+            std::process::exit(2);
+        }
+    }
 }
 
 fn main() -> Result<(), QtFileError> {
     let opts = Opts::parse();
 
-    let t = qtfile::parse_file(opts.input)?;
-    println!("{:#?}", t);
+    match opts.subcmd {
+        Some(SubCommand::Validate(v)) => {
+            let t = qtfile::parse_file(v.input)?;
+            let findings = validate::validate(&t);
+
+            for finding in &findings {
+                println!("{}", finding);
+            }
+
+            if findings.iter().any(|f| f.severity == Severity::Error) {
+                std::process::exit(1);
+            }
+        }
+        Some(SubCommand::Diff(d)) => {
+            let left = qtfile::parse_file(d.left)?;
+            let right = qtfile::parse_file(d.right)?;
+            let diff = left.diff(&right);
+
+            print!("{}", diff);
+
+            if !diff.is_empty() {
+                std::process::exit(1);
+            }
+        }
+        Some(SubCommand::Get(g)) => {
+            let t = qtfile::parse_file(g.input)?;
+            let atoms = match t.select(&g.path) {
+                Ok(atoms) => atoms,
+                Err(e) => {
+                    eprintln!("error: {}", e);
+                    std::process::exit(2);
+                }
+            };
+
+            for atom in &atoms {
+                println!("{:#?}", atom);
+            }
+
+            if atoms.is_empty() {
+                std::process::exit(1);
+            }
+        }
+        Some(SubCommand::Seek(s)) => {
+            let t = qtfile::parse_file(s.input)?;
+            let (moov, trak) = select_track(&t, s.track);
+
+            let sample = match trak.seek(moov.time_scale(), s.seconds) {
+                Some(sample) => sample,
+                None => {
+                    eprintln!("no sample is displayed at {}s", s.seconds);
+                    std::process::exit(1);
+                }
+            };
+            let media_time_scale = trak.media_time_scale() as f64;
+
+            println!("track: {}", trak.tkhd_atom.track_id);
+            println!("sample: {}", sample.index);
+            println!("offset: 0x{:x}", sample.offset);
+            println!("size: {}", sample.size);
+            println!(
+                "dts: {} ({:.6}s)",
+                sample.decode_time,
+                sample.decode_time as f64 / media_time_scale
+            );
+            println!(
+                "cts: {} ({:.6}s)",
+                sample.composition_time,
+                sample.composition_time as f64 / media_time_scale
+            );
+        }
+        Some(SubCommand::Samples(s)) => {
+            let t = qtfile::parse_file(s.input)?;
+            let (_, trak) = select_track(&t, s.track);
+            let time_scale = trak.media_time_scale();
+            let samples = trak.samples();
+            let mut out = std::io::stdout();
+
+            if s.csv {
+                samples::write_csv(&mut out, &samples, time_scale, s.time)?;
+            } else {
+                samples::write_table(&mut out, &samples, time_scale, s.time)?;
+            }
+        }
+        Some(SubCommand::Analyze(a)) => {
+            let t = qtfile::parse_file(a.input)?;
+            print!("{}", analysis::analyze(&t, a.window));
+        }
+        Some(SubCommand::Layout(l)) => {
+            let t = qtfile::parse_file(l.input)?;
+            let layout = layout::layout(&t);
+
+            if l.json {
+                println!("{}", layout.to_json());
+            } else {
+                println!("[{}]", layout.ascii_map(l.width));
+                print!("{}", layout);
+            }
+
+            if !layout.issues.is_empty() {
+                std::process::exit(1);
+            }
+        }
+        Some(SubCommand::Metadata(m)) => {
+            let t = qtfile::parse_file(m.input)?;
+            let metadata = t.metadata();
+
+            print!("{}", metadata);
+
+            if metadata.is_empty() {
+                std::process::exit(1);
+            }
+        }
+        Some(SubCommand::StripMetadata(s)) => {
+            if s.input == s.output {
+                eprintln!("error: OUTPUT must differ from INPUT");
+                std::process::exit(2);
+            }
+
+            let mut writer = MetadataWriter::new();
+            if s.all {
+                writer.remove_all();
+            } else if s.key.is_empty() {
+                writer.remove_sensitive();
+            }
+            for key in &s.key {
+                writer.remove(key);
+            }
+
+            let mut r = BufReader::new(File::open(s.input)?);
+            let mut w = BufWriter::new(File::create(s.output)?);
+            if let Err(e) = writer.write(&mut r, &mut w).and_then(|_| Ok(w.flush()?)) {
+                eprintln!("error: {}", e);
+                std::process::exit(1);
+            }
+        }
+        Some(SubCommand::Artwork(a)) => {
+            let t = qtfile::parse_file(a.input)?;
+            let artwork = t.artwork();
+
+            for (i, image) in artwork.iter().enumerate() {
+                println!("{}: {}", i, image);
+            }
+
+            if let Some(dir) = a.extract {
+                for (i, image) in artwork.iter().enumerate() {
+                    let path = dir.join(format!("artwork-{}.{}", i, image.format.extension()));
+                    std::fs::write(&path, &image.data)?;
+                    println!("wrote {}", path.display());
+                }
+            }
+
+            if artwork.is_empty() {
+                std::process::exit(1);
+            }
+        }
+        Some(SubCommand::Chapters(c)) => {
+            let mut r = BufReader::new(File::open(&c.input)?);
+            let t = qtfile::parse(&mut r)?;
+            let chapters = t.chapters(&mut r)?;
+
+            for chapter in &chapters {
+                println!("{}", chapter);
+            }
+
+            if chapters.is_empty() {
+                std::process::exit(1);
+            }
+        }
+        Some(SubCommand::Subtitles(s)) => {
+            let mut r = BufReader::new(File::open(&s.input)?);
+            let t = qtfile::parse(&mut r)?;
+            let trak = match s.track {
+                Some(id) => select_track(&t, Some(id)).1,
+                None => match subtitles::subtitle_tracks(&t).first() {
+                    Some(trak) => trak,
+                    None => {
+                        eprintln!("no subtitle track");
+                        std::process::exit(1);
+                    }
+                },
+            };
+
+            let movie_time_scale = t.moov().map_or(0, |moov| moov.time_scale());
+            let cues = subtitles::subtitles(trak, movie_time_scale, &mut r)?;
+            match s.format {
+                SubtitleExport::Srt => print!("{}", subtitles::to_srt(&cues)),
+                SubtitleExport::WebVtt => print!("{}", subtitles::to_webvtt(&cues)),
+            }
+
+            if cues.is_empty() {
+                std::process::exit(1);
+            }
+        }
+        None => {
+            let input = match opts.input {
+                Some(input) => input,
+                None => {
+                    eprintln!("error: INPUT or a subcommand is required");
+                    std::process::exit(2);
+                }
+            };
+
+            let t = qtfile::parse_file(input)?;
+            println!("{:#?}", t);
+        }
+    }
 
     Ok(())
 }
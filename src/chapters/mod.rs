@@ -0,0 +1,150 @@
+use std::fmt;
+use std::io::{self, Read, Seek, SeekFrom};
+
+use crate::atom::chpl;
+use crate::atom::trak::TrakAtom;
+use crate::atom::tref::TrackReferenceType;
+use crate::atom::AtomParseError;
+use crate::qtfile::QtFile;
+
+const TEXT_FORMAT: u32 = 0x7465_7874; // 'text'
+const TX3G_FORMAT: u32 = 0x7478_3367; // 'tx3g'
+
+/// A chapter marker
+#[derive(Debug, PartialEq, Clone)]
+pub struct Chapter {
+    /// The start in `time_scale`
+    pub start_time: u64,
+    pub time_scale: u32,
+    pub title: String,
+}
+
+impl Chapter {
+    /// Returns the start in seconds
+    pub fn seconds(&self) -> f64 {
+        if self.time_scale == 0 {
+            0.0
+        } else {
+            self.start_time as f64 / self.time_scale as f64
+        }
+    }
+}
+
+impl fmt::Display for Chapter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let millis = (self.seconds() * 1000.0).round() as u64;
+        write!(
+            f,
+            "{:02}:{:02}:{:02}.{:03} {}",
+            millis / 3_600_000,
+            millis / 60_000 % 60,
+            millis / 1000 % 60,
+            millis % 1000,
+            self.title
+        )
+    }
+}
+
+/// Returns the string in a 'text' or 'tx3g' sample
+///
+/// The sample starts with the length of the string, which is UTF-16 if it
+/// begins with a byte order mark and UTF-8 otherwise.
+pub fn decode_text_sample(data: &[u8]) -> String {
+    let size = match data.get(..2) {
+        Some(b) => u16::from_be_bytes([b[0], b[1]]) as usize,
+        None => return String::new(),
+    };
+    let text = &data[2..data.len().min(2 + size)];
+
+    match text {
+        [0xfe, 0xff, rest @ ..] => String::from_utf16_lossy(
+            &rest
+                .chunks_exact(2)
+                .map(|c| u16::from_be_bytes([c[0], c[1]]))
+                .collect::<Vec<_>>(),
+        ),
+        _ => String::from_utf8_lossy(text).into_owned(),
+    }
+}
+
+/// Returns whether the 'stsd' entries of `trak` are text sample entries
+fn is_text_track(trak: &TrakAtom) -> bool {
+    trak.stbl_atom()
+        .and_then(|stbl| stbl.stsd_atom.as_ref())
+        .map_or(false, |stsd| {
+            !stsd.sample_description_table.is_empty()
+                && stsd
+                    .sample_description_table
+                    .iter()
+                    .all(|d| d.data_format == TEXT_FORMAT || d.data_format == TX3G_FORMAT)
+        })
+}
+
+/// Returns the first text track which a 'tref/chap' refers to
+pub fn chapter_track(qt: &QtFile) -> Option<&TrakAtom> {
+    let moov = qt.moov()?;
+
+    moov.trak_atom
+        .iter()
+        .flat_map(|t| t.referenced_track_ids(TrackReferenceType::Chapter))
+        .filter_map(|id| moov.track(id))
+        .find(|t| is_text_track(t))
+}
+
+/// Returns the chapters of the movie in `r`
+///
+/// The chapter track is read if there is one, and the 'chpl' in 'moov/udta'
+/// otherwise. Chapter track times are in the movie time scale and 'chpl'
+/// times are in 100 nanoseconds.
+///
+/// # Arguments
+///
+/// * `r` - the file `qt` was parsed from
+pub fn chapters<R: Read + Seek>(qt: &QtFile, r: &mut R) -> Result<Vec<Chapter>, AtomParseError> {
+    let moov = match qt.moov() {
+        Some(moov) => moov,
+        None => return Ok(Vec::new()),
+    };
+
+    if let Some(trak) = chapter_track(qt) {
+        let time_scale = moov.time_scale();
+        let samples = trak.samples();
+        let mut chapters = Vec::new();
+
+        for presented in trak.presentation(time_scale) {
+            let sample = &samples[presented.index];
+            let mut data = Vec::new();
+            r.seek(SeekFrom::Start(sample.offset))?;
+            // the size is read from the file, so it is not trusted for an
+            // allocation
+            r.by_ref().take(sample.size as u64).read_to_end(&mut data)?;
+            if data.len() < sample.size as usize {
+                return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into());
+            }
+
+            chapters.push(Chapter {
+                start_time: presented.movie_time,
+                time_scale,
+                title: decode_text_sample(&data),
+            });
+        }
+
+        return Ok(chapters);
+    }
+
+    let chpl = moov
+        .udta_atom
+        .as_ref()
+        .and_then(|udta| udta.chpl_atom.as_ref());
+
+    Ok(chpl.map_or_else(Vec::new, |chpl| {
+        chpl.chapter_list_table
+            .iter()
+            .map(|entry| Chapter {
+                start_time: entry.start_time,
+                time_scale: chpl::TIME_SCALE,
+                title: entry.title.clone(),
+            })
+            .collect()
+    }))
+}
@@ -0,0 +1,495 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+use crate::atom::{self, Atom, AtomHead};
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum DiffEntry {
+    /// The atom exists only in the right file
+    Added { path: String, atom_head: AtomHead },
+    /// The atom exists only in the left file
+    Removed { path: String, atom_head: AtomHead },
+    /// A scalar field of the atom differs
+    FieldChanged {
+        path: String,
+        field: &'static str,
+        left: String,
+        right: String,
+    },
+    /// A table of the atom differs
+    TableChanged {
+        path: String,
+        table: &'static str,
+        left_len: usize,
+        right_len: usize,
+        first_difference: usize,
+    },
+}
+
+impl fmt::Display for DiffEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DiffEntry::Added { path, atom_head } => write!(
+                f,
+                "+ {} (0x{:x} bytes at 0x{:x})",
+                path, atom_head.atom_size, atom_head.atom_offset
+            ),
+            DiffEntry::Removed { path, atom_head } => write!(
+                f,
+                "- {} (0x{:x} bytes at 0x{:x})",
+                path, atom_head.atom_size, atom_head.atom_offset
+            ),
+            DiffEntry::FieldChanged {
+                path,
+                field,
+                left,
+                right,
+            } => write!(f, "~ {}.{}: {} -> {}", path, field, left, right),
+            DiffEntry::TableChanged {
+                path,
+                table,
+                left_len,
+                right_len,
+                first_difference,
+            } => write!(
+                f,
+                "~ {}.{}: {} -> {} entries, first difference at index {}",
+                path, table, left_len, right_len, first_difference
+            ),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct AtomDiff {
+    pub entries: Vec<DiffEntry>,
+}
+
+impl AtomDiff {
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+impl fmt::Display for AtomDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for entry in &self.entries {
+            writeln!(f, "{}", entry)?;
+        }
+        Ok(())
+    }
+}
+
+/// An atom with its position in the tree
+struct Node<'a> {
+    /// path with an index on every segment, used to align both trees
+    key: String,
+    /// path shown to the user, where a lone atom of its type has no index
+    path: String,
+    atom: &'a dyn Atom,
+}
+
+fn flatten<'a>(atoms: &[&'a dyn Atom], key: &str, path: &str, nodes: &mut Vec<Node<'a>>) {
+    let mut counts = HashMap::new();
+    for atom in atoms {
+        *counts.entry(atom.head().atom_type).or_insert(0) += 1;
+    }
+
+    let mut indices = HashMap::new();
+
+    for atom in atoms {
+        let atom_type = atom.head().atom_type;
+        let name = atom.fourcc();
+        let index = indices.entry(atom_type).or_insert(0);
+
+        let child_key = format!("{}/{}[{}]", key, name, index);
+        let child_path = match (counts[&atom_type], path) {
+            (1, "") => name,
+            (1, _) => format!("{}/{}", path, name),
+            (_, "") => format!("{}[{}]", name, index),
+            (_, _) => format!("{}/{}[{}]", path, name, index),
+        };
+        *index += 1;
+
+        nodes.push(Node {
+            key: child_key.clone(),
+            path: child_path.clone(),
+            atom: *atom,
+        });
+
+        flatten(&atom.children(), &child_key, &child_path, nodes);
+    }
+}
+
+#[derive(Default)]
+struct Fields {
+    scalars: Vec<(&'static str, String)>,
+    tables: Vec<(&'static str, Vec<String>)>,
+}
+
+/// Returns the fields of `atom` which are compared, except its children
+fn fields(atom: &dyn Atom) -> Fields {
+    macro_rules! scalars {
+        ($a:expr, $($f:ident),*) => {
+            vec![$((stringify!($f), format!("{:?}", $a.$f))),*]
+        };
+    }
+
+    macro_rules! tables {
+        ($a:expr, $($f:ident),*) => {
+            vec![$((stringify!($f), $a.$f.iter().map(|e| format!("{:?}", e)).collect())),*]
+        };
+    }
+
+    if let Some(a) = atom.downcast_ref::<atom::ftyp::FtypAtom>() {
+        Fields {
+            scalars: scalars!(a, major_brand, minor_version),
+            tables: tables!(a, compatible_brands),
+        }
+    } else if let Some(a) = atom.downcast_ref::<atom::mvhd::MvhdAtom>() {
+        Fields {
+            scalars: scalars!(
+                a,
+                atom_version,
+                atom_flags,
+                creation_time,
+                modification_time,
+                time_scale,
+                duration,
+                preferred_rate,
+                preferred_volume,
+                matrix_structure,
+                preview_time,
+                preview_duration,
+                poster_time,
+                selection_time,
+                selection_duration,
+                current_time,
+                next_track_id
+            ),
+            ..Default::default()
+        }
+    } else if let Some(a) = atom.downcast_ref::<atom::tkhd::TkhdAtom>() {
+        Fields {
+            scalars: scalars!(
+                a,
+                atom_version,
+                atom_flags,
+                creation_time,
+                modification_time,
+                track_id,
+                duration,
+                layer,
+                alternate_group,
+                volume,
+                matrix_structure,
+                track_width,
+                track_height
+            ),
+            ..Default::default()
+        }
+    } else if let Some(a) = atom.downcast_ref::<atom::mdhd::MdhdAtom>() {
+        Fields {
+            scalars: scalars!(
+                a,
+                atom_version,
+                atom_flags,
+                creation_time,
+                modification_time,
+                time_scale,
+                duration,
+                language,
+                quality
+            ),
+            ..Default::default()
+        }
+    } else if let Some(a) = atom.downcast_ref::<atom::elst::ElstAtom>() {
+        Fields {
+            scalars: scalars!(a, atom_version, atom_flags),
+            tables: tables!(a, edit_list_table),
+        }
+    } else if let Some(a) = atom.downcast_ref::<atom::hdlr::HdlrAtom>() {
+        Fields {
+            scalars: scalars!(
+                a,
+                atom_version,
+                atom_flags,
+                component_type,
+                component_sub_type,
+                component_manufacturer,
+                component_flags,
+                component_flags_mask,
+                component_name
+            ),
+            ..Default::default()
+        }
+    } else if let Some(a) = atom.downcast_ref::<atom::vmhd::VmhdAtom>() {
+        Fields {
+            scalars: scalars!(a, atom_version, atom_flags, graphics_mode, opcolor),
+            ..Default::default()
+        }
+    } else if let Some(a) = atom.downcast_ref::<atom::smhd::SmhdAtom>() {
+        Fields {
+            scalars: scalars!(a, atom_version, atom_flags, balance),
+            ..Default::default()
+        }
+    } else if let Some(a) = atom.downcast_ref::<atom::dref::DrefAtom>() {
+        Fields {
+            scalars: scalars!(a, atom_version, atom_flags, number_of_entries),
+            ..Default::default()
+        }
+    } else if let Some(a) = atom.downcast_ref::<atom::dref::DataReferenceAtom>() {
+        Fields {
+            scalars: scalars!(a, atom_version, atom_flags, data_reference),
+            ..Default::default()
+        }
+    } else if let Some(a) = atom.downcast_ref::<atom::stsd::StsdAtom>() {
+        Fields {
+            scalars: scalars!(a, atom_version, atom_flags),
+            tables: tables!(a, sample_description_table),
+        }
+    } else if let Some(a) = atom.downcast_ref::<atom::stts::SttsAtom>() {
+        Fields {
+            scalars: scalars!(a, atom_version, atom_flags),
+            tables: tables!(a, time_to_sample_table),
+        }
+    } else if let Some(a) = atom.downcast_ref::<atom::stss::StssAtom>() {
+        Fields {
+            scalars: scalars!(a, atom_version, atom_flags),
+            tables: tables!(a, sync_sample_table),
+        }
+    } else if let Some(a) = atom.downcast_ref::<atom::ctts::CttsAtom>() {
+        Fields {
+            scalars: scalars!(a, atom_version, atom_flags),
+            tables: tables!(a, composition_offset_table),
+        }
+    } else if let Some(a) = atom.downcast_ref::<atom::stsc::StscAtom>() {
+        Fields {
+            scalars: scalars!(a, atom_version, atom_flags),
+            tables: tables!(a, sample_to_chunk_table),
+        }
+    } else if let Some(a) = atom.downcast_ref::<atom::stsz::StszAtom>() {
+        Fields {
+            scalars: scalars!(a, atom_version, atom_flags, sample_size),
+            tables: tables!(a, sample_size_table),
+        }
+    } else if let Some(a) = atom.downcast_ref::<atom::stco::StcoAtom>() {
+        Fields {
+            scalars: scalars!(a, atom_version, atom_flags),
+            tables: tables!(a, chunk_offset_table),
+        }
+    } else if let Some(a) = atom.downcast_ref::<atom::co64::Co64Atom>() {
+        Fields {
+            scalars: scalars!(a, atom_version, atom_flags),
+            tables: tables!(a, chunk_offset_table),
+        }
+    } else if let Some(a) = atom.downcast_ref::<atom::udta::UserDataAtom>() {
+        Fields {
+            scalars: scalars!(a, user_data),
+            ..Default::default()
+        }
+    } else if let Some(a) = atom.downcast_ref::<atom::chpl::ChplAtom>() {
+        Fields {
+            scalars: scalars!(a, atom_version, atom_flags),
+            tables: tables!(a, chapter_list_table),
+        }
+    } else if let Some(a) = atom.downcast_ref::<atom::tref::TrackReferenceTypeAtom>() {
+        Fields {
+            scalars: scalars!(a, reference_type),
+            tables: tables!(a, track_ids),
+        }
+    } else if let Some(a) = atom.downcast_ref::<atom::meta::MetaAtom>() {
+        Fields {
+            scalars: scalars!(a, atom_version_and_flags),
+            ..Default::default()
+        }
+    } else if let Some(a) = atom.downcast_ref::<atom::keys::KeysAtom>() {
+        Fields {
+            scalars: scalars!(a, atom_version, atom_flags, entry_count),
+            tables: tables!(a, key_table),
+        }
+    } else if let Some(a) = atom.downcast_ref::<atom::ilst::ItemAtom>() {
+        Fields {
+            scalars: scalars!(a, mean, name),
+            ..Default::default()
+        }
+    } else if let Some(a) = atom.downcast_ref::<atom::ilst::DataAtom>() {
+        Fields {
+            scalars: scalars!(a, type_indicator, locale),
+            tables: tables!(a, value),
+        }
+    } else if let Some(a) = atom.downcast_ref::<atom::gmin::GminAtom>() {
+        Fields {
+            scalars: scalars!(a, atom_version, atom_flags, graphics_mode, opcolor, balance),
+            ..Default::default()
+        }
+    } else if let Some(a) = atom.downcast_ref::<atom::tcmi::TcmiAtom>() {
+        Fields {
+            scalars: scalars!(
+                a,
+                atom_version,
+                atom_flags,
+                text_font,
+                text_face,
+                text_size,
+                text_color,
+                background_color,
+                font_name
+            ),
+            ..Default::default()
+        }
+    } else if let Some(a) = atom.downcast_ref::<atom::hmhd::HmhdAtom>() {
+        Fields {
+            scalars: scalars!(
+                a,
+                atom_version,
+                atom_flags,
+                max_pdu_size,
+                avg_pdu_size,
+                max_bitrate,
+                avg_bitrate
+            ),
+            ..Default::default()
+        }
+    } else if let Some(a) = atom.downcast_ref::<atom::load::LoadAtom>() {
+        Fields {
+            scalars: scalars!(
+                a,
+                preload_start_time,
+                preload_duration,
+                preload_flags,
+                default_hints
+            ),
+            ..Default::default()
+        }
+    } else if let Some(a) = atom.downcast_ref::<atom::imap::TrackInputAtom>() {
+        Fields {
+            scalars: scalars!(a, atom_id, input_type, object_id),
+            ..Default::default()
+        }
+    } else if let Some(a) = atom.downcast_ref::<atom::crgn::CrgnAtom>() {
+        Fields {
+            scalars: scalars!(a, region_size, region_bounding_box),
+            tables: tables!(a, clipping_region_data),
+        }
+    } else if let Some(a) = atom.downcast_ref::<atom::kmat::KmatAtom>() {
+        Fields {
+            scalars: scalars!(a, atom_version, atom_flags, matte_image_description),
+            tables: tables!(a, matte_data),
+        }
+    } else if let Some(a) = atom.downcast_ref::<atom::pnot::PnotAtom>() {
+        Fields {
+            scalars: scalars!(
+                a,
+                modification_date,
+                version_number,
+                preview_type,
+                atom_index
+            ),
+            ..Default::default()
+        }
+    } else if let Some(a) = atom.downcast_ref::<atom::pict::PictAtom>() {
+        Fields {
+            tables: tables!(a, data),
+            ..Default::default()
+        }
+    } else {
+        Fields::default()
+    }
+}
+
+fn compare(path: &str, left: &dyn Atom, right: &dyn Atom, entries: &mut Vec<DiffEntry>) {
+    let left = fields(left);
+    let right = fields(right);
+
+    for ((field, l), (_, r)) in left.scalars.into_iter().zip(right.scalars) {
+        if l != r {
+            entries.push(DiffEntry::FieldChanged {
+                path: path.to_string(),
+                field,
+                left: l,
+                right: r,
+            });
+        }
+    }
+
+    for ((table, l), (_, r)) in left.tables.into_iter().zip(right.tables) {
+        if l != r {
+            let first_difference = l
+                .iter()
+                .zip(r.iter())
+                .position(|(a, b)| a != b)
+                .unwrap_or_else(|| l.len().min(r.len()));
+
+            entries.push(DiffEntry::TableChanged {
+                path: path.to_string(),
+                table,
+                left_len: l.len(),
+                right_len: r.len(),
+                first_difference,
+            });
+        }
+    }
+}
+
+/// Returns the differences between two lists of top-level atoms
+///
+/// Atoms are aligned by their path, and an added or removed atom is reported
+/// once without its descendants.
+pub fn diff(left: &[&dyn Atom], right: &[&dyn Atom]) -> AtomDiff {
+    let mut left_nodes = Vec::new();
+    let mut right_nodes = Vec::new();
+
+    flatten(left, "", "", &mut left_nodes);
+    flatten(right, "", "", &mut right_nodes);
+
+    let left_keys = left_nodes
+        .iter()
+        .map(|n| n.key.as_str())
+        .collect::<HashSet<_>>();
+    let right_map = right_nodes
+        .iter()
+        .map(|n| (n.key.as_str(), n))
+        .collect::<HashMap<_, _>>();
+
+    let mut entries = Vec::new();
+    let mut removed: Vec<&str> = Vec::new();
+
+    for node in &left_nodes {
+        match right_map.get(node.key.as_str()) {
+            Some(r) => compare(&node.path, node.atom, r.atom, &mut entries),
+            None => {
+                if removed
+                    .iter()
+                    .any(|k| node.key.starts_with(&format!("{}/", k)))
+                {
+                    continue;
+                }
+                removed.push(&node.key);
+                entries.push(DiffEntry::Removed {
+                    path: node.path.clone(),
+                    atom_head: node.atom.head().clone(),
+                });
+            }
+        }
+    }
+
+    let mut added: Vec<&str> = Vec::new();
+
+    for node in &right_nodes {
+        if left_keys.contains(node.key.as_str())
+            || added
+                .iter()
+                .any(|k| node.key.starts_with(&format!("{}/", k)))
+        {
+            continue;
+        }
+        added.push(&node.key);
+        entries.push(DiffEntry::Added {
+            path: node.path.clone(),
+            atom_head: node.atom.head().clone(),
+        });
+    }
+
+    AtomDiff { entries }
+}
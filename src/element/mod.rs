@@ -1,5 +1,6 @@
 pub mod qtfile_datetime;
 pub mod qtfile_matrix;
+pub mod qtfile_rect;
 
 use thiserror::Error;
 
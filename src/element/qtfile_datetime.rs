@@ -14,13 +14,25 @@ lazy_static! {
 
 #[derive(PartialEq)]
 pub struct QtFileDateTime {
-    value: u32,
+    value: u64,
     utc: DateTime<Utc>,
 }
 
 impl QtFileDateTime {
     pub fn new(value: u32) -> Self {
-        let utc = *REFERENCE_DATETIME + Duration::seconds(value as i64);
+        QtFileDateTime::from_u64(value as u64)
+    }
+
+    /// Returns the date and time of a 64-bit value of a version 1 atom
+    ///
+    /// A value past the latest representable date and time is shown as that
+    /// date and time, while `value` keeps it as is.
+    pub fn from_u64(value: u64) -> Self {
+        // `Duration::seconds` panics past the milliseconds in an i64
+        let utc = Some(value)
+            .filter(|v| *v <= (i64::MAX / 1000) as u64)
+            .and_then(|v| REFERENCE_DATETIME.checked_add_signed(Duration::seconds(v as i64)))
+            .unwrap_or(chrono::MAX_DATETIME);
         QtFileDateTime { value, utc }
     }
 
@@ -82,4 +94,14 @@ mod test_qtfile_datetime {
 
         assert_eq!(format!("{}", t), "1904-01-01 01:00:00");
     }
+
+    #[test]
+    fn test_qtfile_out_of_range() {
+        for value in &[1 << 45, i64::MAX as u64, u64::MAX] {
+            let t = qtfile_datetime::QtFileDateTime::from_u64(*value);
+
+            assert_eq!(t.value, *value);
+            assert_eq!(t.utc, chrono::MAX_DATETIME);
+        }
+    }
 }
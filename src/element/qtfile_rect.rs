@@ -0,0 +1,36 @@
+use std::fmt;
+use std::io::Read;
+
+use byteorder::{BigEndian, ReadBytesExt};
+
+use crate::element::ElementParseError;
+
+/// A QuickDraw rectangle
+#[derive(Debug, PartialEq, Clone)]
+pub struct QtFileRect {
+    pub top: i16,
+    pub left: i16,
+    pub bottom: i16,
+    pub right: i16,
+}
+
+impl QtFileRect {
+    pub fn parse<R: Read>(r: &mut R) -> Result<Self, ElementParseError> {
+        Ok(QtFileRect {
+            top: r.read_i16::<BigEndian>()?,
+            left: r.read_i16::<BigEndian>()?,
+            bottom: r.read_i16::<BigEndian>()?,
+            right: r.read_i16::<BigEndian>()?,
+        })
+    }
+}
+
+impl fmt::Display for QtFileRect {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "({}, {})-({}, {})",
+            self.left, self.top, self.right, self.bottom
+        )
+    }
+}
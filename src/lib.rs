@@ -1,6 +1,14 @@
 #[macro_use]
 extern crate mopa;
 
+pub mod analysis;
+pub mod artwork;
 pub mod atom;
+pub mod chapters;
+pub mod diff;
 pub mod element;
+pub mod metadata;
 pub mod qtfile;
+pub mod query;
+pub mod subtitles;
+pub mod validate;
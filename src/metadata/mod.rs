@@ -0,0 +1,300 @@
+pub mod write;
+
+use std::collections::BTreeMap;
+use std::convert::TryFrom;
+use std::fmt;
+
+use crate::atom::ilst::{self, DataAtom, ItemAtom};
+use crate::atom::meta::MetaAtom;
+use crate::atom::type_to_string;
+use crate::qtfile::QtFile;
+
+/// A point given by an ISO 6709 string such as `+35.6586+139.7454+040.000/`
+#[derive(Debug, PartialEq, Clone)]
+pub struct Location {
+    pub latitude: f64,
+    pub longitude: f64,
+    /// The altitude in meters
+    pub altitude: Option<f64>,
+}
+
+impl Location {
+    /// Returns the location in `s` written in signed decimal degrees
+    pub fn from_iso6709(s: &str) -> Option<Self> {
+        let s = s.trim_end_matches('/');
+        let mut starts = s
+            .char_indices()
+            .filter(|(_, c)| *c == '+' || *c == '-')
+            .map(|(i, _)| i)
+            .collect::<Vec<_>>();
+        if starts.first() != Some(&0) || !(2..=3).contains(&starts.len()) {
+            return None;
+        }
+        starts.push(s.len());
+
+        let values = starts
+            .windows(2)
+            .map(|w| s[w[0]..w[1]].parse::<f64>().ok())
+            .collect::<Option<Vec<_>>>()?;
+
+        Some(Location {
+            latitude: values[0],
+            longitude: values[1],
+            altitude: values.get(2).copied(),
+        })
+    }
+
+    /// Returns the location as an ISO 6709 string in signed decimal degrees
+    pub fn to_iso6709(&self) -> String {
+        let mut s = format!("{:+08.4}{:+09.4}", self.latitude, self.longitude);
+        if let Some(altitude) = self.altitude {
+            s += &format!("{:+08.3}", altitude);
+        }
+        s + "/"
+    }
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:+}, {:+}", self.latitude, self.longitude)?;
+        if let Some(altitude) = self.altitude {
+            write!(f, ", {} m", altitude)?;
+        }
+        Ok(())
+    }
+}
+
+/// A value decoded from a 'data' atom by its well-known type
+#[derive(Debug, PartialEq, Clone)]
+pub enum MetadataValue {
+    Utf8(String),
+    Utf16(String),
+    Integer(i64),
+    Unsigned(u64),
+    Float32(f32),
+    Float64(f64),
+    Jpeg(Vec<u8>),
+    Png(Vec<u8>),
+    Bmp(Vec<u8>),
+    /// A UTF-8 ISO 6709 string under a location key
+    Location(Location),
+    /// A value of any other type, or which does not fit its type
+    Other {
+        type_indicator: u32,
+        data: Vec<u8>,
+    },
+}
+
+/// Returns the big-endian integer in `data` of 1 to 8 bytes
+fn be_integer(data: &[u8], signed: bool) -> Option<u64> {
+    if data.is_empty() || data.len() > 8 {
+        return None;
+    }
+
+    let value = data.iter().fold(0_u64, |v, b| v << 8 | *b as u64);
+    let unused = 64 - data.len() as u32 * 8;
+
+    if signed {
+        Some((((value << unused) as i64) >> unused) as u64)
+    } else {
+        Some(value)
+    }
+}
+
+/// Returns the size of the fixed-size integer type `well_known_type`
+fn integer_size(well_known_type: u32) -> usize {
+    match well_known_type {
+        65 | 75 => 1,
+        66 | 76 => 2,
+        67 | 77 => 4,
+        _ => 8,
+    }
+}
+
+/// Returns whether the value of `key` is an ISO 6709 string
+fn is_location_key(key: &str) -> bool {
+    key.ends_with("location.ISO6709") || key == "\u{a9}xyz"
+}
+
+impl MetadataValue {
+    /// Returns the value of `data` stored under `key`
+    pub fn new(key: &str, data: &DataAtom) -> Self {
+        let value = &data.value;
+        let decoded = match data.well_known_type() {
+            Some(1) | Some(4) => String::from_utf8(value.clone()).ok().map(|s| {
+                let location = if is_location_key(key) {
+                    Location::from_iso6709(&s)
+                } else {
+                    None
+                };
+                location.map_or(MetadataValue::Utf8(s), MetadataValue::Location)
+            }),
+            Some(2) | Some(5) => {
+                let units = value
+                    .chunks_exact(2)
+                    .map(|c| u16::from_be_bytes([c[0], c[1]]))
+                    .collect::<Vec<_>>();
+                String::from_utf16(&units)
+                    .ok()
+                    .filter(|_| value.len() % 2 == 0)
+                    .map(MetadataValue::Utf16)
+            }
+            Some(13) => Some(MetadataValue::Jpeg(value.clone())),
+            Some(14) => Some(MetadataValue::Png(value.clone())),
+            Some(27) => Some(MetadataValue::Bmp(value.clone())),
+            Some(21) => be_integer(value, true).map(|v| MetadataValue::Integer(v as i64)),
+            Some(22) => be_integer(value, false).map(MetadataValue::Unsigned),
+            Some(23) => <[u8; 4]>::try_from(&value[..])
+                .ok()
+                .map(|b| MetadataValue::Float32(f32::from_be_bytes(b))),
+            Some(24) => <[u8; 8]>::try_from(&value[..])
+                .ok()
+                .map(|b| MetadataValue::Float64(f64::from_be_bytes(b))),
+            Some(t @ (65 | 66 | 67 | 74)) => Some(value)
+                .filter(|v| v.len() == integer_size(t))
+                .and_then(|v| be_integer(v, true))
+                .map(|v| MetadataValue::Integer(v as i64)),
+            Some(t @ (75 | 76 | 77 | 78)) => Some(value)
+                .filter(|v| v.len() == integer_size(t))
+                .and_then(|v| be_integer(v, false))
+                .map(MetadataValue::Unsigned),
+            _ => None,
+        };
+
+        decoded.unwrap_or_else(|| MetadataValue::Other {
+            type_indicator: data.type_indicator,
+            data: value.clone(),
+        })
+    }
+
+    /// Returns the type indicator and payload of a 'data' atom holding the value
+    pub fn to_data(&self) -> (u32, Vec<u8>) {
+        match self {
+            MetadataValue::Utf8(s) => (1, s.as_bytes().to_vec()),
+            MetadataValue::Utf16(s) => (2, s.encode_utf16().flat_map(u16::to_be_bytes).collect()),
+            MetadataValue::Integer(v) => match i32::try_from(*v) {
+                Ok(v) => (21, v.to_be_bytes().to_vec()),
+                Err(_) => (21, v.to_be_bytes().to_vec()),
+            },
+            MetadataValue::Unsigned(v) => match u32::try_from(*v) {
+                Ok(v) => (22, v.to_be_bytes().to_vec()),
+                Err(_) => (22, v.to_be_bytes().to_vec()),
+            },
+            MetadataValue::Float32(v) => (23, v.to_be_bytes().to_vec()),
+            MetadataValue::Float64(v) => (24, v.to_be_bytes().to_vec()),
+            MetadataValue::Jpeg(d) => (13, d.clone()),
+            MetadataValue::Png(d) => (14, d.clone()),
+            MetadataValue::Bmp(d) => (27, d.clone()),
+            MetadataValue::Location(l) => (1, l.to_iso6709().into_bytes()),
+            MetadataValue::Other {
+                type_indicator,
+                data,
+            } => (*type_indicator, data.clone()),
+        }
+    }
+}
+
+impl fmt::Display for MetadataValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MetadataValue::Utf8(s) | MetadataValue::Utf16(s) => write!(f, "{}", s),
+            MetadataValue::Integer(v) => write!(f, "{}", v),
+            MetadataValue::Unsigned(v) => write!(f, "{}", v),
+            MetadataValue::Float32(v) => write!(f, "{}", v),
+            MetadataValue::Float64(v) => write!(f, "{}", v),
+            MetadataValue::Jpeg(d) => write!(f, "JPEG image ({} bytes)", d.len()),
+            MetadataValue::Png(d) => write!(f, "PNG image ({} bytes)", d.len()),
+            MetadataValue::Bmp(d) => write!(f, "BMP image ({} bytes)", d.len()),
+            MetadataValue::Location(l) => write!(f, "{}", l),
+            MetadataValue::Other {
+                type_indicator,
+                data,
+            } => write!(f, "type {} ({} bytes)", type_indicator, data.len()),
+        }
+    }
+}
+
+/// Values by key from 'keys' and 'ilst'
+///
+/// Keys are reverse DNS names from 'keys' such as
+/// `com.apple.quicktime.make`, FourCCs such as `©nam`, or `mean:name` for
+/// freeform items.
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct Metadata {
+    pub entries: BTreeMap<String, Vec<MetadataValue>>,
+}
+
+impl Metadata {
+    /// Returns the first value of `key`
+    pub fn get(&self, key: &str) -> Option<&MetadataValue> {
+        self.entries.get(key).and_then(|values| values.first())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Adds the items of `meta`
+    pub fn extend(&mut self, meta: &MetaAtom) {
+        let ilst = match &meta.ilst_atom {
+            Some(ilst) => ilst,
+            None => return,
+        };
+
+        for item in &ilst.item_atom {
+            let key = item_key(meta, item);
+            let values = self.entries.entry(key.clone()).or_default();
+
+            values.extend(item.data_atom.iter().map(|d| MetadataValue::new(&key, d)));
+        }
+    }
+}
+
+/// Returns the key of `item` in `meta`
+pub(crate) fn item_key(meta: &MetaAtom, item: &ItemAtom) -> String {
+    let atom_type = item.atom_head.atom_type;
+
+    if atom_type == ilst::FREEFORM {
+        if let Some(name) = &item.name {
+            return match &item.mean {
+                Some(mean) => format!("{}:{}", mean, name),
+                None => name.clone(),
+            };
+        }
+    }
+
+    match &meta.keys_atom {
+        Some(keys) => match keys.key(atom_type) {
+            Some(key) => key.key_value.clone(),
+            None => format!("#{}", atom_type),
+        },
+        None => type_to_string(atom_type),
+    }
+}
+
+impl fmt::Display for Metadata {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (key, values) in &self.entries {
+            for value in values {
+                writeln!(f, "{}: {}", key, value)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Returns the metadata in 'moov/meta' and 'moov/udta/meta'
+pub fn metadata(qt: &QtFile) -> Metadata {
+    let mut metadata = Metadata::default();
+
+    if let Some(moov) = qt.moov() {
+        if let Some(meta) = &moov.meta_atom {
+            metadata.extend(meta);
+        }
+        if let Some(meta) = moov.udta_atom.as_ref().and_then(|u| u.meta_atom.as_ref()) {
+            metadata.extend(meta);
+        }
+    }
+
+    metadata
+}
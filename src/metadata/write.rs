@@ -0,0 +1,681 @@
+use std::convert::TryFrom;
+use std::io::{self, Read, Seek, SeekFrom, Write};
+
+use byteorder::{BigEndian, ByteOrder};
+use thiserror::Error;
+
+use super::MetadataValue;
+use crate::atom::{self, co64, hdlr, ilst, keys, meta, moov, stco, type_to_string, udta};
+
+#[derive(Debug, Error)]
+pub enum MetadataWriteError {
+    #[error("no 'moov' atom")]
+    MoovNotFound,
+    #[error("key `{0}' is neither a reverse DNS name nor a '©' FourCC")]
+    InvalidKey(String),
+    #[error("value of `{0}' cannot be stored as text")]
+    NotText(String),
+    #[error("atom {0} is not made of child atoms")]
+    NotContainer(u32),
+    #[error("value of `{0}' is longer than 65535 bytes")]
+    TextTooLong(String),
+
+    #[error(transparent)]
+    AtomParseError(#[from] atom::AtomParseError),
+    #[error(transparent)]
+    IoError(#[from] io::Error),
+}
+
+const TRAK_ID: u32 = 0x7472_616b; // 'trak'
+const MDIA_ID: u32 = 0x6d64_6961; // 'mdia'
+const MINF_ID: u32 = 0x6d69_6e66; // 'minf'
+const STBL_ID: u32 = 0x7374_626c; // 'stbl'
+const DATA_ID: u32 = 0x6461_7461; // 'data'
+const MEAN_ID: u32 = 0x6d65_616e; // 'mean'
+const NAME_ID: u32 = 0x6e61_6d65; // 'name'
+const MDTA_ID: u32 = 0x6d64_7461; // 'mdta'
+
+/// The 'und' language code
+const UNDETERMINED: u16 = 0x55c4;
+
+/// An atom kept as bytes, split into children where metadata or chunk
+/// offsets may need rewriting
+#[derive(Debug, Clone)]
+struct RawAtom {
+    atom_type: u32,
+    body: Body,
+}
+
+#[derive(Debug, Clone)]
+enum Body {
+    Data(Vec<u8>),
+    Children {
+        /// Version and flags of a full box, or the bytes before the children
+        prefix: Vec<u8>,
+        children: Vec<RawAtom>,
+        /// Bytes after the children such as the 32-bit zero ending 'udta'
+        suffix: Vec<u8>,
+    },
+}
+
+impl RawAtom {
+    fn data(atom_type: u32, data: Vec<u8>) -> Self {
+        RawAtom {
+            atom_type,
+            body: Body::Data(data),
+        }
+    }
+
+    fn container(atom_type: u32, prefix: Vec<u8>, children: Vec<RawAtom>) -> Self {
+        RawAtom {
+            atom_type,
+            body: Body::Children {
+                prefix,
+                children,
+                suffix: Vec::new(),
+            },
+        }
+    }
+
+    fn children(&self) -> &[RawAtom] {
+        match &self.body {
+            Body::Children { children, .. } => children,
+            Body::Data(_) => &[],
+        }
+    }
+
+    fn children_mut(&mut self) -> Option<&mut Vec<RawAtom>> {
+        match &mut self.body {
+            Body::Children { children, .. } => Some(children),
+            Body::Data(_) => None,
+        }
+    }
+
+    /// Returns the children, or an error if the body could not be split into
+    /// them
+    fn children_or_err(&mut self) -> Result<&mut Vec<RawAtom>, MetadataWriteError> {
+        match &mut self.body {
+            Body::Children { children, .. } => Ok(children),
+            Body::Data(_) => Err(MetadataWriteError::NotContainer(self.atom_type)),
+        }
+    }
+
+    fn payload(&self) -> &[u8] {
+        match &self.body {
+            Body::Data(data) => data,
+            Body::Children { .. } => &[],
+        }
+    }
+
+    fn child(&self, atom_type: u32) -> Option<&RawAtom> {
+        self.children().iter().find(|a| a.atom_type == atom_type)
+    }
+
+    fn child_mut(&mut self, atom_type: u32) -> Option<&mut RawAtom> {
+        self.children_mut()?
+            .iter_mut()
+            .find(|a| a.atom_type == atom_type)
+    }
+
+    /// Returns the child of `atom_type`, appending it from `new` if missing
+    ///
+    /// An atom whose body could not be split into children has none to return.
+    fn child_or_insert(
+        &mut self,
+        atom_type: u32,
+        new: impl FnOnce() -> RawAtom,
+    ) -> Result<&mut RawAtom, MetadataWriteError> {
+        let children = self.children_or_err()?;
+        let index = match children.iter().position(|a| a.atom_type == atom_type) {
+            Some(index) => index,
+            None => {
+                children.push(new());
+                children.len() - 1
+            }
+        };
+        Ok(&mut children[index])
+    }
+
+    /// Returns an error for the first metadata container from this atom down
+    /// whose body could not be split into children
+    ///
+    /// Metadata in such an atom could be neither found nor removed.
+    fn check_metadata_containers(&self) -> Result<(), MetadataWriteError> {
+        let is_metadata_container = [
+            moov::ATOM_ID,
+            TRAK_ID,
+            udta::ATOM_ID,
+            meta::ATOM_ID,
+            ilst::ATOM_ID,
+        ]
+        .contains(&self.atom_type);
+
+        match &self.body {
+            Body::Data(_) if is_metadata_container => {
+                Err(MetadataWriteError::NotContainer(self.atom_type))
+            }
+            Body::Data(_) => Ok(()),
+            Body::Children { children, .. } => children
+                .iter()
+                .try_for_each(|child| child.check_metadata_containers()),
+        }
+    }
+
+    /// Calls `f` on this atom and every descendant
+    fn for_each_mut<F: FnMut(&mut RawAtom)>(&mut self, f: &mut F) {
+        f(self);
+        if let Some(children) = self.children_mut() {
+            for child in children {
+                child.for_each_mut(f);
+            }
+        }
+    }
+
+    fn serialize(&self, out: &mut Vec<u8>) {
+        let start = out.len();
+        out.extend_from_slice(&[0; 8]);
+        BigEndian::write_u32(&mut out[start + 4..start + 8], self.atom_type);
+
+        match &self.body {
+            Body::Data(data) => out.extend_from_slice(data),
+            Body::Children {
+                prefix,
+                children,
+                suffix,
+            } => {
+                out.extend_from_slice(prefix);
+                for child in children {
+                    child.serialize(out);
+                }
+                out.extend_from_slice(suffix);
+            }
+        }
+
+        let size = out.len() - start;
+        match u32::try_from(size) {
+            Ok(size) => BigEndian::write_u32(&mut out[start..start + 4], size),
+            Err(_) => {
+                // extended size
+                let mut head = [0; 16];
+                BigEndian::write_u32(&mut head[0..4], 1);
+                BigEndian::write_u32(&mut head[4..8], self.atom_type);
+                BigEndian::write_u64(&mut head[8..16], size as u64 + 8);
+                out.splice(start..start + 8, head.iter().copied());
+            }
+        }
+    }
+}
+
+/// Returns whether the atom of `atom_type` under `parent` is split into children
+fn is_container(parent: u32, atom_type: u32) -> bool {
+    parent == ilst::ATOM_ID
+        || [
+            moov::ATOM_ID,
+            TRAK_ID,
+            MDIA_ID,
+            MINF_ID,
+            STBL_ID,
+            udta::ATOM_ID,
+            meta::ATOM_ID,
+            ilst::ATOM_ID,
+        ]
+        .contains(&atom_type)
+}
+
+/// Returns the atoms in `data`, or `None` unless they fill it up to at
+/// most 7 trailing bytes
+fn parse_atoms(parent: u32, data: &[u8]) -> Option<(Vec<RawAtom>, Vec<u8>)> {
+    let mut atoms = Vec::new();
+    let mut offset = 0;
+
+    while offset + 8 <= data.len() {
+        let size = BigEndian::read_u32(&data[offset..]) as u64;
+        let atom_type = BigEndian::read_u32(&data[offset + 4..]);
+        let (size, header_size) = match size {
+            0 => ((data.len() - offset) as u64, 8),
+            1 if offset + 16 <= data.len() => (BigEndian::read_u64(&data[offset + 8..]), 16),
+            s => (s, 8),
+        };
+        if size < header_size || offset as u64 + size > data.len() as u64 {
+            // a 32-bit zero may end 'udta'
+            if data[offset..offset + 4] == [0; 4] {
+                break;
+            }
+            return None;
+        }
+
+        let body = &data[offset + header_size as usize..offset + size as usize];
+        atoms.push(parse_atom(parent, atom_type, body));
+        offset += size as usize;
+    }
+
+    Some((atoms, data[offset..].to_vec()))
+}
+
+fn parse_atom(parent: u32, atom_type: u32, body: &[u8]) -> RawAtom {
+    if is_container(parent, atom_type) {
+        // the full box form of 'meta' has version and flags before 'hdlr'
+        let prefix_size = if atom_type == meta::ATOM_ID && body.len() >= 8 && body[4..8] != *b"hdlr"
+        {
+            4
+        } else {
+            0
+        };
+
+        if let Some((children, suffix)) = parse_atoms(atom_type, &body[prefix_size..]) {
+            return RawAtom {
+                atom_type,
+                body: Body::Children {
+                    prefix: body[..prefix_size].to_vec(),
+                    children,
+                    suffix,
+                },
+            };
+        }
+    }
+
+    RawAtom::data(atom_type, body.to_vec())
+}
+
+/// Returns the FourCC `key` such as "©nam" as an atom type
+fn fourcc(key: &str) -> Option<u32> {
+    let bytes = key
+        .chars()
+        .map(|c| u8::try_from(c as u32).ok())
+        .collect::<Option<Vec<_>>>()?;
+
+    match bytes[..] {
+        [a, b, c, d] => Some(u32::from_be_bytes([a, b, c, d])),
+        _ => None,
+    }
+}
+
+/// Returns the key of a '----' item as "mean:name"
+fn freeform_key(item: &RawAtom) -> Option<String> {
+    let text = |atom_type| {
+        item.child(atom_type)
+            .map(|a| String::from_utf8_lossy(a.payload().get(4..).unwrap_or(&[])).into_owned())
+    };
+    let name = text(NAME_ID)?;
+
+    Some(match text(MEAN_ID) {
+        Some(mean) => format!("{}:{}", mean, name),
+        None => name,
+    })
+}
+
+/// Returns the keys in the payload of 'keys'
+fn read_keys(payload: &[u8]) -> Vec<(u32, String)> {
+    let mut keys = Vec::new();
+    let mut offset = 8;
+
+    while offset + 8 <= payload.len() {
+        let size = BigEndian::read_u32(&payload[offset..]) as usize;
+        if size < 8 || offset + size > payload.len() {
+            break;
+        }
+        keys.push((
+            BigEndian::read_u32(&payload[offset + 4..]),
+            String::from_utf8_lossy(&payload[offset + 8..offset + size]).into_owned(),
+        ));
+        offset += size;
+    }
+
+    keys
+}
+
+/// Returns the payload of 'keys' holding `keys`
+fn write_keys(version_and_flags: &[u8], keys: &[(u32, String)]) -> Vec<u8> {
+    let mut payload = version_and_flags.to_vec();
+    payload.extend_from_slice(&(keys.len() as u32).to_be_bytes());
+
+    for (namespace, key) in keys {
+        payload.extend_from_slice(&(8 + key.len() as u32).to_be_bytes());
+        payload.extend_from_slice(&namespace.to_be_bytes());
+        payload.extend_from_slice(key.as_bytes());
+    }
+
+    payload
+}
+
+/// Returns an item of `atom_type` holding `value`
+fn item(atom_type: u32, value: &MetadataValue) -> RawAtom {
+    let (type_indicator, data) = value.to_data();
+    let mut payload = type_indicator.to_be_bytes().to_vec();
+    payload.extend_from_slice(&[0; 4]); // locale
+    payload.extend_from_slice(&data);
+
+    RawAtom::container(atom_type, Vec::new(), vec![RawAtom::data(DATA_ID, payload)])
+}
+
+/// Removes the keys which `matches` from 'meta'
+fn remove_from_meta(meta: &mut RawAtom, matches: &dyn Fn(&str) -> bool) {
+    let keys_atom = meta.child(keys::ATOM_ID).map(|a| a.payload().to_vec());
+
+    let ilst = match meta.child_mut(ilst::ATOM_ID).and_then(|a| a.children_mut()) {
+        Some(ilst) => ilst,
+        None => return,
+    };
+
+    match keys_atom {
+        Some(payload) if payload.len() >= 8 => {
+            let keys = read_keys(&payload);
+            // the new 1-based index of each key, or 0 if removed
+            let mut indexes = Vec::new();
+            let mut kept = Vec::new();
+            for key in &keys {
+                if matches(&key.1) {
+                    indexes.push(0);
+                } else {
+                    kept.push(key.clone());
+                    indexes.push(kept.len() as u32);
+                }
+            }
+
+            ilst.retain(|item| {
+                let index = item.atom_type as usize;
+                index == 0 || index > keys.len() || indexes[index - 1] != 0
+            });
+            for item in ilst.iter_mut() {
+                let index = item.atom_type as usize;
+                if index != 0 && index <= keys.len() {
+                    item.atom_type = indexes[index - 1];
+                }
+            }
+
+            let payload = write_keys(&payload[..4], &kept);
+            if let Some(keys_atom) = meta.child_mut(keys::ATOM_ID) {
+                keys_atom.body = Body::Data(payload);
+            }
+        }
+        _ => ilst.retain(|item| {
+            let key = if item.atom_type == ilst::FREEFORM {
+                freeform_key(item)
+            } else {
+                None
+            };
+            !matches(&key.unwrap_or_else(|| type_to_string(item.atom_type)))
+        }),
+    }
+}
+
+/// Sets `key` to `value` in the QuickTime 'meta' of 'moov'
+fn set_in_meta(
+    moov: &mut RawAtom,
+    key: &str,
+    value: &MetadataValue,
+) -> Result<(), MetadataWriteError> {
+    let meta = moov.child_or_insert(meta::ATOM_ID, || {
+        let mut hdlr = vec![0; 8];
+        hdlr.extend_from_slice(&MDTA_ID.to_be_bytes());
+        hdlr.extend_from_slice(&[0; 13]);
+
+        RawAtom::container(
+            meta::ATOM_ID,
+            Vec::new(),
+            vec![RawAtom::data(hdlr::ATOM_ID, hdlr)],
+        )
+    })?;
+
+    let mut keys = meta
+        .child(keys::ATOM_ID)
+        .map_or_else(Vec::new, |a| read_keys(a.payload()));
+    let index = match keys.iter().position(|(_, k)| k == key) {
+        Some(i) => i as u32 + 1,
+        None => {
+            keys.push((MDTA_ID, key.to_string()));
+            keys.len() as u32
+        }
+    };
+
+    // the item goes first so that 'keys' is left alone if 'ilst' is malformed
+    let items = meta
+        .child_or_insert(ilst::ATOM_ID, || {
+            RawAtom::container(ilst::ATOM_ID, Vec::new(), Vec::new())
+        })?
+        .children_or_err()?;
+    items.retain(|item| item.atom_type != index);
+    items.push(item(index, value));
+
+    meta.child_or_insert(keys::ATOM_ID, || RawAtom::data(keys::ATOM_ID, Vec::new()))?
+        .body = Body::Data(write_keys(&[0; 4], &keys));
+
+    Ok(())
+}
+
+/// Sets the '©' user data of `atom_type` to `text` in 'udta' of 'moov'
+fn set_in_udta(moov: &mut RawAtom, atom_type: u32, text: &str) -> Result<(), MetadataWriteError> {
+    let size = u16::try_from(text.len())
+        .map_err(|_| MetadataWriteError::TextTooLong(type_to_string(atom_type)))?;
+    let mut payload = size.to_be_bytes().to_vec();
+    payload.extend_from_slice(&UNDETERMINED.to_be_bytes());
+    payload.extend_from_slice(text.as_bytes());
+
+    let entries = moov
+        .child_or_insert(udta::ATOM_ID, || {
+            RawAtom::container(udta::ATOM_ID, Vec::new(), Vec::new())
+        })?
+        .children_or_err()?;
+    match entries.iter_mut().find(|a| a.atom_type == atom_type) {
+        Some(entry) => entry.body = Body::Data(payload),
+        None => entries.push(RawAtom::data(atom_type, payload)),
+    }
+
+    Ok(())
+}
+
+/// Adds `delta` to the chunk offsets from `start`, turning 'stco' into
+/// 'co64' where an offset no longer fits
+fn patch_chunk_offsets(moov: &mut RawAtom, start: u64, delta: i64) {
+    moov.for_each_mut(&mut |atom| {
+        let entry_size = match atom.atom_type {
+            stco::ATOM_ID => 4,
+            co64::ATOM_ID => 8,
+            _ => return,
+        };
+        let payload = atom.payload();
+        if payload.len() < 8 {
+            return;
+        }
+
+        let count =
+            (BigEndian::read_u32(&payload[4..]) as usize).min((payload.len() - 8) / entry_size);
+        let offsets = payload[8..8 + count * entry_size]
+            .chunks_exact(entry_size)
+            .map(|e| match entry_size {
+                4 => BigEndian::read_u32(e) as u64,
+                _ => BigEndian::read_u64(e),
+            })
+            .map(|offset| {
+                if offset >= start {
+                    (offset as i64 + delta) as u64
+                } else {
+                    offset
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let is_co64 = entry_size == 8 || offsets.iter().any(|o| *o > u32::MAX as u64);
+        let mut patched = payload[..8].to_vec();
+        for offset in offsets {
+            if is_co64 {
+                patched.extend_from_slice(&offset.to_be_bytes());
+            } else {
+                patched.extend_from_slice(&(offset as u32).to_be_bytes());
+            }
+        }
+
+        atom.atom_type = if is_co64 {
+            co64::ATOM_ID
+        } else {
+            stco::ATOM_ID
+        };
+        atom.body = Body::Data(patched);
+    });
+}
+
+/// Returns whether `key` may reveal where or with which device a file was
+/// made, such as `com.apple.quicktime.location.ISO6709` or a serial number
+pub fn is_sensitive(key: &str) -> bool {
+    let key = key.to_lowercase();
+
+    key == "\u{a9}xyz"
+        || ["location", "serial", "gps"]
+            .iter()
+            .any(|k| key.contains(k))
+}
+
+enum Edit {
+    Set(String, MetadataValue),
+    Remove(Box<dyn Fn(&str) -> bool>),
+}
+
+/// Rewrites 'moov' with metadata set or removed
+///
+/// Keys are reverse DNS names stored in 'meta' with 'keys', or '©' FourCCs
+/// such as `©nam` stored in 'udta'. Removal also covers iTunes-style 'ilst'
+/// items and the 'meta' and 'udta' atoms of tracks.
+#[derive(Default)]
+pub struct MetadataWriter {
+    edits: Vec<Edit>,
+}
+
+impl MetadataWriter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets `key` to `value`
+    pub fn set(&mut self, key: &str, value: MetadataValue) -> &mut Self {
+        self.edits.push(Edit::Set(key.to_string(), value));
+        self
+    }
+
+    /// Removes every value of `key`
+    pub fn remove(&mut self, key: &str) -> &mut Self {
+        let key = key.to_string();
+        self.edits.push(Edit::Remove(Box::new(move |k| k == key)));
+        self
+    }
+
+    /// Removes the keys for which [`is_sensitive`] holds
+    pub fn remove_sensitive(&mut self) -> &mut Self {
+        self.edits.push(Edit::Remove(Box::new(is_sensitive)));
+        self
+    }
+
+    /// Removes all metadata and '©' user data
+    pub fn remove_all(&mut self) -> &mut Self {
+        self.edits.push(Edit::Remove(Box::new(|_| true)));
+        self
+    }
+
+    /// Returns 'moov' with the edits applied
+    fn edit(&self, moov: &mut RawAtom) -> Result<(), MetadataWriteError> {
+        for edit in &self.edits {
+            match edit {
+                Edit::Set(key, value) if key.contains('.') => set_in_meta(moov, key, value)?,
+                Edit::Set(key, value) => {
+                    let atom_type = match fourcc(key) {
+                        Some(t) if t >> 24 == 0xa9 => t,
+                        _ => return Err(MetadataWriteError::InvalidKey(key.clone())),
+                    };
+                    let text = match value {
+                        MetadataValue::Utf8(s) | MetadataValue::Utf16(s) => s.clone(),
+                        MetadataValue::Location(l) => l.to_iso6709(),
+                        _ => return Err(MetadataWriteError::NotText(key.clone())),
+                    };
+                    set_in_udta(moov, atom_type, &text)?;
+                }
+                Edit::Remove(matches) => {
+                    moov.check_metadata_containers()?;
+                    moov.for_each_mut(&mut |atom| {
+                        if atom.atom_type == meta::ATOM_ID {
+                            remove_from_meta(atom, matches.as_ref());
+                        } else if atom.atom_type == udta::ATOM_ID {
+                            if let Some(entries) = atom.children_mut() {
+                                entries.retain(|a| {
+                                    a.atom_type >> 24 != 0xa9
+                                        || !matches(&type_to_string(a.atom_type))
+                                });
+                            }
+                        }
+                    });
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Writes the file in `r` to `w` with the edits applied to 'moov'
+    ///
+    /// When 'moov' precedes media data, the chunk offsets past it are moved
+    /// by the change in its size.
+    ///
+    /// # Arguments
+    ///
+    /// * `r` - the original file
+    /// * `w` - the destination, which must not be `r`
+    pub fn write<R: Read + Seek, W: Write>(
+        &self,
+        r: &mut R,
+        w: &mut W,
+    ) -> Result<(), MetadataWriteError> {
+        let file_size = r.seek(SeekFrom::End(0))?;
+        r.seek(SeekFrom::Start(0))?;
+
+        let moov_head = loop {
+            let head = match atom::parse_atom_head(r) {
+                Ok(head) => head,
+                Err(atom::AtomParseError::NoMoreAtom) => {
+                    return Err(MetadataWriteError::MoovNotFound)
+                }
+                Err(e) => return Err(e.into()),
+            };
+            if head.atom_type == moov::ATOM_ID {
+                break head;
+            }
+            if head.atom_size == 0 || head.atom_offset + head.atom_size >= file_size {
+                return Err(MetadataWriteError::MoovNotFound);
+            }
+            r.seek(SeekFrom::Start(head.atom_offset + head.atom_size))?;
+        };
+
+        let moov_offset = moov_head.atom_offset;
+        let moov_tail = moov_offset + moov_head.atom_size;
+        let header_size = r.seek(SeekFrom::Current(0))? - moov_offset;
+        let mut body = Vec::new();
+        r.take(moov_head.atom_size - header_size)
+            .read_to_end(&mut body)?;
+
+        let mut moov = parse_atom(0, moov::ATOM_ID, &body);
+        self.edit(&mut moov)?;
+
+        // growing 'stco' into 'co64' changes the size again
+        let mut delta = 0;
+        let new_moov = loop {
+            let mut patched = moov.clone();
+            if delta != 0 {
+                patch_chunk_offsets(&mut patched, moov_tail, delta);
+            }
+
+            let mut out = Vec::new();
+            patched.serialize(&mut out);
+
+            let new_delta = out.len() as i64 - moov_head.atom_size as i64;
+            if new_delta == delta {
+                break out;
+            }
+            delta = new_delta;
+        };
+
+        r.seek(SeekFrom::Start(0))?;
+        io::copy(&mut r.by_ref().take(moov_offset), w)?;
+        w.write_all(&new_moov)?;
+        r.seek(SeekFrom::Start(moov_tail))?;
+        io::copy(r, w)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,208 @@
+use std::fs::File;
+use std::io::{BufReader, Read, Seek, SeekFrom};
+use std::path::PathBuf;
+use std::sync::{Mutex, OnceLock};
+
+use crate::atom::{self, Atom, AtomHead, AtomParseError};
+use crate::qtfile::QtFileError;
+use crate::query::AtomPath;
+
+/// Atom types whose payload is a sequence of child atoms
+const CONTAINER_TYPES: [u32; 15] = [
+    atom::moov::ATOM_ID,
+    atom::trak::ATOM_ID,
+    atom::clip::ATOM_ID,
+    atom::matt::ATOM_ID,
+    atom::edts::ATOM_ID,
+    atom::tref::ATOM_ID,
+    atom::mdia::ATOM_ID,
+    atom::minf::ATOM_ID,
+    atom::gmhd::ATOM_ID,
+    atom::dinf::ATOM_ID,
+    atom::dref::ATOM_ID,
+    atom::stbl::ATOM_ID,
+    atom::udta::ATOM_ID,
+    atom::meta::ATOM_ID,
+    atom::ilst::ATOM_ID,
+];
+
+/// An indexed atom which is decoded on first access
+#[derive(Debug)]
+pub struct LazyAtom {
+    pub atom_head: AtomHead,
+    pub children: Vec<LazyAtom>,
+    decoded: OnceLock<Box<dyn Atom>>,
+}
+
+impl LazyAtom {
+    /// Returns whether the payload has been decoded
+    pub fn is_decoded(&self) -> bool {
+        self.decoded.get().is_some()
+    }
+}
+
+/// Returns whether the atom of `atom_type` under `parent` holds child atoms
+fn is_container(parent: Option<u32>, atom_type: u32) -> bool {
+    match (parent, atom_type) {
+        // the references in 'tref' hold track IDs
+        (Some(atom::tref::ATOM_ID), _) => false,
+        // 'tmcd' is also a track reference type and a sample format
+        (_, atom::tmcd::ATOM_ID) => parent == Some(atom::gmhd::ATOM_ID),
+        _ => CONTAINER_TYPES.contains(&atom_type),
+    }
+}
+
+/// Returns the atoms under `parent` from the current position to `tail`
+/// without decoding their payloads
+fn scan<R: Read + Seek>(
+    r: &mut R,
+    parent: Option<u32>,
+    tail: Option<u64>,
+) -> Result<Vec<LazyAtom>, QtFileError> {
+    let mut atoms = Vec::new();
+
+    loop {
+        if let Some(tail) = tail {
+            if r.seek(SeekFrom::Current(0))? + 8 > tail {
+                break;
+            }
+        }
+
+        let atom_head = match atom::parse_atom_head(r) {
+            Ok(h) => h,
+            Err(AtomParseError::NoMoreAtom) => break,
+            Err(e) => return Err(e.into()),
+        };
+
+        let atom_tail = atom_head.atom_offset + atom_head.atom_size;
+
+        if atom_head.atom_size < 8 || tail.map_or(false, |t| atom_tail > t) {
+            return Err(QtFileError::InvalidAtomSize(atom_head.atom_size));
+        }
+
+        let children = if is_container(parent, atom_head.atom_type) {
+            match atom_head.atom_type {
+                atom::meta::ATOM_ID => {
+                    atom::meta::parse_version_and_flags(r, atom_tail)?;
+                }
+                // version, flags and the number of entries
+                atom::dref::ATOM_ID => {
+                    r.seek(SeekFrom::Current(8))?;
+                }
+                _ => {}
+            }
+            scan(r, Some(atom_head.atom_type), Some(atom_tail))?
+        } else {
+            Vec::new()
+        };
+
+        r.seek(SeekFrom::Start(atom_tail))?;
+
+        atoms.push(LazyAtom {
+            atom_head,
+            children,
+            decoded: OnceLock::new(),
+        });
+    }
+
+    Ok(atoms)
+}
+
+/// A file whose atom tree is indexed first and decoded on demand
+#[derive(Debug)]
+pub struct LazyQtFile<R> {
+    reader: Mutex<R>,
+    atoms: Vec<LazyAtom>,
+}
+
+impl<R: Read + Seek> LazyQtFile<R> {
+    /// Returns a LazyQtFile after indexing all the atoms in `r`
+    pub fn new(mut r: R) -> Result<Self, QtFileError> {
+        r.seek(SeekFrom::Start(0))?;
+        let atoms = scan(&mut r, None, None)?;
+
+        Ok(LazyQtFile {
+            reader: Mutex::new(r),
+            atoms,
+        })
+    }
+
+    /// Returns the index of the top-level atoms
+    pub fn atoms(&self) -> &[LazyAtom] {
+        &self.atoms
+    }
+
+    /// Returns the decoded `atom`, decoding it first if needed
+    pub fn decode<'a>(&self, atom: &'a LazyAtom) -> Result<&'a dyn Atom, QtFileError> {
+        if let Some(decoded) = atom.decoded.get() {
+            return Ok(decoded.as_ref());
+        }
+
+        let decoded = {
+            let mut r = self.reader.lock().unwrap_or_else(|e| e.into_inner());
+            r.seek(SeekFrom::Start(atom.atom_head.atom_offset))?;
+            atom::parse(&mut *r)?
+        };
+
+        // another thread may have decoded it meanwhile; either result is the same
+        let _ = atom.decoded.set(decoded);
+
+        Ok(atom.decoded.get().unwrap().as_ref())
+    }
+
+    /// Returns the indexed atoms which `path` selects without decoding them
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - a path such as `moov/trak[0]/mdia/hdlr` (see [`AtomPath`])
+    pub fn select(&self, path: &str) -> Result<Vec<&LazyAtom>, QtFileError> {
+        let path = path.parse::<AtomPath>()?;
+        let mut selected = self.atoms.iter().collect::<Vec<_>>();
+
+        for (i, segment) in path.segments.iter().enumerate() {
+            if i > 0 {
+                selected = selected
+                    .into_iter()
+                    .flat_map(|a| a.children.iter())
+                    .collect();
+            }
+            selected = segment.filter(selected, |a| a.atom_head.atom_type);
+        }
+
+        Ok(selected)
+    }
+
+    /// Returns the atoms of type `T` which `path` selects, decoding only them
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - a path such as `moov/trak[0]/mdia/hdlr` (see [`AtomPath`])
+    pub fn find<T: Atom>(&self, path: &str) -> Result<Vec<&T>, QtFileError> {
+        let mut found = Vec::new();
+
+        for atom in self.select(path)? {
+            if let Some(a) = self.decode(atom)?.downcast_ref::<T>() {
+                found.push(a);
+            }
+        }
+
+        Ok(found)
+    }
+
+    /// Returns the first 'ftyp' atom
+    pub fn ftyp(&self) -> Result<Option<&atom::ftyp::FtypAtom>, QtFileError> {
+        Ok(self.find("ftyp[0]")?.into_iter().next())
+    }
+
+    /// Returns the first 'moov' atom
+    pub fn moov(&self) -> Result<Option<&atom::moov::MoovAtom>, QtFileError> {
+        Ok(self.find("moov[0]")?.into_iter().next())
+    }
+}
+
+/// Returns a LazyQtFile which has indexed `file_name`
+pub fn open(file_name: PathBuf) -> Result<LazyQtFile<BufReader<File>>, QtFileError> {
+    let f = File::open(file_name)?;
+
+    LazyQtFile::new(BufReader::new(f))
+}
@@ -1,12 +1,23 @@
-use std::cell::RefCell;
+pub mod lazy;
+pub mod stream;
+
 use std::fs::File;
-use std::io::BufReader;
+use std::io::{BufReader, Read, Seek, SeekFrom};
 use std::path::PathBuf;
-use std::rc::Rc;
 
 use thiserror::Error;
+#[cfg(feature = "async")]
+use tokio::io::{AsyncRead, AsyncSeek, AsyncSeekExt};
 
-use super::atom::{self, Atom, AtomParseError};
+use super::artwork::{self, Artwork};
+use super::atom::{
+    self, free, ftyp, mdat, moov, pict, pnot, wide, Atom, AtomParseError, AtomVisitor,
+    UnimplementedAtom,
+};
+use super::chapters::{self, Chapter};
+use super::diff::{self, AtomDiff};
+use super::metadata::{self, Metadata};
+use super::query::{self, AtomPath, QueryError};
 
 #[derive(Error, Debug)]
 pub enum QtFileError {
@@ -17,15 +28,141 @@ pub enum QtFileError {
     AtomParseError(#[from] AtomParseError),
     #[error(transparent)]
     IoError(#[from] std::io::Error),
+    #[error(transparent)]
+    QueryError(#[from] QueryError),
+}
+
+/// An atom at the top level of a file
+#[derive(Debug, PartialEq)]
+pub enum TopLevelAtom {
+    Ftyp(ftyp::FtypAtom),
+    Moov(moov::MoovAtom),
+    Mdat(mdat::MdatAtom),
+    Free(free::FreeAtom),
+    Wide(wide::WideAtom),
+    Pnot(pnot::PnotAtom),
+    Pict(pict::PictAtom),
+    Unknown(UnimplementedAtom),
+}
+
+impl TopLevelAtom {
+    pub fn as_atom(&self) -> &dyn Atom {
+        match self {
+            TopLevelAtom::Ftyp(a) => a,
+            TopLevelAtom::Moov(a) => a,
+            TopLevelAtom::Mdat(a) => a,
+            TopLevelAtom::Free(a) => a,
+            TopLevelAtom::Wide(a) => a,
+            TopLevelAtom::Pnot(a) => a,
+            TopLevelAtom::Pict(a) => a,
+            TopLevelAtom::Unknown(a) => a,
+        }
+    }
 }
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
 pub struct QtFile {
-    atoms: Vec<Rc<RefCell<Box<dyn Atom>>>>,
+    atoms: Vec<TopLevelAtom>,
+    file_size: u64,
+}
+
+impl QtFile {
+    /// Returns an iterator over the top-level atoms
+    pub fn iter(&self) -> std::slice::Iter<'_, TopLevelAtom> {
+        self.atoms.iter()
+    }
+
+    /// Returns the number of bytes in the input
+    pub fn file_size(&self) -> u64 {
+        self.file_size
+    }
+
+    /// Returns the first 'ftyp' atom
+    pub fn ftyp(&self) -> Option<&ftyp::FtypAtom> {
+        self.atoms.iter().find_map(|a| match a {
+            TopLevelAtom::Ftyp(a) => Some(a),
+            _ => None,
+        })
+    }
+
+    /// Returns the first 'moov' atom
+    pub fn moov(&self) -> Option<&moov::MoovAtom> {
+        self.atoms.iter().find_map(|a| match a {
+            TopLevelAtom::Moov(a) => Some(a),
+            _ => None,
+        })
+    }
+
+    /// Returns an iterator over the 'mdat' atoms
+    pub fn mdat(&self) -> impl Iterator<Item = &mdat::MdatAtom> {
+        self.atoms.iter().filter_map(|a| match a {
+            TopLevelAtom::Mdat(a) => Some(a),
+            _ => None,
+        })
+    }
+
+    /// Traverses all the atoms in depth-first order
+    pub fn walk<V: AtomVisitor + ?Sized>(&self, visitor: &mut V) {
+        for a in &self.atoms {
+            atom::walk(a.as_atom(), visitor);
+        }
+    }
+
+    /// Returns the atoms which `path` selects
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - a path such as `moov/trak[0]/mdia/hdlr` (see [`AtomPath`])
+    pub fn select(&self, path: &str) -> Result<Vec<&dyn Atom>, QueryError> {
+        let path = path.parse::<AtomPath>()?;
+        let atoms = self.atoms.iter().map(|a| a.as_atom()).collect::<Vec<_>>();
+
+        Ok(query::select(&atoms, &path.segments))
+    }
+
+    /// Returns the atoms of type `T` which `path` selects
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - a path such as `moov/trak[0]/mdia/hdlr` (see [`AtomPath`])
+    pub fn find<T: Atom>(&self, path: &str) -> Result<Vec<&T>, QueryError> {
+        Ok(self
+            .select(path)?
+            .into_iter()
+            .filter_map(|a| a.downcast_ref::<T>())
+            .collect())
+    }
+
+    /// Returns the differences from `self` to `other` atom by atom
+    pub fn diff(&self, other: &QtFile) -> AtomDiff {
+        diff::diff(
+            &self.atoms.iter().map(|a| a.as_atom()).collect::<Vec<_>>(),
+            &other.atoms.iter().map(|a| a.as_atom()).collect::<Vec<_>>(),
+        )
+    }
+
+    /// Returns the metadata of the movie from 'keys' and 'ilst'
+    pub fn metadata(&self) -> Metadata {
+        metadata::metadata(self)
+    }
+
+    /// Returns the embedded cover art, thumbnails and previews
+    pub fn artwork(&self) -> Vec<Artwork> {
+        artwork::artwork(self)
+    }
+
+    /// Returns the chapters from the chapter track or 'chpl'
+    ///
+    /// # Arguments
+    ///
+    /// * `r` - the file this was parsed from
+    pub fn chapters<R: Read + Seek>(&self, r: &mut R) -> Result<Vec<Chapter>, AtomParseError> {
+        chapters::chapters(self, r)
+    }
 }
 
 impl std::iter::IntoIterator for QtFile {
-    type Item = Rc<RefCell<Box<dyn Atom>>>;
+    type Item = TopLevelAtom;
     type IntoIter = std::vec::IntoIter<Self::Item>;
 
     fn into_iter(self) -> Self::IntoIter {
@@ -33,14 +170,77 @@ impl std::iter::IntoIterator for QtFile {
     }
 }
 
+/// Returns a top-level atom from `r`
+pub fn parse_atom<R: Read + Seek>(r: &mut R) -> Result<TopLevelAtom, AtomParseError> {
+    let atom_head = atom::parse_atom_head(r)?;
+
+    let atom = match atom_head.atom_type {
+        ftyp::ATOM_ID => TopLevelAtom::Ftyp(ftyp::parse(r, atom_head)?),
+        moov::ATOM_ID => TopLevelAtom::Moov(moov::parse(r, atom_head)?),
+        mdat::ATOM_ID => TopLevelAtom::Mdat(mdat::parse(r, atom_head)?),
+        free::ATOM_ID => TopLevelAtom::Free(free::parse(r, atom_head)?),
+        wide::ATOM_ID => TopLevelAtom::Wide(wide::parse(r, atom_head)?),
+        pnot::ATOM_ID => TopLevelAtom::Pnot(pnot::parse(r, atom_head)?),
+        pict::ATOM_ID => TopLevelAtom::Pict(pict::parse(r, atom_head)?),
+        _ => {
+            r.seek(SeekFrom::Start(atom_head.atom_offset + atom_head.atom_size))?;
+            TopLevelAtom::Unknown(UnimplementedAtom { atom_head })
+        }
+    };
+
+    Ok(atom)
+}
+
+/// Returns a QtFile parsed from all the atoms in `r`
+pub fn parse<R: Read + Seek>(r: &mut R) -> Result<QtFile, QtFileError> {
+    let mut atoms = Vec::new();
+
+    loop {
+        match parse_atom(r) {
+            Ok(a) => atoms.push(a),
+            Err(atom::AtomParseError::NoMoreAtom) => break,
+            Err(e) => {
+                return Err(QtFileError::AtomParseError(e));
+            }
+        }
+    }
+
+    let file_size = r.seek(SeekFrom::End(0))?;
+
+    Ok(QtFile { atoms, file_size })
+}
+
 pub fn parse_file(file_name: PathBuf) -> Result<QtFile, QtFileError> {
     let f = File::open(file_name)?;
     let mut reader = BufReader::new(f);
-    let mut atoms = Vec::<Rc<RefCell<Box<dyn Atom>>>>::new();
+
+    parse(&mut reader)
+}
+
+/// Returns a top-level atom from `r` asynchronously
+#[cfg(feature = "async")]
+pub async fn parse_atom_async<R: AsyncRead + AsyncSeek + Unpin>(
+    r: &mut R,
+) -> Result<TopLevelAtom, AtomParseError> {
+    match atom::read_atom_async(r).await? {
+        (atom_head, header_size, None) => Ok(TopLevelAtom::Mdat(mdat::MdatAtom {
+            atom_head,
+            header_size,
+        })),
+        (_, _, Some(mut cursor)) => parse_atom(&mut cursor),
+    }
+}
+
+/// Returns a QtFile parsed from all the atoms in `r` asynchronously
+#[cfg(feature = "async")]
+pub async fn parse_async<R: AsyncRead + AsyncSeek + Unpin>(
+    r: &mut R,
+) -> Result<QtFile, QtFileError> {
+    let mut atoms = Vec::new();
 
     loop {
-        match atom::parse(&mut reader) {
-            Ok(a) => atoms.push(Rc::new(RefCell::new(a))),
+        match parse_atom_async(r).await {
+            Ok(a) => atoms.push(a),
             Err(atom::AtomParseError::NoMoreAtom) => break,
             Err(e) => {
                 return Err(QtFileError::AtomParseError(e));
@@ -48,5 +248,15 @@ pub fn parse_file(file_name: PathBuf) -> Result<QtFile, QtFileError> {
         }
     }
 
-    Ok(QtFile { atoms })
+    let file_size = r.seek(SeekFrom::End(0)).await?;
+
+    Ok(QtFile { atoms, file_size })
+}
+
+#[cfg(feature = "async")]
+pub async fn parse_file_async(file_name: PathBuf) -> Result<QtFile, QtFileError> {
+    let f = tokio::fs::File::open(file_name).await?;
+    let mut reader = tokio::io::BufReader::new(f);
+
+    parse_async(&mut reader).await
 }
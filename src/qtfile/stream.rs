@@ -0,0 +1,233 @@
+use std::io::{self, Cursor, Read, Seek, SeekFrom};
+
+use byteorder::{BigEndian, ByteOrder};
+
+use crate::atom::{free, mdat, AtomHead, UnimplementedAtom};
+use crate::qtfile::{self, QtFile, QtFileError, TopLevelAtom};
+
+const SKIP_ATOM_ID: u32 = 0x736b_6970; // 'skip'
+
+/// An in-memory atom which seeks by the offsets in its original file
+pub(crate) struct OffsetCursor {
+    base: u64,
+    cursor: Cursor<Vec<u8>>,
+}
+
+impl OffsetCursor {
+    /// Returns a cursor over `data`, which starts at `base` in the file
+    pub(crate) fn new(base: u64, data: Vec<u8>) -> Self {
+        OffsetCursor {
+            base,
+            cursor: Cursor::new(data),
+        }
+    }
+}
+
+impl Read for OffsetCursor {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.cursor.read(buf)
+    }
+}
+
+impl Seek for OffsetCursor {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let pos = match pos {
+            SeekFrom::Start(offset) => {
+                SeekFrom::Start(offset.checked_sub(self.base).ok_or_else(|| {
+                    io::Error::new(io::ErrorKind::InvalidInput, "seek before the atom")
+                })?)
+            }
+            pos => pos,
+        };
+
+        Ok(self.cursor.seek(pos)? + self.base)
+    }
+}
+
+/// Reads into `buf` until it is full or the input ends, and returns the length
+fn read_full<R: Read>(r: &mut R, buf: &mut [u8]) -> io::Result<usize> {
+    let mut len = 0;
+
+    while len < buf.len() {
+        match r.read(&mut buf[len..]) {
+            Ok(0) => break,
+            Ok(n) => len += n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+
+    Ok(len)
+}
+
+/// The header bytes of an atom and its size, where the size is `None` for an
+/// atom which lasts to the end of the stream
+type AtomHeader = (Vec<u8>, Option<u64>);
+
+/// Returns the header of the next atom
+fn read_atom_header<R: Read>(r: &mut R) -> Result<Option<AtomHeader>, QtFileError> {
+    let mut header = vec![0_u8; 8];
+
+    match read_full(r, &mut header)? {
+        0 => return Ok(None),
+        8 => {}
+        _ => return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into()),
+    }
+
+    let atom_size = match BigEndian::read_u32(&header[0..4]) {
+        0 => None,
+        1 => {
+            header.resize(16, 0);
+            r.read_exact(&mut header[8..16])?;
+            Some(BigEndian::read_u64(&header[8..16]))
+        }
+        s => Some(s as u64),
+    };
+
+    if let Some(size) = atom_size {
+        if size < header.len() as u64 {
+            return Err(QtFileError::InvalidAtomSize(size));
+        }
+    }
+
+    Ok(Some((header, atom_size)))
+}
+
+/// A parser which reads top-level atoms from a non-seekable input
+///
+/// Each atom is yielded as soon as it is complete, so 'moov' is available
+/// before the rest of the stream has been received. The payloads of 'mdat',
+/// 'free' and 'skip' are read and discarded.
+pub struct StreamParser<R> {
+    reader: R,
+    offset: u64,
+    moov_received: bool,
+    finished: bool,
+}
+
+impl<R: Read> StreamParser<R> {
+    pub fn new(reader: R) -> Self {
+        StreamParser {
+            reader,
+            offset: 0,
+            moov_received: false,
+            finished: false,
+        }
+    }
+
+    /// Returns the number of bytes consumed so far
+    pub fn bytes_read(&self) -> u64 {
+        self.offset
+    }
+
+    /// Returns whether 'moov' has been yielded
+    pub fn moov_received(&self) -> bool {
+        self.moov_received
+    }
+
+    /// Reads and discards the payload after a header of `header_size` bytes,
+    /// and returns its length
+    fn skip_payload(
+        &mut self,
+        header_size: u64,
+        atom_size: Option<u64>,
+    ) -> Result<u64, QtFileError> {
+        match atom_size {
+            Some(size) => {
+                let payload = size - header_size;
+                let skipped = io::copy(&mut (&mut self.reader).take(payload), &mut io::sink())?;
+                if skipped < payload {
+                    return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into());
+                }
+                Ok(skipped)
+            }
+            None => Ok(io::copy(&mut self.reader, &mut io::sink())?),
+        }
+    }
+
+    fn next_atom(&mut self) -> Result<Option<TopLevelAtom>, QtFileError> {
+        let (mut data, atom_size) = match read_atom_header(&mut self.reader)? {
+            Some(h) => h,
+            None => return Ok(None),
+        };
+        let atom_offset = self.offset;
+        let atom_type = BigEndian::read_u32(&data[4..8]);
+
+        let atom = match atom_type {
+            mdat::ATOM_ID | free::ATOM_ID | SKIP_ATOM_ID => {
+                let header_size = data.len() as u64;
+                let payload = self.skip_payload(header_size, atom_size)?;
+                let atom_head = AtomHead {
+                    atom_offset,
+                    atom_size: header_size + payload,
+                    atom_type,
+                };
+
+                match atom_type {
+                    mdat::ATOM_ID => TopLevelAtom::Mdat(mdat::MdatAtom {
+                        atom_head,
+                        header_size,
+                    }),
+                    free::ATOM_ID => TopLevelAtom::Free(free::FreeAtom { atom_head }),
+                    _ => TopLevelAtom::Unknown(UnimplementedAtom { atom_head }),
+                }
+            }
+            _ => {
+                // the payload is read as it arrives rather than allocated by
+                // the size, which may be corrupt
+                match atom_size {
+                    Some(size) => {
+                        let payload = size - data.len() as u64;
+                        let read = (&mut self.reader).take(payload).read_to_end(&mut data)?;
+                        if (read as u64) < payload {
+                            return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into());
+                        }
+                    }
+                    None => {
+                        self.reader.read_to_end(&mut data)?;
+                    }
+                }
+
+                let mut r = OffsetCursor::new(atom_offset, data);
+                r.seek(SeekFrom::Start(atom_offset))?;
+                qtfile::parse_atom(&mut r)?
+            }
+        };
+
+        self.offset += atom.as_atom().head().atom_size;
+
+        if let TopLevelAtom::Moov(_) = atom {
+            self.moov_received = true;
+        }
+
+        Ok(Some(atom))
+    }
+}
+
+impl<R: Read> Iterator for StreamParser<R> {
+    type Item = Result<TopLevelAtom, QtFileError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+
+        let next = self.next_atom().transpose();
+        if !matches!(next, Some(Ok(_))) {
+            self.finished = true;
+        }
+
+        next
+    }
+}
+
+/// Returns a QtFile parsed from all the atoms in a non-seekable `r`
+pub fn parse_stream<R: Read>(r: R) -> Result<QtFile, QtFileError> {
+    let mut parser = StreamParser::new(r);
+    let atoms = parser.by_ref().collect::<Result<Vec<_>, _>>()?;
+
+    Ok(QtFile {
+        atoms,
+        file_size: parser.bytes_read(),
+    })
+}
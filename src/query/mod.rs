@@ -0,0 +1,184 @@
+use std::convert::TryFrom;
+use std::fmt;
+use std::str::FromStr;
+
+use byteorder::{BigEndian, ByteOrder};
+use thiserror::Error;
+
+use crate::atom::{self, Atom};
+
+#[derive(Debug, Error, PartialEq)]
+pub enum QueryError {
+    #[error("path is empty")]
+    EmptyPath,
+    #[error("invalid path segment `{0}'")]
+    InvalidSegment(String),
+}
+
+/// One step of an [`AtomPath`]
+#[derive(Debug, PartialEq, Clone)]
+pub struct Segment {
+    /// atom type to match, or `None` for any type
+    pub atom_type: Option<u32>,
+    /// index among the matching siblings, or `None` for all of them
+    pub index: Option<usize>,
+}
+
+impl FromStr for Segment {
+    type Err = QueryError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || QueryError::InvalidSegment(s.to_string());
+
+        let (name, index) = match s.find('[') {
+            Some(open) => {
+                let index = s[open + 1..].strip_suffix(']').ok_or_else(invalid)?;
+                let index = match index {
+                    "*" => None,
+                    i => Some(i.parse::<usize>().map_err(|_| invalid())?),
+                };
+                (&s[..open], index)
+            }
+            None => (s, None),
+        };
+
+        let atom_type = match name {
+            "*" => None,
+            name => {
+                // each character is a byte of the type, as in `type_to_string`
+                let bytes = name
+                    .chars()
+                    .map(|c| u8::try_from(c as u32).map_err(|_| invalid()))
+                    .collect::<Result<Vec<_>, _>>()?;
+                if bytes.len() != 4 {
+                    return Err(invalid());
+                }
+                Some(BigEndian::read_u32(&bytes))
+            }
+        };
+
+        Ok(Segment { atom_type, index })
+    }
+}
+
+impl Segment {
+    /// Returns the items of `siblings` which this segment matches
+    ///
+    /// # Arguments
+    ///
+    /// * `siblings` - items in file order
+    /// * `atom_type` - returns the atom type of an item
+    pub fn filter<T, F: Fn(&T) -> u32>(&self, siblings: Vec<T>, atom_type: F) -> Vec<T> {
+        let matched = siblings.into_iter().filter(|a| match self.atom_type {
+            Some(t) => atom_type(a) == t,
+            None => true,
+        });
+
+        match self.index {
+            Some(i) => matched.skip(i).take(1).collect(),
+            None => matched.collect(),
+        }
+    }
+}
+
+impl fmt::Display for Segment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.atom_type {
+            Some(t) => write!(f, "{}", atom::type_to_string(t))?,
+            None => write!(f, "*")?,
+        }
+        if let Some(i) = self.index {
+            write!(f, "[{}]", i)?;
+        }
+        Ok(())
+    }
+}
+
+/// A path to atoms such as `moov/trak[0]/mdia/hdlr`
+///
+/// A segment without an index matches every sibling of its type, `*`
+/// matches any type and `[n]` selects the n-th (0-origin) match.
+#[derive(Debug, PartialEq, Clone)]
+pub struct AtomPath {
+    pub segments: Vec<Segment>,
+}
+
+impl FromStr for AtomPath {
+    type Err = QueryError;
+
+    /// Parses a path
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use atom_analyzer::query::{AtomPath, Segment};
+    ///
+    /// let path = "moov/trak[1]/*".parse::<AtomPath>().unwrap();
+    ///
+    /// assert_eq!(
+    ///     path.segments,
+    ///     vec![
+    ///         Segment { atom_type: Some(0x6d6f_6f76), index: None },
+    ///         Segment { atom_type: Some(0x7472_616b), index: Some(1) },
+    ///         Segment { atom_type: None, index: None },
+    ///     ]
+    /// );
+    /// ```
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let segments = s
+            .trim_matches('/')
+            .split('/')
+            .filter(|s| !s.is_empty())
+            .map(|s| s.parse::<Segment>())
+            .collect::<Result<Vec<_>, _>>()?;
+
+        if segments.is_empty() {
+            return Err(QueryError::EmptyPath);
+        }
+
+        Ok(AtomPath { segments })
+    }
+}
+
+impl fmt::Display for AtomPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, segment) in self.segments.iter().enumerate() {
+            if i > 0 {
+                write!(f, "/")?;
+            }
+            write!(f, "{}", segment)?;
+        }
+        Ok(())
+    }
+}
+
+/// Returns `atom` itself if `segments` is empty, or its descendants which
+/// `segments` selects
+pub fn select_from<'a>(atom: &'a dyn Atom, segments: &[Segment]) -> Vec<&'a dyn Atom> {
+    if segments.is_empty() {
+        vec![atom]
+    } else {
+        select(&atom.children(), segments)
+    }
+}
+
+/// Returns the atoms under `atoms` which `segments` selects
+///
+/// # Arguments
+///
+/// * `atoms` - sibling atoms which the first segment is applied to
+/// * `segments` - the segments of a path
+pub fn select<'a>(atoms: &[&'a dyn Atom], segments: &[Segment]) -> Vec<&'a dyn Atom> {
+    let (first, rest) = match segments.split_first() {
+        Some(s) => s,
+        None => return atoms.to_vec(),
+    };
+
+    let mut selected = Vec::new();
+
+    for atom in first.filter(atoms.to_vec(), |a| a.head().atom_type) {
+        selected.extend(select_from(atom, rest));
+    }
+
+    selected
+}
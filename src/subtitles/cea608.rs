@@ -0,0 +1,234 @@
+use crate::subtitles::{self, cea708, Cue};
+
+const CDAT_ATOM_ID: u32 = 0x6364_6174; // 'cdat'
+
+/// The special characters from 0x11 0x30 to 0x11 0x3f
+const SPECIAL_CHARACTERS: [char; 16] = [
+    '®', '°', '½', '¿', '™', '¢', '£', '♪', 'à', ' ', 'è', 'â', 'ê', 'î', 'ô', 'û',
+];
+
+/// Returns the character of a standard character code without parity
+fn standard_character(c: u8) -> char {
+    match c {
+        0x2a => 'á',
+        0x5c => 'é',
+        0x5e => 'í',
+        0x5f => 'ó',
+        0x60 => 'ú',
+        0x7b => 'ç',
+        0x7c => '÷',
+        0x7d => 'Ñ',
+        0x7e => 'ñ',
+        0x7f => '█',
+        c => char::from(c),
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum Mode {
+    PopOn,
+    /// The number of rows
+    RollUp(usize),
+    PaintOn,
+}
+
+/// Decodes the byte pairs of CEA-608 data channel 1 into cues
+///
+/// Pop-on captions are shown from the end of caption command until they are
+/// erased or replaced. Roll-up captions are shown a row at a time as each
+/// carriage return completes it, and paint-on captions from their first
+/// character. Extended characters keep their standard fallback.
+pub struct Cea608Decoder {
+    time_scale: u32,
+    mode: Mode,
+    /// The rows of the non-displayed memory
+    loading: Vec<String>,
+    /// The rows of the displayed memory
+    displayed: Vec<String>,
+    /// The start and text of the cue on screen
+    shown: Option<(u64, String)>,
+    last_control: Option<(u8, u8)>,
+    is_channel_1: bool,
+    cues: Vec<Cue>,
+}
+
+/// Returns the non-empty rows joined by newlines
+pub(crate) fn join_rows(rows: &[String]) -> String {
+    rows.iter()
+        .map(|row| row.trim())
+        .filter(|row| !row.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+impl Cea608Decoder {
+    /// Returns a decoder of cues timed in `time_scale`
+    pub fn new(time_scale: u32) -> Self {
+        Cea608Decoder {
+            time_scale,
+            mode: Mode::PopOn,
+            loading: Vec::new(),
+            displayed: Vec::new(),
+            shown: None,
+            last_control: None,
+            is_channel_1: true,
+            cues: Vec::new(),
+        }
+    }
+
+    /// Returns the memory which characters are written to
+    fn rows(&mut self) -> &mut Vec<String> {
+        match self.mode {
+            Mode::PopOn => &mut self.loading,
+            _ => &mut self.displayed,
+        }
+    }
+
+    /// Ends the cue on screen at `time`
+    fn close(&mut self, time: u64) {
+        if let Some((start_time, text)) = self.shown.take() {
+            if !text.is_empty() && start_time < time {
+                self.cues.push(Cue {
+                    start_time,
+                    end_time: time,
+                    time_scale: self.time_scale,
+                    id: None,
+                    settings: None,
+                    text,
+                });
+            }
+        }
+    }
+
+    fn write(&mut self, time: u64, c: char) {
+        let rows = self.rows();
+        match rows.last_mut() {
+            Some(row) => row.push(c),
+            None => rows.push(c.to_string()),
+        }
+
+        if self.mode == Mode::PaintOn {
+            let start_time = self.shown.as_ref().map_or(time, |(t, _)| *t);
+            self.shown = Some((start_time, join_rows(&self.displayed)));
+        }
+    }
+
+    fn new_row(&mut self) {
+        let rows = self.rows();
+        if rows.last().map_or(false, |row| !row.is_empty()) {
+            rows.push(String::new());
+        }
+    }
+
+    fn control(&mut self, time: u64, c1: u8, c2: u8) {
+        match (c1 & 0x77, c2) {
+            (0x11, 0x30..=0x3f) => self.write(time, SPECIAL_CHARACTERS[(c2 & 0x0f) as usize]),
+            // a mid-row code is shown as a space
+            (0x11, 0x20..=0x2f) => self.write(time, ' '),
+            (0x14, 0x20) | (0x15, 0x20) => self.mode = Mode::PopOn,
+            (0x14, 0x21) | (0x15, 0x21) => {
+                if let Some(row) = self.rows().last_mut() {
+                    row.pop();
+                }
+            }
+            (0x14, 0x25..=0x27) | (0x15, 0x25..=0x27) => {
+                if !matches!(self.mode, Mode::RollUp(_)) {
+                    self.close(time);
+                    self.displayed.clear();
+                }
+                self.mode = Mode::RollUp((c2 - 0x23) as usize);
+            }
+            (0x14, 0x29) | (0x15, 0x29) => self.mode = Mode::PaintOn,
+            (0x14, 0x2c) | (0x15, 0x2c) => {
+                self.close(time);
+                self.displayed.clear();
+            }
+            (0x14, 0x2d) | (0x15, 0x2d) => match self.mode {
+                Mode::RollUp(rows) => {
+                    self.close(time);
+                    self.displayed.push(String::new());
+                    let excess = self.displayed.len().saturating_sub(rows);
+                    self.displayed.drain(..excess);
+
+                    let completed = &self.displayed[..self.displayed.len() - 1];
+                    self.shown = Some((time, join_rows(completed)));
+                }
+                _ => self.new_row(),
+            },
+            (0x14, 0x2e) | (0x15, 0x2e) => self.loading.clear(),
+            (0x14, 0x2f) | (0x15, 0x2f) => {
+                self.close(time);
+                self.displayed = std::mem::take(&mut self.loading);
+                self.shown = Some((time, join_rows(&self.displayed)));
+                self.mode = Mode::PopOn;
+            }
+            // a preamble address code moves to another row
+            (0x10..=0x17, 0x40..=0x7f) => {
+                if !matches!(self.mode, Mode::RollUp(_)) {
+                    self.new_row();
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Decodes a byte pair received at `time`
+    pub fn push(&mut self, time: u64, pair: [u8; 2]) {
+        let c1 = pair[0] & 0x7f;
+        let c2 = pair[1] & 0x7f;
+
+        if c1 == 0 && c2 == 0 {
+            return;
+        }
+
+        if (0x10..=0x1f).contains(&c1) {
+            // control codes are sent twice
+            if self.last_control == Some((c1, c2)) {
+                self.last_control = None;
+                return;
+            }
+            self.last_control = Some((c1, c2));
+            self.is_channel_1 = c1 & 0x08 == 0;
+
+            if self.is_channel_1 {
+                self.control(time, c1, c2);
+            }
+            return;
+        }
+
+        self.last_control = None;
+        if !self.is_channel_1 {
+            return;
+        }
+        for c in &[c1, c2] {
+            if *c >= 0x20 {
+                self.write(time, standard_character(*c));
+            }
+        }
+    }
+
+    /// Returns the cues, ending the one on screen at `time`
+    pub fn finish(mut self, time: u64) -> Vec<Cue> {
+        self.close(time);
+        self.cues
+    }
+}
+
+/// Returns the field 1 byte pairs in the 'cdat' atoms of a 'c608' sample
+pub fn c608_pairs(data: &[u8]) -> Vec<[u8; 2]> {
+    subtitles::atoms(data)
+        .into_iter()
+        .filter(|(atom_type, _)| *atom_type == CDAT_ATOM_ID)
+        .flat_map(|(_, payload)| payload.chunks_exact(2).map(|c| [c[0], c[1]]))
+        .collect()
+}
+
+/// Returns the CEA-608 field 1 byte pairs carried in the caption distribution
+/// packets of a 'c708' sample
+pub fn c708_pairs(data: &[u8]) -> Vec<[u8; 2]> {
+    cea708::cc_data(data)
+        .into_iter()
+        .filter(|(cc_type, _)| *cc_type == 0)
+        .map(|(_, pair)| pair)
+        .collect()
+}
@@ -0,0 +1,404 @@
+use crate::subtitles::{self, cea608::join_rows, Cue};
+
+const CCDP_ATOM_ID: u32 = 0x6363_6470; // 'ccdp'
+
+const CDP_IDENTIFIER: u16 = 0x9669;
+const TIME_CODE_SECTION_ID: u8 = 0x71;
+const CC_DATA_SECTION_ID: u8 = 0x72;
+
+/// The `cc_type` of a DTVCC packet start and its data
+const DTVCC_PACKET_START: u8 = 3;
+const DTVCC_PACKET_DATA: u8 = 2;
+
+/// The primary caption service, which is the only one decoded
+const PRIMARY_SERVICE: u8 = 1;
+
+const EXT1: u8 = 0x10;
+
+/// Returns the `cc_type` and bytes of the valid triplets in the caption
+/// distribution packets of a 'c708' sample
+pub fn cc_data(data: &[u8]) -> Vec<(u8, [u8; 2])> {
+    let mut triplets = Vec::new();
+
+    let cdps = subtitles::atoms(data)
+        .into_iter()
+        .filter(|(atom_type, _)| *atom_type == CCDP_ATOM_ID);
+
+    for (_, cdp) in cdps {
+        if cdp.len() < 7 || u16::from_be_bytes([cdp[0], cdp[1]]) != CDP_IDENTIFIER {
+            continue;
+        }
+
+        // the sections follow the identifier, length, frame rate, flags and
+        // sequence counter
+        let mut offset = 7;
+        if cdp.get(offset) == Some(&TIME_CODE_SECTION_ID) {
+            offset += 5;
+        }
+        let (count, data) = match cdp.get(offset..) {
+            Some([CC_DATA_SECTION_ID, count, rest @ ..]) => (*count & 0x1f, rest),
+            _ => continue,
+        };
+
+        for triplet in data.chunks_exact(3).take(count as usize) {
+            if triplet[0] & 0x04 != 0 {
+                triplets.push((triplet[0] & 0x03, [triplet[1], triplet[2]]));
+            }
+        }
+    }
+
+    triplets
+}
+
+/// Returns the character of a G2 code after EXT1, or `None` for codes
+/// without a printable counterpart
+fn g2_character(c: u8) -> Option<char> {
+    Some(match c {
+        0x20 | 0x21 => ' ',
+        0x25 => '…',
+        0x2a => 'Š',
+        0x2c => 'Œ',
+        0x30 => '█',
+        0x31 => '‘',
+        0x32 => '’',
+        0x33 => '“',
+        0x34 => '”',
+        0x35 => '•',
+        0x39 => '™',
+        0x3a => 'š',
+        0x3c => 'œ',
+        0x3d => '℠',
+        0x3f => 'Ÿ',
+        0x76 => '⅛',
+        0x77 => '⅜',
+        0x78 => '⅝',
+        0x79 => '⅞',
+        0x7a => '│',
+        0x7b => '┐',
+        0x7c => '└',
+        0x7d => '─',
+        0x7e => '┘',
+        0x7f => '┌',
+        _ => return None,
+    })
+}
+
+/// Returns the number of parameter bytes of the C0 or C1 code `c`
+fn parameter_count(c: u8) -> usize {
+    match c {
+        0x00..=0x0f => 0,
+        0x10..=0x17 => 1,
+        0x18..=0x1f => 2,
+        // CLW, DSW, HDW, TGW, DLW and DLY
+        0x88..=0x8d => 1,
+        // SPA and SPL
+        0x90 | 0x92 => 2,
+        // SPC
+        0x91 => 3,
+        // SWA
+        0x97 => 4,
+        // DF0 to DF7
+        0x98..=0x9f => 6,
+        _ => 0,
+    }
+}
+
+#[derive(Debug, Default, Clone)]
+struct Window {
+    is_visible: bool,
+    row_count: usize,
+    rows: Vec<String>,
+    pen_row: usize,
+}
+
+impl Window {
+    fn clear(&mut self) {
+        self.rows.clear();
+        self.pen_row = 0;
+    }
+
+    /// Returns the row at the pen
+    fn row(&mut self) -> &mut String {
+        if self.rows.len() <= self.pen_row {
+            self.rows.resize(self.pen_row + 1, String::new());
+        }
+        &mut self.rows[self.pen_row]
+    }
+
+    /// Moves the pen to the next row, scrolling up at the last one
+    fn carriage_return(&mut self) {
+        if self.pen_row + 1 < self.row_count.max(1) {
+            self.pen_row += 1;
+        } else if !self.rows.is_empty() {
+            self.rows.remove(0);
+        }
+        self.row();
+    }
+}
+
+/// Decodes the DTVCC packets of CEA-708 caption service 1 into cues
+///
+/// The text of the visible windows is shown as one cue, which is extended
+/// while characters are appended and replaced when it otherwise changes. Pen
+/// columns, styles and delays are ignored.
+pub struct Cea708Decoder {
+    time_scale: u32,
+    packet: Vec<u8>,
+    /// The number of bytes of the packet being assembled
+    packet_size: usize,
+    windows: [Option<Window>; 8],
+    current_window: usize,
+    /// The start and text of the cue on screen
+    shown: Option<(u64, String)>,
+    cues: Vec<Cue>,
+}
+
+impl Cea708Decoder {
+    /// Returns a decoder of cues timed in `time_scale`
+    pub fn new(time_scale: u32) -> Self {
+        Cea708Decoder {
+            time_scale,
+            packet: Vec::new(),
+            packet_size: 0,
+            windows: Default::default(),
+            current_window: 0,
+            shown: None,
+            cues: Vec::new(),
+        }
+    }
+
+    /// Ends the cue on screen at `time`
+    fn close(&mut self, time: u64) {
+        if let Some((start_time, text)) = self.shown.take() {
+            if !text.is_empty() && start_time < time {
+                self.cues.push(Cue {
+                    start_time,
+                    end_time: time,
+                    time_scale: self.time_scale,
+                    id: None,
+                    settings: None,
+                    text,
+                });
+            }
+        }
+    }
+
+    /// Updates the cue on screen at `time` to the text of the visible windows
+    fn update(&mut self, time: u64) {
+        let text = self
+            .windows
+            .iter()
+            .flatten()
+            .filter(|w| w.is_visible)
+            .map(|w| join_rows(&w.rows))
+            .filter(|text| !text.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        match &mut self.shown {
+            Some((_, shown)) if *shown == text => {}
+            // characters appended to the cue on screen extend it
+            Some((_, shown)) if !shown.is_empty() && text.starts_with(shown.as_str()) => {
+                *shown = text
+            }
+            _ => {
+                self.close(time);
+                if !text.is_empty() {
+                    self.shown = Some((time, text));
+                }
+            }
+        }
+    }
+
+    /// Calls `f` on the windows set in the bitmap `windows`
+    fn for_each_window<F: FnMut(&mut Window)>(&mut self, windows: u8, mut f: F) {
+        for (i, window) in self.windows.iter_mut().enumerate() {
+            if let Some(window) = window {
+                if windows & (1 << i) != 0 {
+                    f(window);
+                }
+            }
+        }
+    }
+
+    fn write(&mut self, c: char) {
+        if let Some(window) = &mut self.windows[self.current_window] {
+            window.row().push(c);
+        }
+    }
+
+    /// Applies the C0 or C1 code `c` with its parameters
+    fn control(&mut self, c: u8, parameters: &[u8]) {
+        let window = self.windows[self.current_window].as_mut();
+
+        match (c, parameters) {
+            // BS
+            (0x08, _) => {
+                if let Some(w) = window {
+                    w.row().pop();
+                }
+            }
+            // FF
+            (0x0c, _) => {
+                if let Some(w) = window {
+                    w.clear();
+                }
+            }
+            // CR
+            (0x0d, _) => {
+                if let Some(w) = window {
+                    w.carriage_return();
+                }
+            }
+            // HCR
+            (0x0e, _) => {
+                if let Some(w) = window {
+                    w.row().clear();
+                }
+            }
+            // CW0 to CW7
+            (0x80..=0x87, _) => self.current_window = (c & 0x07) as usize,
+            (0x88, [windows]) => self.for_each_window(*windows, Window::clear),
+            (0x89, [windows]) => self.for_each_window(*windows, |w| w.is_visible = true),
+            (0x8a, [windows]) => self.for_each_window(*windows, |w| w.is_visible = false),
+            (0x8b, [windows]) => self.for_each_window(*windows, |w| w.is_visible = !w.is_visible),
+            // DLW
+            (0x8c, [windows]) => {
+                for (i, window) in self.windows.iter_mut().enumerate() {
+                    if windows & (1 << i) != 0 {
+                        *window = None;
+                    }
+                }
+            }
+            // RST
+            (0x8f, _) => {
+                self.windows = Default::default();
+                self.current_window = 0;
+            }
+            // SPL
+            (0x92, [row, _]) => {
+                if let Some(w) = window {
+                    w.pen_row = (row & 0x0f) as usize;
+                    w.row();
+                }
+            }
+            // DF0 to DF7, which keep the text of a window defined before
+            (0x98..=0x9f, [visibility, _, _, rows, ..]) => {
+                let id = (c & 0x07) as usize;
+                let window = self.windows[id].get_or_insert_with(Window::default);
+                window.is_visible = visibility & 0x20 != 0;
+                window.row_count = (rows & 0x0f) as usize + 1;
+                self.current_window = id;
+            }
+            _ => {}
+        }
+    }
+
+    /// Decodes the service block `data`
+    fn service_block(&mut self, data: &[u8]) {
+        let mut i = 0;
+
+        while i < data.len() {
+            let c = data[i];
+            i += 1;
+
+            match c {
+                EXT1 => {
+                    let c = match data.get(i) {
+                        Some(c) => *c,
+                        None => break,
+                    };
+                    i += 1;
+                    match c {
+                        0x08..=0x0f => i += 1,
+                        0x10..=0x17 => i += 2,
+                        0x18..=0x1f => i += 3,
+                        0x20..=0x7f => {
+                            if let Some(c) = g2_character(c) {
+                                self.write(c);
+                            }
+                        }
+                        0x80..=0x87 => i += 4,
+                        0x88..=0x8f => i += 5,
+                        // variable length codes with the length in the low
+                        // six bits of the next byte
+                        0x90..=0x9f => i += 1 + data.get(i).map_or(0, |l| (l & 0x3f) as usize),
+                        // G3 holds only the [CC] icon
+                        _ => {}
+                    }
+                }
+                0x00..=0x1f | 0x80..=0x9f => {
+                    let end = (i + parameter_count(c)).min(data.len());
+                    self.control(c, &data[i..end]);
+                    i = end;
+                }
+                0x7f => self.write('♪'),
+                // G0 is ASCII and G1 is Latin-1
+                c => self.write(char::from(c)),
+            }
+        }
+    }
+
+    /// Decodes the service blocks of a complete packet at `time`
+    fn packet(&mut self, time: u64) {
+        let packet = std::mem::take(&mut self.packet);
+        let mut i = 0;
+
+        while i < packet.len() {
+            let mut service = packet[i] >> 5;
+            let size = (packet[i] & 0x1f) as usize;
+            i += 1;
+            if service == 0 {
+                // a null block ends the packet
+                break;
+            }
+            if service == 7 {
+                match packet.get(i) {
+                    Some(extended) => service = extended & 0x3f,
+                    None => break,
+                }
+                i += 1;
+            }
+
+            let end = (i + size).min(packet.len());
+            if service == PRIMARY_SERVICE {
+                self.service_block(&packet[i..end]);
+            }
+            i = end;
+        }
+
+        self.update(time);
+    }
+
+    /// Decodes a triplet of `cc_type` received at `time`
+    pub fn push(&mut self, time: u64, cc_type: u8, data: [u8; 2]) {
+        match cc_type {
+            DTVCC_PACKET_START => {
+                // the size in the header counts pairs of bytes, with 0 for 64
+                let size = match data[0] & 0x3f {
+                    0 => 128,
+                    s => s as usize * 2,
+                };
+                self.packet.clear();
+                self.packet.push(data[1]);
+                self.packet_size = size - 1;
+            }
+            DTVCC_PACKET_DATA if self.packet_size > self.packet.len() => {
+                self.packet.extend_from_slice(&data)
+            }
+            _ => return,
+        }
+
+        if self.packet_size > 0 && self.packet.len() >= self.packet_size {
+            self.packet.truncate(self.packet_size);
+            self.packet_size = 0;
+            self.packet(time);
+        }
+    }
+
+    /// Returns the cues, ending the one on screen at `time`
+    pub fn finish(mut self, time: u64) -> Vec<Cue> {
+        self.close(time);
+        self.cues
+    }
+}
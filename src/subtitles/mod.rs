@@ -0,0 +1,303 @@
+pub mod cea608;
+pub mod cea708;
+pub mod stpp;
+pub mod tx3g;
+pub mod wvtt;
+
+use std::fmt::Write;
+use std::io::{self, Read, Seek, SeekFrom};
+
+use crate::atom::stbl::Sample;
+use crate::atom::trak::{PresentedSample, TrakAtom};
+use crate::atom::AtomParseError;
+use crate::qtfile::QtFile;
+
+/// The sample formats of subtitle and closed caption tracks
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum SubtitleFormat {
+    /// 3GPP timed text
+    Tx3g,
+    /// QuickTime text
+    Text,
+    /// CEA-608 byte pairs
+    Cea608,
+    /// CEA-708 caption distribution packets, of which caption service 1 is
+    /// decoded, or the CEA-608 data they carry if that service is empty
+    Cea708,
+    /// WebVTT cues
+    WebVtt,
+    /// XML documents such as TTML
+    Ttml,
+}
+
+impl SubtitleFormat {
+    /// Returns the subtitle format of the sample description format
+    /// `data_format`
+    pub fn new(data_format: u32) -> Option<Self> {
+        match data_format {
+            0x7478_3367 => Some(SubtitleFormat::Tx3g),   // 'tx3g'
+            0x7465_7874 => Some(SubtitleFormat::Text),   // 'text'
+            0x6336_3038 => Some(SubtitleFormat::Cea608), // 'c608'
+            0x6337_3038 => Some(SubtitleFormat::Cea708), // 'c708'
+            0x7776_7474 => Some(SubtitleFormat::WebVtt), // 'wvtt'
+            0x7374_7070 => Some(SubtitleFormat::Ttml),   // 'stpp'
+            _ => None,
+        }
+    }
+}
+
+/// A subtitle or caption shown over an interval
+#[derive(Debug, PartialEq, Clone)]
+pub struct Cue {
+    /// The start in `time_scale`
+    pub start_time: u64,
+    /// The end in `time_scale`
+    pub end_time: u64,
+    pub time_scale: u32,
+    /// The WebVTT cue identifier
+    pub id: Option<String>,
+    /// The WebVTT cue settings
+    pub settings: Option<String>,
+    pub text: String,
+}
+
+/// Returns the type and payload of each atom in `data` up to the first
+/// malformed one
+pub(crate) fn atoms(data: &[u8]) -> Vec<(u32, &[u8])> {
+    let mut atoms = Vec::new();
+    let mut offset = 0;
+
+    while offset + 8 <= data.len() {
+        let mut head = [0; 8];
+        head.copy_from_slice(&data[offset..offset + 8]);
+        let size = u32::from_be_bytes([head[0], head[1], head[2], head[3]]) as usize;
+        let atom_type = u32::from_be_bytes([head[4], head[5], head[6], head[7]]);
+
+        if size < 8 || offset + size > data.len() {
+            break;
+        }
+        atoms.push((atom_type, &data[offset + 8..offset + size]));
+        offset += size;
+    }
+
+    atoms
+}
+
+/// Returns the subtitle format of the first 'stsd' entry of `trak`
+pub fn subtitle_format(trak: &TrakAtom) -> Option<SubtitleFormat> {
+    trak.sample_description(1)
+        .and_then(|d| SubtitleFormat::new(d.data_format))
+}
+
+/// Returns the tracks whose samples are subtitles or closed captions
+pub fn subtitle_tracks(qt: &QtFile) -> Vec<&TrakAtom> {
+    qt.moov().map_or_else(Vec::new, |moov| {
+        moov.trak_atom
+            .iter()
+            .filter(|t| subtitle_format(t).is_some())
+            .collect()
+    })
+}
+
+fn read_sample<R: Read + Seek>(r: &mut R, sample: &Sample) -> Result<Vec<u8>, AtomParseError> {
+    let mut data = Vec::new();
+    r.seek(SeekFrom::Start(sample.offset))?;
+    // the size is read from the file, so it is not trusted for an allocation
+    r.by_ref().take(sample.size as u64).read_to_end(&mut data)?;
+    if data.len() < sample.size as usize {
+        return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into());
+    }
+
+    Ok(data)
+}
+
+/// Returns the cues decoded from the samples of `trak` in `r`
+///
+/// Times are in the movie time scale on the movie timeline, so a sample is
+/// shown once for each edit which shows it. Empty samples make gaps, and a cue
+/// repeated in consecutive samples is merged into one.
+///
+/// # Arguments
+///
+/// * `trak` - a track of a [`SubtitleFormat`]
+/// * `movie_time_scale` - the time scale in 'mvhd'
+/// * `r` - the file the track was parsed from
+pub fn subtitles<R: Read + Seek>(
+    trak: &TrakAtom,
+    movie_time_scale: u32,
+    r: &mut R,
+) -> Result<Vec<Cue>, AtomParseError> {
+    let format = match subtitle_format(trak) {
+        Some(format) => format,
+        None => return Ok(Vec::new()),
+    };
+    let samples = trak.samples();
+    let presentation = trak.presentation(movie_time_scale);
+    let cue = |presented: &PresentedSample, text: String| Cue {
+        start_time: presented.movie_time,
+        end_time: presented.movie_time + presented.movie_duration,
+        time_scale: movie_time_scale,
+        id: None,
+        settings: None,
+        text,
+    };
+    let mut cues = Vec::new();
+
+    match format {
+        SubtitleFormat::Tx3g | SubtitleFormat::Text => {
+            for presented in &presentation {
+                let data = read_sample(r, &samples[presented.index])?;
+                cues.push(cue(presented, tx3g::TextSample::parse(&data).text));
+            }
+        }
+        SubtitleFormat::Cea608 | SubtitleFormat::Cea708 => {
+            let mut decoder = cea608::Cea608Decoder::new(movie_time_scale);
+            let mut dtvcc_decoder = cea708::Cea708Decoder::new(movie_time_scale);
+            for presented in &presentation {
+                let time = presented.movie_time;
+                let data = read_sample(r, &samples[presented.index])?;
+                let pairs = if format == SubtitleFormat::Cea608 {
+                    cea608::c608_pairs(&data)
+                } else {
+                    for (cc_type, data) in cea708::cc_data(&data) {
+                        dtvcc_decoder.push(time, cc_type, data);
+                    }
+                    cea608::c708_pairs(&data)
+                };
+                for pair in pairs {
+                    decoder.push(time, pair);
+                }
+            }
+            let end_time = presentation
+                .last()
+                .map_or(0, |p| p.movie_time + p.movie_duration);
+            cues = dtvcc_decoder.finish(end_time);
+            if cues.is_empty() {
+                cues = decoder.finish(end_time);
+            }
+        }
+        SubtitleFormat::WebVtt => {
+            for presented in &presentation {
+                for vtt in wvtt::parse_sample(&read_sample(r, &samples[presented.index])?) {
+                    cues.push(Cue {
+                        id: vtt.id,
+                        settings: vtt.settings,
+                        ..cue(presented, vtt.payload)
+                    });
+                }
+            }
+        }
+        SubtitleFormat::Ttml => {
+            let media_time_scale = trak.media_time_scale();
+
+            for presented in &presentation {
+                let sample = &samples[presented.index];
+                let document = read_sample(r, sample)?;
+                let paragraphs = stpp::parse_document(&String::from_utf8_lossy(&document));
+
+                // document times are on the media timeline, and are moved by
+                // the edit showing the sample and cut to the time it is shown
+                let sample_seconds = if media_time_scale == 0 {
+                    0.0
+                } else {
+                    sample.composition_time as f64 / media_time_scale as f64
+                };
+                let sample_cue = cue(presented, String::new());
+                let movie_time = |seconds: f64| {
+                    let elapsed = (seconds - sample_seconds) * movie_time_scale as f64;
+                    ((presented.movie_time as f64 + elapsed).round().max(0.0) as u64)
+                        .clamp(sample_cue.start_time, sample_cue.end_time)
+                };
+
+                for p in paragraphs {
+                    let start_time = p.begin.map_or(sample_cue.start_time, movie_time);
+                    let end_time = p.end.map_or(sample_cue.end_time, movie_time);
+                    if start_time < end_time {
+                        cues.push(Cue {
+                            start_time,
+                            end_time,
+                            ..cue(presented, p.text)
+                        });
+                    }
+                }
+            }
+        }
+    }
+
+    let mut merged: Vec<Cue> = Vec::new();
+    for cue in cues.into_iter().filter(|c| !c.text.is_empty()) {
+        match merged.last_mut() {
+            Some(last)
+                if last.end_time == cue.start_time
+                    && last.text == cue.text
+                    && last.id == cue.id
+                    && last.settings == cue.settings =>
+            {
+                last.end_time = cue.end_time
+            }
+            _ => merged.push(cue),
+        }
+    }
+
+    Ok(merged)
+}
+
+/// Returns `time` in `time_scale` as `HH:MM:SS` and milliseconds after
+/// `separator`
+fn timestamp(time: u64, time_scale: u32, separator: char) -> String {
+    let millis = if time_scale == 0 {
+        0
+    } else {
+        (time as u128 * 1000 / time_scale as u128) as u64
+    };
+
+    format!(
+        "{:02}:{:02}:{:02}{}{:03}",
+        millis / 3_600_000,
+        millis / 60_000 % 60,
+        millis / 1000 % 60,
+        separator,
+        millis % 1000
+    )
+}
+
+/// Returns `cues` as a SubRip document
+pub fn to_srt(cues: &[Cue]) -> String {
+    let mut srt = String::new();
+
+    for (i, cue) in cues.iter().enumerate() {
+        let _ = write!(
+            srt,
+            "{}\n{} --> {}\n{}\n\n",
+            i + 1,
+            timestamp(cue.start_time, cue.time_scale, ','),
+            timestamp(cue.end_time, cue.time_scale, ','),
+            cue.text
+        );
+    }
+
+    srt
+}
+
+/// Returns `cues` as a WebVTT document
+pub fn to_webvtt(cues: &[Cue]) -> String {
+    let mut vtt = String::from("WEBVTT\n\n");
+
+    for cue in cues {
+        if let Some(id) = &cue.id {
+            let _ = writeln!(vtt, "{}", id);
+        }
+        let _ = write!(
+            vtt,
+            "{} --> {}",
+            timestamp(cue.start_time, cue.time_scale, '.'),
+            timestamp(cue.end_time, cue.time_scale, '.')
+        );
+        if let Some(settings) = &cue.settings {
+            let _ = write!(vtt, " {}", settings);
+        }
+        let _ = write!(vtt, "\n{}\n\n", cue.text);
+    }
+
+    vtt
+}
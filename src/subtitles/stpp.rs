@@ -0,0 +1,223 @@
+use crate::atom::stsd::SampleDescription;
+
+/// An XML subtitle sample description from 'stsd'
+#[derive(Debug, PartialEq, Clone)]
+pub struct XmlSubtitleSampleEntry {
+    /// The namespaces of the documents such as the TTML namespace
+    pub namespace: String,
+    pub schema_location: String,
+    /// The MIME types of the images and fonts in subsamples
+    pub auxiliary_mime_types: String,
+}
+
+impl XmlSubtitleSampleEntry {
+    /// Returns the XML subtitle sample entry held in `description`
+    ///
+    /// # Arguments
+    ///
+    /// * `description` - A sample description whose data format is 'stpp'
+    pub fn new(description: &SampleDescription) -> Self {
+        let mut strings = description
+            .data
+            .split(|b| *b == 0)
+            .map(|s| String::from_utf8_lossy(s).into_owned());
+
+        XmlSubtitleSampleEntry {
+            namespace: strings.next().unwrap_or_default(),
+            schema_location: strings.next().unwrap_or_default(),
+            auxiliary_mime_types: strings.next().unwrap_or_default(),
+        }
+    }
+}
+
+/// A `p` element of a TTML document
+#[derive(Debug, PartialEq, Clone)]
+pub struct TtmlParagraph {
+    /// The start in seconds on the media timeline
+    pub begin: Option<f64>,
+    /// The end in seconds on the media timeline
+    pub end: Option<f64>,
+    /// The text with line breaks for `br` elements
+    pub text: String,
+}
+
+/// Returns the value of the attribute `name` in the start tag `tag`
+fn attribute<'a>(tag: &'a str, name: &str) -> Option<&'a str> {
+    let mut rest = tag;
+
+    while let Some(i) = rest.find(name) {
+        let after = &rest[i + name.len()..];
+        let is_start = rest[..i].ends_with(char::is_whitespace);
+
+        if let (true, Some(value)) = (is_start, after.trim_start().strip_prefix('=')) {
+            let value = value.trim_start();
+            let quote = value.chars().next()?;
+            if quote == '"' || quote == '\'' {
+                let value = &value[1..];
+                return value.find(quote).map(|end| &value[..end]);
+            }
+        }
+        rest = after;
+    }
+
+    None
+}
+
+/// Returns the seconds of a TTML time expression such as `00:00:01.500`,
+/// `00:00:01:12`, `1.5s`, `1500ms` or `45000t`
+///
+/// # Arguments
+///
+/// * `frame_rate` - the frames per second for frames
+/// * `tick_rate` - the ticks per second for ticks
+pub fn parse_time(expression: &str, frame_rate: f64, tick_rate: f64) -> Option<f64> {
+    let expression = expression.trim();
+
+    if expression.contains(':') {
+        let parts = expression
+            .split(':')
+            .map(|p| p.parse::<f64>().ok())
+            .collect::<Option<Vec<_>>>()?;
+
+        return match parts[..] {
+            [h, m, s] => Some(h * 3600.0 + m * 60.0 + s),
+            [h, m, s, f] => Some(h * 3600.0 + m * 60.0 + s + f / frame_rate),
+            _ => None,
+        };
+    }
+
+    let split = expression.find(|c: char| c.is_ascii_alphabetic())?;
+    let value = expression[..split].parse::<f64>().ok()?;
+
+    match &expression[split..] {
+        "h" => Some(value * 3600.0),
+        "m" => Some(value * 60.0),
+        "s" => Some(value),
+        "ms" => Some(value / 1000.0),
+        "f" => Some(value / frame_rate),
+        "t" => Some(value / tick_rate),
+        _ => None,
+    }
+}
+
+/// Returns `text` with its character and entity references replaced
+fn decode_entities(text: &str) -> String {
+    let mut decoded = String::new();
+    let mut rest = text;
+
+    while let Some(i) = rest.find('&') {
+        decoded.push_str(&rest[..i]);
+        rest = &rest[i..];
+
+        let end = match rest.find(';') {
+            Some(end) => end,
+            None => break,
+        };
+        let c = match &rest[1..end] {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            r => match r.strip_prefix("#x") {
+                Some(hex) => u32::from_str_radix(hex, 16).ok(),
+                None => r.strip_prefix('#').and_then(|d| d.parse().ok()),
+            }
+            .and_then(char::from_u32),
+        };
+
+        match c {
+            Some(c) => {
+                decoded.push(c);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                decoded.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+
+    decoded.push_str(rest);
+    decoded
+}
+
+/// Returns the text content of `content` with `br` elements as line breaks
+/// and whitespace collapsed
+fn text_content(content: &str) -> String {
+    let mut lines = vec![String::new()];
+    let mut rest = content;
+
+    while let Some(i) = rest.find('<') {
+        lines.last_mut().unwrap().push_str(&rest[..i]);
+        let end = rest[i..].find('>').map_or(rest.len(), |e| i + e + 1);
+        let tag = rest[i + 1..end].trim_end_matches('>').trim_end_matches('/');
+
+        if tag.trim() == "br" || tag.starts_with("br ") {
+            lines.push(String::new());
+        }
+        rest = &rest[end..];
+    }
+    lines.last_mut().unwrap().push_str(rest);
+
+    lines
+        .iter()
+        .map(|line| decode_entities(&line.split_whitespace().collect::<Vec<_>>().join(" ")))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Returns the `p` elements of the TTML document `xml`
+///
+/// Times are read from the `begin`, `end` and `dur` attributes of each `p`,
+/// with the frame and tick rates of the `tt` element.
+pub fn parse_document(xml: &str) -> Vec<TtmlParagraph> {
+    let root = xml
+        .find("<tt")
+        .and_then(|i| xml[i..].find('>').map(|end| &xml[i..i + end]))
+        .unwrap_or("");
+    let rate = |name, default| {
+        attribute(root, name)
+            .and_then(|v| v.trim().parse::<f64>().ok())
+            .filter(|v| *v > 0.0)
+            .unwrap_or(default)
+    };
+    let frame_rate = rate("ttp:frameRate", 30.0);
+    let tick_rate = rate("ttp:tickRate", 1.0);
+
+    let mut paragraphs = Vec::new();
+    let mut rest = xml;
+
+    while let Some(i) = rest.find("<p") {
+        rest = &rest[i + 2..];
+        if !rest.starts_with(|c: char| c.is_whitespace() || c == '>' || c == '/') {
+            continue;
+        }
+
+        let tag_end = match rest.find('>') {
+            Some(end) => end,
+            None => break,
+        };
+        let tag = &rest[..tag_end];
+        rest = &rest[tag_end + 1..];
+        if tag.ends_with('/') {
+            continue;
+        }
+
+        let content_end = rest.find("</p>").unwrap_or(rest.len());
+        let content = &rest[..content_end];
+        rest = &rest[content_end..];
+
+        let time = |name| attribute(tag, name).and_then(|v| parse_time(v, frame_rate, tick_rate));
+        let begin = time("begin");
+        let end = time("end").or_else(|| Some(begin.unwrap_or(0.0) + time("dur")?));
+
+        paragraphs.push(TtmlParagraph {
+            begin,
+            end,
+            text: text_content(content),
+        });
+    }
+
+    paragraphs
+}
@@ -0,0 +1,224 @@
+use std::io::{Cursor, Read};
+
+use byteorder::{BigEndian, ReadBytesExt};
+
+use crate::atom::stsd::SampleDescription;
+use crate::atom::AtomParseError;
+use crate::chapters;
+use crate::subtitles;
+
+const FTAB_ATOM_ID: u32 = 0x6674_6162; // 'ftab'
+const STYL_ATOM_ID: u32 = 0x7374_796c; // 'styl'
+const HLIT_ATOM_ID: u32 = 0x686c_6974; // 'hlit'
+const HCLR_ATOM_ID: u32 = 0x6863_6c72; // 'hclr'
+const TBOX_ATOM_ID: u32 = 0x7462_6f78; // 'tbox'
+
+/// A rectangle in pixels relative to the track
+#[derive(Debug, PartialEq, Clone)]
+pub struct BoxRecord {
+    pub top: i16,
+    pub left: i16,
+    pub bottom: i16,
+    pub right: i16,
+}
+
+impl BoxRecord {
+    fn parse<R: Read>(r: &mut R) -> Result<Self, AtomParseError> {
+        Ok(BoxRecord {
+            top: r.read_i16::<BigEndian>()?,
+            left: r.read_i16::<BigEndian>()?,
+            bottom: r.read_i16::<BigEndian>()?,
+            right: r.read_i16::<BigEndian>()?,
+        })
+    }
+}
+
+/// The style of the characters from `start_char` up to `end_char`
+#[derive(Debug, PartialEq, Clone)]
+pub struct StyleRecord {
+    pub start_char: u16,
+    pub end_char: u16,
+    pub font_id: u16,
+    pub face_style_flags: u8,
+    pub font_size: u8,
+    /// RGBA
+    pub text_color: [u8; 4],
+}
+
+impl StyleRecord {
+    pub const BOLD: u8 = 0x01;
+    pub const ITALIC: u8 = 0x02;
+    pub const UNDERLINE: u8 = 0x04;
+
+    fn parse<R: Read>(r: &mut R) -> Result<Self, AtomParseError> {
+        let start_char = r.read_u16::<BigEndian>()?;
+        let end_char = r.read_u16::<BigEndian>()?;
+        let font_id = r.read_u16::<BigEndian>()?;
+        let face_style_flags = r.read_u8()?;
+        let font_size = r.read_u8()?;
+        let mut text_color = [0; 4];
+        r.read_exact(&mut text_color)?;
+
+        Ok(StyleRecord {
+            start_char,
+            end_char,
+            font_id,
+            face_style_flags,
+            font_size,
+            text_color,
+        })
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct FontRecord {
+    pub font_id: u16,
+    pub font_name: String,
+}
+
+/// A 3GPP timed text sample description from 'stsd'
+#[derive(Debug, PartialEq, Clone)]
+pub struct TextSampleEntry {
+    pub display_flags: u32,
+    pub horizontal_justification: i8,
+    pub vertical_justification: i8,
+    /// RGBA
+    pub background_color: [u8; 4],
+    pub default_text_box: BoxRecord,
+    pub default_style: StyleRecord,
+    /// The fonts in 'ftab'
+    pub font_table: Vec<FontRecord>,
+}
+
+impl TextSampleEntry {
+    /// Returns the timed text sample entry held in `description`
+    ///
+    /// # Arguments
+    ///
+    /// * `description` - A sample description whose data format is 'tx3g'
+    pub fn new(description: &SampleDescription) -> Result<Self, AtomParseError> {
+        let mut r = Cursor::new(&description.data[..]);
+
+        let display_flags = r.read_u32::<BigEndian>()?;
+        let horizontal_justification = r.read_i8()?;
+        let vertical_justification = r.read_i8()?;
+        let mut background_color = [0; 4];
+        r.read_exact(&mut background_color)?;
+        let default_text_box = BoxRecord::parse(&mut r)?;
+        let default_style = StyleRecord::parse(&mut r)?;
+
+        let mut font_table = Vec::new();
+        let children = subtitles::atoms(&description.data[r.position() as usize..]);
+
+        for (_, data) in children.iter().filter(|(t, _)| *t == FTAB_ATOM_ID) {
+            let mut r = Cursor::new(data);
+            for _ in 0..r.read_u16::<BigEndian>()? {
+                let font_id = r.read_u16::<BigEndian>()?;
+                let mut name = vec![0; r.read_u8()? as usize];
+                r.read_exact(&mut name)?;
+                font_table.push(FontRecord {
+                    font_id,
+                    font_name: String::from_utf8_lossy(&name).into_owned(),
+                });
+            }
+        }
+
+        Ok(TextSampleEntry {
+            display_flags,
+            horizontal_justification,
+            vertical_justification,
+            background_color,
+            default_text_box,
+            default_style,
+            font_table,
+        })
+    }
+}
+
+/// A modifier atom following the text of a sample
+#[derive(Debug, PartialEq, Clone)]
+pub enum TextModifier {
+    /// 'styl'
+    Style(Vec<StyleRecord>),
+    /// 'hlit'
+    Highlight { start_char: u16, end_char: u16 },
+    /// 'hclr' in RGBA
+    HighlightColor([u8; 4]),
+    /// 'tbox'
+    TextBox(BoxRecord),
+    /// Any other modifier such as 'krok' or 'href'
+    Other { modifier_type: u32, data: Vec<u8> },
+}
+
+impl TextModifier {
+    fn parse(modifier_type: u32, data: &[u8]) -> Result<Self, AtomParseError> {
+        let mut r = Cursor::new(data);
+
+        let modifier = match modifier_type {
+            STYL_ATOM_ID => {
+                let entry_count = r.read_u16::<BigEndian>()?;
+                let styles = (0..entry_count)
+                    .map(|_| StyleRecord::parse(&mut r))
+                    .collect::<Result<_, _>>()?;
+                TextModifier::Style(styles)
+            }
+            HLIT_ATOM_ID => TextModifier::Highlight {
+                start_char: r.read_u16::<BigEndian>()?,
+                end_char: r.read_u16::<BigEndian>()?,
+            },
+            HCLR_ATOM_ID => {
+                let mut color = [0; 4];
+                r.read_exact(&mut color)?;
+                TextModifier::HighlightColor(color)
+            }
+            TBOX_ATOM_ID => TextModifier::TextBox(BoxRecord::parse(&mut r)?),
+            _ => TextModifier::Other {
+                modifier_type,
+                data: data.to_vec(),
+            },
+        };
+
+        Ok(modifier)
+    }
+}
+
+/// A 'tx3g' or QuickTime 'text' sample
+#[derive(Debug, PartialEq, Clone)]
+pub struct TextSample {
+    pub text: String,
+    pub modifiers: Vec<TextModifier>,
+}
+
+impl TextSample {
+    /// Returns the text and the modifiers in `data`
+    ///
+    /// Modifiers from the first malformed one on are dropped.
+    pub fn parse(data: &[u8]) -> Self {
+        let text = chapters::decode_text_sample(data);
+        let text_size = data
+            .get(..2)
+            .map_or(0, |b| u16::from_be_bytes([b[0], b[1]]) as usize);
+
+        let mut modifiers = Vec::new();
+        for (modifier_type, data) in subtitles::atoms(data.get(2 + text_size..).unwrap_or(&[])) {
+            match TextModifier::parse(modifier_type, data) {
+                Ok(modifier) => modifiers.push(modifier),
+                Err(_) => break,
+            }
+        }
+
+        TextSample { text, modifiers }
+    }
+
+    /// Returns the style records of the 'styl' modifiers
+    pub fn styles(&self) -> Vec<&StyleRecord> {
+        self.modifiers
+            .iter()
+            .filter_map(|m| match m {
+                TextModifier::Style(styles) => Some(styles),
+                _ => None,
+            })
+            .flatten()
+            .collect()
+    }
+}
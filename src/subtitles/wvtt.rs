@@ -0,0 +1,83 @@
+use crate::atom::stsd::SampleDescription;
+use crate::subtitles;
+
+const VTTC_CONFIG_ATOM_ID: u32 = 0x7674_7443; // 'vttC'
+const VLAB_ATOM_ID: u32 = 0x766c_6162; // 'vlab'
+const VTTC_ATOM_ID: u32 = 0x7674_7463; // 'vttc'
+const IDEN_ATOM_ID: u32 = 0x6964_656e; // 'iden'
+const STTG_ATOM_ID: u32 = 0x7374_7467; // 'sttg'
+const PAYL_ATOM_ID: u32 = 0x7061_796c; // 'payl'
+
+/// Returns the UTF-8 string in `data`
+fn string(data: &[u8]) -> String {
+    String::from_utf8_lossy(data).into_owned()
+}
+
+/// A WebVTT sample description from 'stsd'
+#[derive(Debug, PartialEq, Clone)]
+pub struct WebVttSampleEntry {
+    /// The header of the WebVTT file in 'vttC'
+    pub config: Option<String>,
+    /// The label in 'vlab'
+    pub label: Option<String>,
+}
+
+impl WebVttSampleEntry {
+    /// Returns the WebVTT sample entry held in `description`
+    ///
+    /// # Arguments
+    ///
+    /// * `description` - A sample description whose data format is 'wvtt'
+    pub fn new(description: &SampleDescription) -> Self {
+        let children = subtitles::atoms(&description.data);
+        let find = |atom_type| {
+            children
+                .iter()
+                .find(|(t, _)| *t == atom_type)
+                .map(|(_, data)| string(data))
+        };
+
+        WebVttSampleEntry {
+            config: find(VTTC_CONFIG_ATOM_ID),
+            label: find(VLAB_ATOM_ID),
+        }
+    }
+}
+
+/// A cue in a 'vttc' atom of a sample
+#[derive(Debug, PartialEq, Clone)]
+pub struct WebVttCue {
+    /// The identifier in 'iden'
+    pub id: Option<String>,
+    /// The cue settings in 'sttg'
+    pub settings: Option<String>,
+    /// The text in 'payl'
+    pub payload: String,
+}
+
+/// Returns the cues in a 'wvtt' sample
+///
+/// A sample of 'vtte' alone holds no cues.
+pub fn parse_sample(data: &[u8]) -> Vec<WebVttCue> {
+    subtitles::atoms(data)
+        .into_iter()
+        .filter(|(atom_type, _)| *atom_type == VTTC_ATOM_ID)
+        .map(|(_, vttc)| {
+            let mut cue = WebVttCue {
+                id: None,
+                settings: None,
+                payload: String::new(),
+            };
+
+            for (atom_type, data) in subtitles::atoms(vttc) {
+                match atom_type {
+                    IDEN_ATOM_ID => cue.id = Some(string(data)),
+                    STTG_ATOM_ID => cue.settings = Some(string(data)),
+                    PAYL_ATOM_ID => cue.payload = string(data),
+                    _ => {}
+                }
+            }
+            cue
+        })
+        .collect()
+}
@@ -0,0 +1,119 @@
+pub mod ranges;
+pub mod rules;
+
+use std::fmt;
+
+use crate::qtfile::QtFile;
+
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+pub enum Severity {
+    Info,
+    Warning,
+    Error,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Info => write!(f, "info"),
+            Severity::Warning => write!(f, "warning"),
+            Severity::Error => write!(f, "error"),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Finding {
+    pub severity: Severity,
+    pub rule_id: &'static str,
+    pub atom_offset: u64,
+    pub message: String,
+}
+
+impl Finding {
+    pub fn new(
+        severity: Severity,
+        rule_id: &'static str,
+        atom_offset: u64,
+        message: String,
+    ) -> Self {
+        Self {
+            severity,
+            rule_id,
+            atom_offset,
+            message,
+        }
+    }
+}
+
+impl fmt::Display for Finding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}[{}] at 0x{:016x}: {}",
+            self.severity, self.rule_id, self.atom_offset, self.message
+        )
+    }
+}
+
+/// A structural check run against a parsed file
+pub trait Rule {
+    /// Returns the identifier reported in each finding of this rule
+    fn id(&self) -> &'static str;
+
+    /// Appends the findings of this rule for `qt` to `findings`
+    fn check(&self, qt: &QtFile, findings: &mut Vec<Finding>);
+}
+
+pub struct Validator {
+    rules: Vec<Box<dyn Rule>>,
+}
+
+impl Validator {
+    /// Returns a validator without any rules
+    pub fn new() -> Self {
+        Validator { rules: Vec::new() }
+    }
+
+    pub fn add_rule(&mut self, rule: Box<dyn Rule>) -> &mut Self {
+        self.rules.push(rule);
+        self
+    }
+
+    /// Returns the findings of every rule, sorted by atom offset
+    pub fn validate(&self, qt: &QtFile) -> Vec<Finding> {
+        let mut findings = Vec::new();
+
+        for rule in &self.rules {
+            rule.check(qt, &mut findings);
+        }
+
+        findings.sort_by_key(|f| f.atom_offset);
+        findings
+    }
+}
+
+impl Default for Validator {
+    /// Returns a validator with all the rules in [`rules`]
+    fn default() -> Self {
+        let mut validator = Validator::new();
+
+        validator
+            .add_rule(Box::new(rules::FtypFirst))
+            .add_rule(Box::new(rules::RequiredAtoms))
+            .add_rule(Box::new(rules::SampleCounts))
+            .add_rule(Box::new(rules::ChunkOffsetsInMdat))
+            .add_rule(Box::new(rules::SyncSamples))
+            .add_rule(Box::new(rules::TrackIds))
+            .add_rule(Box::new(rules::TrackReferences))
+            .add_rule(Box::new(rules::EditListDuration))
+            .add_rule(Box::new(rules::SampleRanges));
+
+        validator
+    }
+}
+
+/// Returns the findings of the default rule set for `qt`
+pub fn validate(qt: &QtFile) -> Vec<Finding> {
+    Validator::default().validate(qt)
+}
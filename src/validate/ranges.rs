@@ -0,0 +1,156 @@
+use std::fmt;
+
+use crate::qtfile::QtFile;
+
+/// A sample of a track
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct SampleRef {
+    pub track_id: u32,
+    /// The 0-based index in decoding order
+    pub index: usize,
+}
+
+impl fmt::Display for SampleRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "track {} sample {}", self.track_id, self.index)
+    }
+}
+
+/// A problem with the bytes a sample refers to
+#[derive(Debug, PartialEq, Clone)]
+pub enum SampleRangeIssue {
+    /// Samples of two different tracks share `size` bytes at `offset`
+    Overlap {
+        first: SampleRef,
+        second: SampleRef,
+        offset: u64,
+        size: u64,
+    },
+    /// The sample is not wholly inside the payload of any 'mdat'
+    OutsideMdat {
+        sample: SampleRef,
+        offset: u64,
+        size: u64,
+    },
+    /// The sample extends past the end of the file
+    PastEof {
+        sample: SampleRef,
+        offset: u64,
+        size: u64,
+    },
+}
+
+impl fmt::Display for SampleRangeIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SampleRangeIssue::Overlap {
+                first,
+                second,
+                offset,
+                size,
+            } => write!(
+                f,
+                "{} and {} share {} bytes at 0x{:x}",
+                first, second, size, offset
+            ),
+            SampleRangeIssue::OutsideMdat {
+                sample,
+                offset,
+                size,
+            } => write!(
+                f,
+                "{} ({} bytes at 0x{:x}) is outside of every 'mdat'",
+                sample, size, offset
+            ),
+            SampleRangeIssue::PastEof {
+                sample,
+                offset,
+                size,
+            } => write!(
+                f,
+                "{} ({} bytes at 0x{:x}) extends past the end of the file",
+                sample, size, offset
+            ),
+        }
+    }
+}
+
+/// Returns the problems with the byte ranges of the samples of all tracks in
+/// `qt`, sorted by offset
+///
+/// A sample which extends past the end of the file is reported only as
+/// [`SampleRangeIssue::PastEof`]. Empty samples are ignored.
+pub fn check(qt: &QtFile) -> Vec<SampleRangeIssue> {
+    let mdat_ranges = qt.mdat().map(|m| m.data_range()).collect::<Vec<_>>();
+    let file_size = qt.file_size();
+
+    let mut ranges = qt
+        .moov()
+        .iter()
+        .flat_map(|moov| moov.trak_atom.iter())
+        .flat_map(|trak| {
+            let track_id = trak.tkhd_atom.track_id;
+            trak.samples().into_iter().map(move |s| {
+                (
+                    SampleRef {
+                        track_id,
+                        index: s.index,
+                    },
+                    s.offset,
+                    s.size as u64,
+                )
+            })
+        })
+        .filter(|&(_, _, size)| size > 0)
+        .collect::<Vec<_>>();
+    ranges.sort_by_key(|&(_, head, size)| (head, size));
+
+    let mut issues = Vec::new();
+    // the ranges which have not ended before the current one
+    let mut active: Vec<(SampleRef, u64, u64)> = Vec::new();
+
+    for &(sample, head, size) in &ranges {
+        // a range ending past the largest offset is outside of any file
+        let tail = match head.checked_add(size) {
+            Some(tail) => tail,
+            None => {
+                issues.push(SampleRangeIssue::PastEof {
+                    sample,
+                    offset: head,
+                    size,
+                });
+                continue;
+            }
+        };
+
+        active.retain(|&(_, _, t)| t > head);
+
+        for &(other, _, other_tail) in &active {
+            if other.track_id != sample.track_id {
+                issues.push(SampleRangeIssue::Overlap {
+                    first: other,
+                    second: sample,
+                    offset: head,
+                    size: tail.min(other_tail) - head,
+                });
+            }
+        }
+        active.push((sample, head, tail));
+
+        if tail > file_size {
+            issues.push(SampleRangeIssue::PastEof {
+                sample,
+                offset: head,
+                size,
+            });
+        } else if !mdat_ranges.iter().any(|&(h, t)| h <= head && tail <= t) {
+            issues.push(SampleRangeIssue::OutsideMdat {
+                sample,
+                offset: head,
+                size,
+            });
+        }
+    }
+
+    issues
+}
@@ -0,0 +1,438 @@
+use std::collections::HashMap;
+
+use crate::atom::tref::TrackReferenceError;
+use crate::atom::{moov, stbl, trak};
+use crate::qtfile::{QtFile, TopLevelAtom};
+use crate::validate::{ranges, Finding, Rule, Severity};
+
+/// Calls `f` for every 'moov' atom in `qt`
+fn for_each_moov<F: FnMut(&moov::MoovAtom)>(qt: &QtFile, mut f: F) {
+    for atom in qt.iter() {
+        if let TopLevelAtom::Moov(moov) = atom {
+            f(moov);
+        }
+    }
+}
+
+/// Calls `f` for every track that has a sample table
+fn for_each_stbl<F: FnMut(&trak::TrakAtom, &stbl::StblAtom)>(qt: &QtFile, mut f: F) {
+    for_each_moov(qt, |moov| {
+        for trak in &moov.trak_atom {
+            if let Some(stbl) = trak.stbl_atom() {
+                f(trak, stbl);
+            }
+        }
+    });
+}
+
+/// 'ftyp' should be the first atom of the file
+pub struct FtypFirst;
+
+impl Rule for FtypFirst {
+    fn id(&self) -> &'static str {
+        "ftyp-first"
+    }
+
+    fn check(&self, qt: &QtFile, findings: &mut Vec<Finding>) {
+        let position = qt.iter().position(|a| matches!(a, TopLevelAtom::Ftyp(_)));
+
+        match position {
+            Some(0) => {}
+            Some(i) => {
+                let ftyp = qt.ftyp().unwrap();
+                findings.push(Finding::new(
+                    Severity::Error,
+                    self.id(),
+                    ftyp.atom_head.atom_offset,
+                    format!("'ftyp' is the atom #{} instead of the first one", i + 1),
+                ));
+            }
+            None => findings.push(Finding::new(
+                Severity::Warning,
+                self.id(),
+                0,
+                "'ftyp' was not found".into(),
+            )),
+        }
+    }
+}
+
+/// Mandatory children of 'moov', 'mdia' and 'stbl' should be present
+///
+/// A 'minf' without 'dinf' or 'stbl' fails to parse, so the file never gets
+/// here and those are not reported.
+pub struct RequiredAtoms;
+
+impl RequiredAtoms {
+    fn missing(&self, atom_offset: u64, parent: &str, child: &str) -> Finding {
+        Finding::new(
+            Severity::Error,
+            self.id(),
+            atom_offset,
+            format!("'{}' has no '{}'", parent, child),
+        )
+    }
+}
+
+impl Rule for RequiredAtoms {
+    fn id(&self) -> &'static str {
+        "required-atom"
+    }
+
+    fn check(&self, qt: &QtFile, findings: &mut Vec<Finding>) {
+        if qt.moov().is_none() {
+            findings.push(Finding::new(
+                Severity::Error,
+                self.id(),
+                0,
+                "'moov' was not found".into(),
+            ));
+        }
+
+        for_each_moov(qt, |moov| {
+            if moov.mvhd_atom.is_none() {
+                findings.push(self.missing(moov.atom_head.atom_offset, "moov", "mvhd"));
+            }
+
+            for trak in &moov.trak_atom {
+                let mdia = &trak.mdia_atom;
+                let offset = mdia.atom_head.atom_offset;
+
+                if mdia.hdlr_atom.is_none() {
+                    findings.push(self.missing(offset, "mdia", "hdlr"));
+                }
+
+                let minf = match &mdia.minf_atom {
+                    Some(minf) => minf,
+                    None => {
+                        findings.push(self.missing(offset, "mdia", "minf"));
+                        continue;
+                    }
+                };
+
+                let stbl = minf.stbl_atom();
+                let offset = stbl.atom_head.atom_offset;
+
+                if stbl.stsd_atom.is_none() {
+                    findings.push(self.missing(offset, "stbl", "stsd"));
+                }
+                if stbl.stts_atom.is_none() {
+                    findings.push(self.missing(offset, "stbl", "stts"));
+                }
+                if stbl.stsc_atom.is_none() {
+                    findings.push(self.missing(offset, "stbl", "stsc"));
+                }
+                if stbl.stsz_atom.is_none() {
+                    findings.push(self.missing(offset, "stbl", "stsz"));
+                }
+                if stbl.stco_atom.is_none() && stbl.co64_atom.is_none() {
+                    findings.push(self.missing(offset, "stbl", "stco"));
+                }
+            }
+        });
+    }
+}
+
+/// 'stts', 'stsz', 'stsc' and 'ctts' should describe the same number of samples
+pub struct SampleCounts;
+
+impl Rule for SampleCounts {
+    fn id(&self) -> &'static str {
+        "sample-count"
+    }
+
+    fn check(&self, qt: &QtFile, findings: &mut Vec<Finding>) {
+        for_each_stbl(qt, |trak, stbl| {
+            let stsz = match &stbl.stsz_atom {
+                Some(stsz) => stsz,
+                None => return,
+            };
+            let expected = stsz.number_of_entries as u64;
+            let track_id = trak.tkhd_atom.track_id;
+
+            let mut compare = |name: &str, atom_offset: u64, count: u64| {
+                if count != expected {
+                    findings.push(Finding::new(
+                        Severity::Error,
+                        self.id(),
+                        atom_offset,
+                        format!(
+                            "track {}: '{}' describes {} samples but 'stsz' has {}",
+                            track_id, name, count, expected
+                        ),
+                    ));
+                }
+            };
+
+            if let Some(stts) = &stbl.stts_atom {
+                compare("stts", stts.atom_head.atom_offset, stts.sample_count());
+            }
+            if let Some(ctts) = &stbl.ctts_atom {
+                compare("ctts", ctts.atom_head.atom_offset, ctts.sample_count());
+            }
+            if let Some(stsc) = &stbl.stsc_atom {
+                if stbl.stco_atom.is_some() || stbl.co64_atom.is_some() {
+                    compare(
+                        "stsc",
+                        stsc.atom_head.atom_offset,
+                        stsc.sample_count(stbl.chunk_offsets().len() as u32),
+                    );
+                }
+            }
+        });
+    }
+}
+
+/// Chunk offsets should point inside an 'mdat' atom
+pub struct ChunkOffsetsInMdat;
+
+impl Rule for ChunkOffsetsInMdat {
+    fn id(&self) -> &'static str {
+        "chunk-offset"
+    }
+
+    fn check(&self, qt: &QtFile, findings: &mut Vec<Finding>) {
+        let mdat_ranges = qt.mdat().map(|m| m.data_range()).collect::<Vec<_>>();
+
+        for_each_stbl(qt, |trak, stbl| {
+            let atom_offset = match (&stbl.stco_atom, &stbl.co64_atom) {
+                (Some(stco), _) => stco.atom_head.atom_offset,
+                (None, Some(co64)) => co64.atom_head.atom_offset,
+                (None, None) => return,
+            };
+
+            for (i, offset) in stbl.chunk_offsets().into_iter().enumerate() {
+                if !mdat_ranges
+                    .iter()
+                    .any(|(head, tail)| *head <= offset && offset < *tail)
+                {
+                    findings.push(Finding::new(
+                        Severity::Error,
+                        self.id(),
+                        atom_offset,
+                        format!(
+                            "track {}: chunk {} at 0x{:x} is outside of every 'mdat'",
+                            trak.tkhd_atom.track_id,
+                            i + 1,
+                            offset
+                        ),
+                    ));
+                }
+            }
+        });
+    }
+}
+
+/// 'stss' entries should be ascending sample numbers within the track
+pub struct SyncSamples;
+
+impl Rule for SyncSamples {
+    fn id(&self) -> &'static str {
+        "sync-sample"
+    }
+
+    fn check(&self, qt: &QtFile, findings: &mut Vec<Finding>) {
+        for_each_stbl(qt, |trak, stbl| {
+            let stss = match &stbl.stss_atom {
+                Some(stss) => stss,
+                None => return,
+            };
+            let sample_count = stbl.stsz_atom.as_ref().map(|s| s.number_of_entries);
+            let track_id = trak.tkhd_atom.track_id;
+            let atom_offset = stss.atom_head.atom_offset;
+
+            let mut previous = 0;
+
+            for sample in &stss.sync_sample_table {
+                let out_of_range = match sample_count {
+                    Some(count) => *sample == 0 || *sample > count,
+                    None => *sample == 0,
+                };
+
+                if out_of_range {
+                    findings.push(Finding::new(
+                        Severity::Error,
+                        self.id(),
+                        atom_offset,
+                        format!("track {}: sync sample {} is out of range", track_id, sample),
+                    ));
+                }
+
+                if *sample <= previous {
+                    findings.push(Finding::new(
+                        Severity::Error,
+                        self.id(),
+                        atom_offset,
+                        format!(
+                            "track {}: sync sample {} follows {}",
+                            track_id, sample, previous
+                        ),
+                    ));
+                }
+
+                previous = *sample;
+            }
+        });
+    }
+}
+
+/// Track IDs should be unique, nonzero and below 'mvhd' next track ID
+pub struct TrackIds;
+
+impl Rule for TrackIds {
+    fn id(&self) -> &'static str {
+        "track-id"
+    }
+
+    fn check(&self, qt: &QtFile, findings: &mut Vec<Finding>) {
+        for_each_moov(qt, |moov| {
+            let next_track_id = moov.mvhd_atom.as_ref().map(|mvhd| mvhd.next_track_id);
+            let mut seen = HashMap::new();
+
+            for trak in &moov.trak_atom {
+                let tkhd = &trak.tkhd_atom;
+                let atom_offset = tkhd.atom_head.atom_offset;
+
+                if tkhd.track_id == 0 {
+                    findings.push(Finding::new(
+                        Severity::Error,
+                        self.id(),
+                        atom_offset,
+                        "track ID 0 is reserved".into(),
+                    ));
+                }
+
+                if let Some(first) = seen.insert(tkhd.track_id, atom_offset) {
+                    findings.push(Finding::new(
+                        Severity::Error,
+                        self.id(),
+                        atom_offset,
+                        format!(
+                            "track ID {} is already used by 'tkhd' at 0x{:x}",
+                            tkhd.track_id, first
+                        ),
+                    ));
+                }
+
+                match next_track_id {
+                    Some(next) if tkhd.track_id >= next => findings.push(Finding::new(
+                        Severity::Error,
+                        self.id(),
+                        atom_offset,
+                        format!(
+                            "track ID {} is not below the next track ID {}",
+                            tkhd.track_id, next
+                        ),
+                    )),
+                    _ => {}
+                }
+            }
+        });
+    }
+}
+
+/// Track IDs in 'tref' should refer to tracks in the same movie
+pub struct TrackReferences;
+
+impl Rule for TrackReferences {
+    fn id(&self) -> &'static str {
+        "track-reference"
+    }
+
+    fn check(&self, qt: &QtFile, findings: &mut Vec<Finding>) {
+        for_each_moov(qt, |moov| {
+            for error in moov.dangling_references() {
+                let TrackReferenceError::DanglingReference {
+                    atom_offset,
+                    track_id,
+                    reference_type,
+                    referenced_track_id,
+                } = error;
+
+                findings.push(Finding::new(
+                    Severity::Error,
+                    self.id(),
+                    atom_offset,
+                    format!(
+                        "track {} refers to missing track {} by '{}'",
+                        track_id, referenced_track_id, reference_type
+                    ),
+                ));
+            }
+        });
+    }
+}
+
+/// 'tkhd' duration should equal the sum of the edit durations
+pub struct EditListDuration;
+
+impl Rule for EditListDuration {
+    fn id(&self) -> &'static str {
+        "edit-list-duration"
+    }
+
+    fn check(&self, qt: &QtFile, findings: &mut Vec<Finding>) {
+        for_each_moov(qt, |moov| {
+            for trak in &moov.trak_atom {
+                let elst = match trak.edts_atom.as_ref().and_then(|e| e.elst_atom.as_ref()) {
+                    Some(elst) => elst,
+                    None => continue,
+                };
+
+                let edit_duration = elst
+                    .edit_list_table
+                    .iter()
+                    .map(|e| e.track_duration)
+                    .sum::<u64>();
+                let tkhd = &trak.tkhd_atom;
+
+                if edit_duration != tkhd.duration as u64 {
+                    findings.push(Finding::new(
+                        Severity::Warning,
+                        self.id(),
+                        tkhd.atom_head.atom_offset,
+                        format!(
+                            "track {}: duration is {} but the edit list lasts {}",
+                            tkhd.track_id, tkhd.duration, edit_duration
+                        ),
+                    ));
+                }
+            }
+        });
+    }
+}
+
+/// Sample data should lie in 'mdat' without overlapping other tracks
+pub struct SampleRanges;
+
+impl Rule for SampleRanges {
+    fn id(&self) -> &'static str {
+        "sample-range"
+    }
+
+    fn check(&self, qt: &QtFile, findings: &mut Vec<Finding>) {
+        let mut trak_offsets = HashMap::new();
+        for_each_moov(qt, |moov| {
+            for trak in &moov.trak_atom {
+                trak_offsets
+                    .entry(trak.tkhd_atom.track_id)
+                    .or_insert(trak.atom_head.atom_offset);
+            }
+        });
+
+        for issue in ranges::check(qt) {
+            let sample = match &issue {
+                ranges::SampleRangeIssue::Overlap { second, .. } => second,
+                ranges::SampleRangeIssue::OutsideMdat { sample, .. }
+                | ranges::SampleRangeIssue::PastEof { sample, .. } => sample,
+            };
+
+            findings.push(Finding::new(
+                Severity::Error,
+                self.id(),
+                trak_offsets.get(&sample.track_id).copied().unwrap_or(0),
+                issue.to_string(),
+            ));
+        }
+    }
+}
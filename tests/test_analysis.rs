@@ -0,0 +1,148 @@
+use std::fs;
+use std::io::Cursor;
+use std::path::PathBuf;
+
+use atom_analyzer::analysis;
+use atom_analyzer::analysis::samples::{self, TimeUnit};
+use atom_analyzer::atom::hdlr::ComponentSubType;
+use atom_analyzer::qtfile;
+
+#[test]
+fn test_camouflage_vga_mov_analysis() {
+    let file_name = PathBuf::from("tests/samples/camouflage_vga.mov");
+    let qt = qtfile::parse_file(file_name).unwrap();
+
+    let report = analysis::analyze(&qt, 1.0);
+    assert_eq!(report.tracks.len(), 1);
+    assert_eq!(report.interleave, None);
+
+    let track = &report.tracks[0];
+    assert_eq!(track.track_id, 1);
+    assert_eq!(track.media_type, Some(ComponentSubType::VideoMedia));
+    assert_eq!(track.sample_count, 30);
+    assert_eq!(track.gop_lengths, vec![30]);
+    assert_eq!(track.frame_durations.get(&512), Some(&30));
+    assert!(!track.is_variable_frame_rate());
+    assert!(track.has_b_frames);
+
+    // the whole track lasts one second
+    let bitrate = track.bitrate.as_ref().unwrap();
+    let bits = qt.moov().unwrap().trak_atom[0]
+        .samples()
+        .iter()
+        .map(|s| s.size as f64 * 8.0)
+        .sum::<f64>();
+    assert_eq!(bitrate.avg, bits);
+    assert_eq!(bitrate.min, bits);
+    assert_eq!(bitrate.max, bits);
+
+    let report = analysis::analyze(&qt, 0.5);
+    let bitrate = report.tracks[0].bitrate.as_ref().unwrap();
+    assert!(bitrate.min < bitrate.avg && bitrate.avg < bitrate.max);
+}
+
+#[test]
+fn test_samples_output() {
+    let file_name = PathBuf::from("tests/samples/camouflage_vga.mov");
+    let qt = qtfile::parse_file(file_name).unwrap();
+    let trak = &qt.moov().unwrap().trak_atom[0];
+    let time_scale = trak.media_time_scale();
+    let list = trak.samples();
+
+    let mut out = Vec::new();
+    samples::write_csv(&mut out, &list, time_scale, TimeUnit::Ticks).unwrap();
+    let out = String::from_utf8(out).unwrap();
+    let lines: Vec<&str> = out.lines().collect();
+    assert_eq!(lines.len(), 31);
+    assert_eq!(lines[0], "index,chunk,offset,size,dts,cts,duration,sync");
+    assert_eq!(lines[1], "0,1,36,23682,0,1024,512,1");
+
+    let mut out = Vec::new();
+    samples::write_csv(&mut out, &list, time_scale, TimeUnit::Seconds).unwrap();
+    let out = String::from_utf8(out).unwrap();
+    assert_eq!(
+        out.lines().nth(1),
+        Some("0,1,36,23682,0.000000,0.066667,0.033333,1")
+    );
+
+    let mut out = Vec::new();
+    samples::write_table(&mut out, &list, time_scale, TimeUnit::Seconds).unwrap();
+    let out = String::from_utf8(out).unwrap();
+    let lines: Vec<&str> = out.lines().collect();
+    assert_eq!(
+        lines[0],
+        "   index  chunk       offset       size            dts            cts     duration sync"
+    );
+    assert_eq!(
+        lines[1],
+        "       0      1         0x24      23682       0.000000       0.066667     0.033333 yes"
+    );
+    assert_eq!("ticks".parse::<TimeUnit>(), Ok(TimeUnit::Ticks));
+    assert!("frames".parse::<TimeUnit>().is_err());
+}
+
+/// Inserts `bytes` at `offset` and grows the atoms at `parents` to hold them
+fn insert(data: &mut Vec<u8>, offset: usize, bytes: &[u8], parents: &[usize]) {
+    data.splice(offset..offset, bytes.iter().copied());
+    for parent in parents {
+        let mut size = [0; 4];
+        size.copy_from_slice(&data[*parent..parent + 4]);
+        let size = u32::from_be_bytes(size) + bytes.len() as u32;
+        data[*parent..parent + 4].copy_from_slice(&size.to_be_bytes());
+    }
+}
+
+/// Returns the sample with its video track split into three chunks of ten
+/// samples at 0x24, 0x1000 and 0x2000, and a copy of the track as sound track
+/// 2 with its chunks at 0x800, 0x2800 and 0x3000
+fn two_tracks() -> Vec<u8> {
+    let mut data = fs::read("tests/samples/camouflage_vga.mov").unwrap();
+
+    // ten samples per chunk in stsc, three chunk offsets in stco
+    data[0x6539..0x653d].copy_from_slice(&10u32.to_be_bytes());
+    data[0x65d9..0x65dd].copy_from_slice(&3u32.to_be_bytes());
+    let offsets = [0x1000u32.to_be_bytes(), 0x2000u32.to_be_bytes()].concat();
+    insert(
+        &mut data,
+        0x65e1,
+        &offsets,
+        &[0x618c, 0x6200, 0x6288, 0x62dd, 0x6349, 0x65cd],
+    );
+
+    let mut trak = data[0x6200..0x65e9].to_vec();
+    trak[0x1c..0x20].copy_from_slice(&2u32.to_be_bytes());
+    trak[0xc0..0xc4].copy_from_slice(b"soun");
+    for (i, offset) in [0x800u32, 0x2800, 0x3000].iter().enumerate() {
+        trak[0x3dd + i * 4..0x3e1 + i * 4].copy_from_slice(&offset.to_be_bytes());
+    }
+    insert(&mut data, 0x65e9, &trak, &[0x618c]);
+
+    data
+}
+
+#[test]
+fn test_interleave() {
+    let qt = qtfile::parse(&mut Cursor::new(two_tracks())).unwrap();
+    let traks = &qt.moov().unwrap().trak_atom;
+    assert_eq!(traks.len(), 2);
+
+    // in file order the chunks start at 0s (video), 0s (audio), 1/3s (video),
+    // 2/3s (video), 1/3s (audio) and 2/3s (audio)
+    let expected = analysis::Interleave {
+        video_track_id: 1,
+        audio_track_id: 2,
+        max_distance: 20.0 * 512.0 / 15360.0,
+    };
+    assert_eq!(
+        analysis::interleave(&traks[0], &traks[1]),
+        Some(expected.clone())
+    );
+
+    let report = analysis::analyze(&qt, 1.0);
+    assert_eq!(report.tracks.len(), 2);
+    assert_eq!(
+        report.tracks[1].media_type,
+        Some(ComponentSubType::SoundMedia)
+    );
+    assert_eq!(report.interleave, Some(expected));
+}
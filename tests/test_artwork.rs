@@ -0,0 +1,136 @@
+use std::fs;
+use std::io::Cursor;
+
+use atom_analyzer::artwork::{ArtworkSource, ImageFormat};
+use atom_analyzer::qtfile;
+
+const JPEG: &[u8] = &[0xff, 0xd8, 0xff, 0xe0, 0, 0x10, b'J', b'F', b'I', b'F'];
+const PNG: &[u8] = b"\x89PNG\r\n\x1a\n\0\0\0\x0dIHDR";
+
+fn atom(atom_type: &[u8; 4], body: &[u8]) -> Vec<u8> {
+    let mut data = (8 + body.len() as u32).to_be_bytes().to_vec();
+    data.extend_from_slice(atom_type);
+    data.extend_from_slice(body);
+    data
+}
+
+fn data(type_indicator: u32, value: &[u8]) -> Vec<u8> {
+    let mut body = type_indicator.to_be_bytes().to_vec();
+    body.extend_from_slice(&[0, 0, 0, 0]);
+    body.extend_from_slice(value);
+    atom(b"data", &body)
+}
+
+/// Returns a QuickDraw version 2 picture
+fn pict() -> Vec<u8> {
+    let mut pict = vec![0, 0, 0, 0, 0, 0, 0, 0x20, 0, 0x20];
+    pict.extend_from_slice(&[0x00, 0x11, 0x02, 0xff, 0x0c, 0x00, 0x00, 0xff]);
+    pict
+}
+
+/// Returns the sample with `udta` appended to 'moov/udta' and `moov` to
+/// 'moov', followed by `tail`
+fn sample_with(udta: &[u8], moov: &[u8], tail: &[u8]) -> Vec<u8> {
+    let mut data = fs::read("tests/samples/camouflage_vga.mov").unwrap();
+
+    // 'moov' at 0x618c and its last child 'udta' at 0x65e1 end the file
+    let grow = |data: &mut Vec<u8>, offset: usize, n: usize| {
+        let size = u32::from_be_bytes([
+            data[offset],
+            data[offset + 1],
+            data[offset + 2],
+            data[offset + 3],
+        ]);
+        data[offset..offset + 4].copy_from_slice(&(size + n as u32).to_be_bytes());
+    };
+
+    data.extend_from_slice(udta);
+    grow(&mut data, 0x65e1, udta.len());
+    grow(&mut data, 0x618c, udta.len());
+    data.extend_from_slice(moov);
+    grow(&mut data, 0x618c, moov.len());
+    data.extend_from_slice(tail);
+    data
+}
+
+#[test]
+fn test_artwork() {
+    // a full box thumbnail
+    let mut thmb = vec![0, 0, 0, 0];
+    thmb.extend_from_slice(JPEG);
+    let udta = atom(b"thmb", &thmb);
+
+    // covers typed as JPEG holding a PNG and as PNG holding unknown data
+    let mut covr = data(13, PNG);
+    covr.extend(data(14, b"not an image"));
+    let mut meta = vec![0, 0, 0, 0];
+    meta.extend(atom(b"hdlr", &[&[0; 8][..], b"mdir", &[0; 12]].concat()));
+    meta.extend(atom(b"ilst", &atom(b"covr", &covr)));
+    let moov = atom(b"meta", &meta);
+
+    // 'pnot' refers to the second 'PICT'
+    let mut pnot = vec![0, 0, 0, 0, 0, 0];
+    pnot.extend_from_slice(b"PICT");
+    pnot.extend_from_slice(&[0, 2]);
+    let mut tail = atom(b"pnot", &pnot);
+    tail.extend(atom(b"PICT", JPEG));
+    tail.extend(atom(b"PICT", &pict()));
+
+    let file = sample_with(&udta, &moov, &tail);
+    let qt = qtfile::parse(&mut Cursor::new(&file)).unwrap();
+    let artwork = qt.artwork();
+
+    assert_eq!(
+        artwork
+            .iter()
+            .map(|a| (a.source, a.format))
+            .collect::<Vec<_>>(),
+        vec![
+            (ArtworkSource::Cover, ImageFormat::Png),
+            (ArtworkSource::Cover, ImageFormat::Png),
+            (ArtworkSource::Thumbnail, ImageFormat::Jpeg),
+            (ArtworkSource::Preview, ImageFormat::Pict),
+        ]
+    );
+    assert_eq!(artwork[1].data, b"not an image");
+    assert_eq!(artwork[2].data, JPEG);
+    assert_eq!(artwork[3].data, pict());
+
+    for image in &artwork {
+        let offset = image.offset as usize;
+        assert_eq!(file[offset..offset + image.data.len()], image.data[..]);
+    }
+
+    assert_eq!(
+        artwork[2].to_string(),
+        format!("thumbnail JPEG (10 bytes at 0x{:x})", 0x6602 + 12)
+    );
+}
+
+#[test]
+fn test_artwork_without_pnot() {
+    let file = sample_with(&[], &[], &atom(b"PICT", JPEG));
+    let qt = qtfile::parse(&mut Cursor::new(&file)).unwrap();
+    let artwork = qt.artwork();
+
+    assert_eq!(artwork.len(), 1);
+    assert_eq!(artwork[0].source, ArtworkSource::Preview);
+    assert_eq!(artwork[0].format, ImageFormat::Jpeg);
+    assert_eq!(artwork[0].offset, 0x6602 + 8);
+
+    let qt = qtfile::parse_file("tests/samples/camouflage_vga.mov".into()).unwrap();
+    assert!(qt.artwork().is_empty());
+}
+
+#[test]
+fn test_image_format() {
+    assert_eq!(ImageFormat::detect(JPEG), ImageFormat::Jpeg);
+    assert_eq!(ImageFormat::detect(PNG), ImageFormat::Png);
+    assert_eq!(
+        ImageFormat::detect(b"BM\x46\0\0\0\0\0\0\0\x36\0\0\0"),
+        ImageFormat::Bmp
+    );
+    assert_eq!(ImageFormat::detect(&pict()), ImageFormat::Pict);
+    assert_eq!(ImageFormat::detect(b"BM"), ImageFormat::Unknown);
+    assert_eq!(ImageFormat::Unknown.extension(), "bin");
+}
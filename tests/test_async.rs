@@ -0,0 +1,141 @@
+#![cfg(feature = "async")]
+
+use std::fs;
+use std::io::{self, SeekFrom};
+use std::path::PathBuf;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use tokio::io::{AsyncRead, AsyncSeek, ReadBuf};
+
+use atom_analyzer::atom::{self, mvhd};
+use atom_analyzer::qtfile;
+
+/// An in-memory reader which hands out at most 5 bytes at a time and returns
+/// `Pending` before every read
+struct PartialReader {
+    data: Vec<u8>,
+    position: u64,
+    ready: bool,
+}
+
+impl PartialReader {
+    fn new(data: Vec<u8>) -> Self {
+        PartialReader {
+            data,
+            position: 0,
+            ready: false,
+        }
+    }
+}
+
+impl AsyncRead for PartialReader {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        if !self.ready {
+            self.ready = true;
+            cx.waker().wake_by_ref();
+            return Poll::Pending;
+        }
+        self.ready = false;
+
+        let start = (self.position as usize).min(self.data.len());
+        let len = buf.remaining().min(5).min(self.data.len() - start);
+        buf.put_slice(&self.data[start..start + len]);
+        self.position += len as u64;
+
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl AsyncSeek for PartialReader {
+    fn start_seek(mut self: Pin<&mut Self>, position: SeekFrom) -> io::Result<()> {
+        let position = match position {
+            SeekFrom::Start(p) => p as i64,
+            SeekFrom::Current(p) => self.position as i64 + p,
+            SeekFrom::End(p) => self.data.len() as i64 + p,
+        };
+        if position < 0 {
+            return Err(io::ErrorKind::InvalidInput.into());
+        }
+        self.position = position as u64;
+        Ok(())
+    }
+
+    fn poll_complete(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<u64>> {
+        Poll::Ready(Ok(self.position))
+    }
+}
+
+#[tokio::test]
+async fn test_camouflage_vga_mov_async() {
+    let file_name = PathBuf::from("tests/samples/camouflage_vga.mov");
+    let data = fs::read(&file_name).unwrap();
+
+    let mut r = PartialReader::new(data.clone());
+    assert_eq!(
+        qtfile::parse_async(&mut r).await.unwrap(),
+        qtfile::parse_file(file_name.clone()).unwrap()
+    );
+    assert_eq!(
+        qtfile::parse_file_async(file_name.clone()).await.unwrap(),
+        qtfile::parse_file(file_name).unwrap()
+    );
+
+    let mut r = PartialReader::new(data);
+    r.position = 0x618c;
+    assert_eq!(
+        atom::parse_atom_head_async(&mut r).await.unwrap(),
+        atom::AtomHead {
+            atom_offset: 0x618c,
+            atom_size: 0x6602 - 0x618c,
+            atom_type: atom::moov::ATOM_ID,
+        }
+    );
+
+    r.position = 0x6194;
+    let mvhd = atom::parse_async(&mut r).await.unwrap();
+    assert_eq!(
+        mvhd.downcast_ref::<mvhd::MvhdAtom>()
+            .unwrap()
+            .atom_head
+            .atom_size,
+        0x6c
+    );
+    assert_eq!(r.position, 0x6200);
+}
+
+#[tokio::test]
+async fn test_async_truncated() {
+    let mut r = PartialReader::new(vec![0, 0, 0, 16, b'f', b'r', b'e', b'e', 0, 0]);
+
+    assert!(matches!(
+        atom::parse_async(&mut r).await,
+        Err(atom::AtomParseError::IoError(_))
+    ));
+
+    let mut r = PartialReader::new(vec![0, 0, 0, 4, b'f', b'r', b'e', b'e']);
+
+    assert!(matches!(
+        qtfile::parse_async(&mut r).await,
+        Err(qtfile::QtFileError::AtomParseError(
+            atom::AtomParseError::UnexpectedError(0)
+        ))
+    ));
+}
+
+#[tokio::test]
+async fn test_async_mdat_extended_size() {
+    let mut data = vec![0, 0, 0, 1, b'm', b'd', b'a', b't'];
+    // an extended size which would fit in 32 bits
+    data.extend_from_slice(&20u64.to_be_bytes());
+    data.extend_from_slice(&[1, 2, 3, 4]);
+
+    let qt = qtfile::parse_async(&mut PartialReader::new(data))
+        .await
+        .unwrap();
+    assert_eq!(qt.mdat().next().unwrap().data_range(), (16, 20));
+}
@@ -0,0 +1,232 @@
+use std::fs;
+use std::io::{self, Cursor};
+
+use atom_analyzer::atom::tref::TrackReferenceType;
+use atom_analyzer::atom::udta::UserData;
+use atom_analyzer::atom::{chpl, AtomParseError};
+use atom_analyzer::chapters::{self, Chapter};
+use atom_analyzer::qtfile;
+
+fn atom(atom_type: &[u8; 4], body: &[u8]) -> Vec<u8> {
+    let mut data = (8 + body.len() as u32).to_be_bytes().to_vec();
+    data.extend_from_slice(atom_type);
+    data.extend_from_slice(body);
+    data
+}
+
+fn full_atom(atom_type: &[u8; 4], values: &[u32]) -> Vec<u8> {
+    let mut body = vec![0; 4];
+    for v in values {
+        body.extend_from_slice(&v.to_be_bytes());
+    }
+    atom(atom_type, &body)
+}
+
+/// Adds `n` to the size of the atom at `offset`
+fn grow(data: &mut [u8], offset: usize, n: usize) {
+    let size = u32::from_be_bytes([
+        data[offset],
+        data[offset + 1],
+        data[offset + 2],
+        data[offset + 3],
+    ]);
+    data[offset..offset + 4].copy_from_slice(&(size + n as u32).to_be_bytes());
+}
+
+/// Returns a text track of ID 2 whose samples of 0.5 seconds each are in one
+/// chunk at `chunk_offset`
+fn text_trak(data: &[u8], samples: &[&[u8]], chunk_offset: u32) -> Vec<u8> {
+    // 'tkhd' at 0x6208 with the track ID at 20
+    let mut tkhd = data[0x6208..0x6264].to_vec();
+    tkhd[20..24].copy_from_slice(&2_u32.to_be_bytes());
+
+    // 'hdlr' at 0x62b0 with the component subtype at 16
+    let mut hdlr = data[0x62b0..0x62dd].to_vec();
+    hdlr[16..20].copy_from_slice(b"text");
+
+    let mut text = (16_u32).to_be_bytes().to_vec();
+    text.extend_from_slice(b"text");
+    text.extend_from_slice(&[0, 0, 0, 0, 0, 0, 0, 1]);
+    let mut stsd = vec![0, 0, 0, 0, 0, 0, 0, 1];
+    stsd.extend(text);
+
+    let mut stsz = vec![0, samples.len() as u32];
+    stsz.extend(samples.iter().map(|s| s.len() as u32));
+
+    let mut stbl = atom(b"stsd", &stsd);
+    stbl.extend(full_atom(b"stts", &[1, samples.len() as u32, 7680]));
+    stbl.extend(full_atom(b"stsc", &[1, 1, samples.len() as u32, 1]));
+    stbl.extend(full_atom(b"stsz", &stsz));
+    stbl.extend(full_atom(b"stco", &[1, chunk_offset]));
+
+    let mut minf = full_atom(b"nmhd", &[]);
+    // 'dinf' at 0x6325
+    minf.extend_from_slice(&data[0x6325..0x6349]);
+    minf.extend(atom(b"stbl", &stbl));
+
+    // 'mdhd' at 0x6290 has the time scale 15360
+    let mut mdia = data[0x6290..0x62b0].to_vec();
+    mdia.extend(hdlr);
+    mdia.extend(atom(b"minf", &minf));
+
+    let mut trak = tkhd;
+    trak.extend(atom(b"mdia", &mdia));
+    atom(b"trak", &trak)
+}
+
+/// Returns the sample with a chapter track holding `samples`
+fn sample_with_chapter_track(samples: &[&[u8]]) -> Vec<u8> {
+    let original = fs::read("tests/samples/camouflage_vga.mov").unwrap();
+    let mut data = original.clone();
+
+    // 'tref' after 'tkhd' in the 'trak' at 0x6200
+    let tref = atom(b"tref", &atom(b"chap", &2_u32.to_be_bytes()));
+    data.splice(0x6264..0x6264, tref.iter().copied());
+    grow(&mut data, 0x6200, tref.len());
+    grow(&mut data, 0x618c, tref.len());
+
+    let trak_size = text_trak(&original, samples, 0).len();
+    let chunk_offset = (data.len() + trak_size) as u32;
+    let trak = text_trak(&original, samples, chunk_offset);
+    data.extend(&trak);
+    grow(&mut data, 0x618c, trak.len());
+
+    for sample in samples {
+        data.extend_from_slice(sample);
+    }
+    data
+}
+
+/// Returns the sample with `chpl` appended to 'moov/udta'
+fn sample_with_chpl(chpl: &[u8]) -> Vec<u8> {
+    let mut data = fs::read("tests/samples/camouflage_vga.mov").unwrap();
+
+    // 'moov' at 0x618c and its last child 'udta' at 0x65e1 end the file
+    data.extend_from_slice(chpl);
+    grow(&mut data, 0x65e1, chpl.len());
+    grow(&mut data, 0x618c, chpl.len());
+    data
+}
+
+fn chpl() -> Vec<u8> {
+    let mut body = vec![1, 0, 0, 0, 0, 0, 0, 0, 2];
+    body.extend_from_slice(&0_u64.to_be_bytes());
+    body.push(5);
+    body.extend_from_slice(b"Intro");
+    body.extend_from_slice(&612_000_000_u64.to_be_bytes());
+    body.push(6);
+    body.extend_from_slice(b"Part 2");
+    atom(b"chpl", &body)
+}
+
+#[test]
+fn test_chapter_track() {
+    // an 'encd' atom may follow the text
+    let intro = b"\x00\x05Intro\x00\x00\x00\x0cencd\x00\x00\x01\x00";
+    let part2 = b"\x00\x08\xfe\xff\x00P\x00\x20\x00\x32";
+    let data = sample_with_chapter_track(&[intro, part2]);
+
+    let qt = qtfile::parse(&mut Cursor::new(&data)).unwrap();
+    let moov = qt.moov().unwrap();
+    assert_eq!(
+        moov.trak_atom[0].referenced_track_ids(TrackReferenceType::Chapter),
+        vec![2]
+    );
+    assert_eq!(
+        chapters::chapter_track(&qt).map(|t| t.tkhd_atom.track_id),
+        Some(2)
+    );
+
+    let chapters = qt.chapters(&mut Cursor::new(&data)).unwrap();
+    assert_eq!(
+        chapters,
+        vec![
+            Chapter {
+                start_time: 0,
+                time_scale: 1000,
+                title: "Intro".to_string(),
+            },
+            Chapter {
+                start_time: 500,
+                time_scale: 1000,
+                title: "P 2".to_string(),
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_chpl() {
+    let data = sample_with_chpl(&chpl());
+    let qt = qtfile::parse(&mut Cursor::new(&data)).unwrap();
+    let chapters = qt.chapters(&mut Cursor::new(&data)).unwrap();
+
+    assert_eq!(chapters.len(), 2);
+    assert_eq!(chapters[1].seconds(), 61.2);
+    assert_eq!(
+        chapters.iter().map(|c| c.to_string()).collect::<Vec<_>>(),
+        vec!["00:00:00.000 Intro", "00:01:01.200 Part 2"]
+    );
+
+    let qt = qtfile::parse_file("tests/samples/camouflage_vga.mov".into()).unwrap();
+    let data = fs::read("tests/samples/camouflage_vga.mov").unwrap();
+    assert!(qt.chapters(&mut Cursor::new(&data)).unwrap().is_empty());
+}
+
+#[test]
+fn test_decode_text_sample() {
+    assert_eq!(chapters::decode_text_sample(b"\x00\x02ab"), "ab");
+    assert_eq!(chapters::decode_text_sample(b"\x00\x09ab"), "ab");
+    assert_eq!(chapters::decode_text_sample(b"\x00"), "");
+}
+
+#[test]
+fn test_malformed_chpl() {
+    // 3 entries are declared but 2 are held, and a text atom follows
+    let mut body = chpl()[8..].to_vec();
+    body[8] = 3;
+    let mut data = atom(b"chpl", &body);
+    data.extend(atom(b"\xa9nam", b"\x00\x05\x55\xc4Title"));
+    let data = sample_with_chpl(&data);
+
+    let qt = qtfile::parse(&mut Cursor::new(&data)).unwrap();
+    let udta = qt.moov().unwrap().udta_atom.as_ref().unwrap();
+    assert!(udta.chpl_atom.is_none());
+    assert_eq!(
+        udta.user_data_atom
+            .iter()
+            .map(|a| a.atom_head.atom_type)
+            .collect::<Vec<_>>(),
+        vec![0xa973_7772, chpl::ATOM_ID, 0xa96e_616d]
+    );
+    assert_eq!(udta.user_data_atom[1].user_data, UserData::Raw(body));
+    assert_eq!(udta.text(0xa96e_616d), Some("Title"));
+    assert!(qt.chapters(&mut Cursor::new(&data)).unwrap().is_empty());
+
+    // a title running past the atom
+    let mut body = chpl()[8..].to_vec();
+    body[31] = 7;
+    let data = sample_with_chpl(&atom(b"chpl", &body));
+    let qt = qtfile::parse(&mut Cursor::new(&data)).unwrap();
+    assert!(qt
+        .moov()
+        .unwrap()
+        .udta_atom
+        .as_ref()
+        .unwrap()
+        .chpl_atom
+        .is_none());
+}
+
+#[test]
+fn test_truncated_chapter_sample() {
+    let mut data = sample_with_chapter_track(&[b"\x00\x05Intro", b"\x00\x03End"]);
+    let qt = qtfile::parse(&mut Cursor::new(&data)).unwrap();
+
+    // the last sample ends the file
+    data.truncate(data.len() - 1);
+    assert!(matches!(
+        qt.chapters(&mut Cursor::new(&data)),
+        Err(AtomParseError::IoError(e)) if e.kind() == io::ErrorKind::UnexpectedEof
+    ));
+}
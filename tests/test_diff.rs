@@ -0,0 +1,150 @@
+use std::fs;
+use std::io::Cursor;
+use std::path::PathBuf;
+
+use atom_analyzer::atom::{self, AtomHead};
+use atom_analyzer::diff::DiffEntry;
+use atom_analyzer::metadata::write::MetadataWriter;
+use atom_analyzer::metadata::MetadataValue;
+use atom_analyzer::qtfile::{self, QtFile};
+
+#[test]
+fn test_camouflage_vga_mov_diff_itself() {
+    let file_name = PathBuf::from("tests/samples/camouflage_vga.mov");
+    let left = qtfile::parse_file(file_name.clone()).unwrap();
+    let right = qtfile::parse_file(file_name).unwrap();
+
+    assert!(left.diff(&right).is_empty());
+}
+
+#[test]
+fn test_camouflage_vga_mov_diff_modified() {
+    let mut data = fs::read("tests/samples/camouflage_vga.mov").unwrap();
+
+    // mvhd time scale: 1000 -> 600
+    data[0x61a8..0x61ac].copy_from_slice(&600_u32.to_be_bytes());
+    // size of the 4th sample: 0x33 -> 0x34
+    data[0x6561..0x6565].copy_from_slice(&0x34_u32.to_be_bytes());
+    // vmhd graphics mode: 0 -> 0x40 (dither copy)
+    data[0x62f1..0x62f3].copy_from_slice(&0x40_u16.to_be_bytes());
+    // flags of the 'url ' entry: self-contained -> none
+    data[0x6348] = 0;
+    // '©swr': "Lavf58.29.100" -> "Xavf58.29.100"
+    data[0x65f5] = b'X';
+    // an additional 'free' atom at the end
+    data.extend_from_slice(&[0, 0, 0, 8, b'f', b'r', b'e', b'e']);
+
+    let left = qtfile::parse_file(PathBuf::from("tests/samples/camouflage_vga.mov")).unwrap();
+    let right = qtfile::parse(&mut Cursor::new(data)).unwrap();
+
+    assert_eq!(
+        left.diff(&right).entries,
+        vec![
+            DiffEntry::FieldChanged {
+                path: "moov/mvhd".into(),
+                field: "time_scale",
+                left: "1000".into(),
+                right: "600".into(),
+            },
+            DiffEntry::FieldChanged {
+                path: "moov/trak/mdia/minf/vmhd".into(),
+                field: "graphics_mode",
+                left: "0".into(),
+                right: "64".into(),
+            },
+            DiffEntry::FieldChanged {
+                path: "moov/trak/mdia/minf/dinf/dref/url ".into(),
+                field: "atom_flags",
+                left: "[0, 0, 1]".into(),
+                right: "[0, 0, 0]".into(),
+            },
+            DiffEntry::TableChanged {
+                path: "moov/trak/mdia/minf/stbl/stsz".into(),
+                table: "sample_size_table",
+                left_len: 30,
+                right_len: 30,
+                first_difference: 3,
+            },
+            DiffEntry::FieldChanged {
+                path: "moov/udta/\u{a9}swr".into(),
+                field: "user_data",
+                left: r#"Text([InternationalText { language: 21956, text: "Lavf58.29.100" }])"#
+                    .into(),
+                right: r#"Text([InternationalText { language: 21956, text: "Xavf58.29.100" }])"#
+                    .into(),
+            },
+            DiffEntry::Added {
+                path: "free".into(),
+                atom_head: AtomHead {
+                    atom_offset: 0x6602,
+                    atom_size: 8,
+                    atom_type: atom::free::ATOM_ID,
+                },
+            },
+        ]
+    );
+}
+
+fn atom(atom_type: &[u8; 4], body: &[u8]) -> Vec<u8> {
+    let mut data = (8 + body.len() as u32).to_be_bytes().to_vec();
+    data.extend_from_slice(atom_type);
+    data.extend_from_slice(body);
+    data
+}
+
+/// Returns the sample with a 'tref' referring to `track_id` by 'tmcd' and the
+/// metadata item `key` set to `value`
+fn sample_with(track_id: u32, key: &str, value: i64) -> QtFile {
+    let mut data = fs::read("tests/samples/camouflage_vga.mov").unwrap();
+
+    // 'edts' at 0x6264 follows 'tkhd' in the 'trak' at 0x6200 in the 'moov'
+    // at 0x618c
+    let tref = atom(b"tref", &atom(b"tmcd", &track_id.to_be_bytes()));
+    data.splice(0x6264..0x6264, tref.iter().copied());
+    for offset in &[0x6200, 0x618c] {
+        let mut size = [0; 4];
+        size.copy_from_slice(&data[*offset..offset + 4]);
+        let size = u32::from_be_bytes(size) + tref.len() as u32;
+        data[*offset..offset + 4].copy_from_slice(&size.to_be_bytes());
+    }
+
+    let mut writer = MetadataWriter::new();
+    writer.set(key, MetadataValue::Integer(value));
+    let mut out = Vec::new();
+    writer.write(&mut Cursor::new(data), &mut out).unwrap();
+
+    qtfile::parse(&mut Cursor::new(out)).unwrap()
+}
+
+#[test]
+fn test_diff_references_and_metadata() {
+    let left = sample_with(2, "com.example.take", 1);
+    let right = sample_with(3, "com.example.scene", 2);
+
+    assert_eq!(
+        left.diff(&right).entries,
+        vec![
+            DiffEntry::TableChanged {
+                path: "moov/trak/tref/tmcd".into(),
+                table: "track_ids",
+                left_len: 1,
+                right_len: 1,
+                first_difference: 0,
+            },
+            DiffEntry::TableChanged {
+                path: "moov/meta/ilst/\0\0\0\u{1}/data".into(),
+                table: "value",
+                left_len: 4,
+                right_len: 4,
+                first_difference: 3,
+            },
+            DiffEntry::TableChanged {
+                path: "moov/meta/keys".into(),
+                table: "key_table",
+                left_len: 1,
+                right_len: 1,
+                first_difference: 0,
+            },
+        ]
+    );
+}
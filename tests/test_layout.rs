@@ -0,0 +1,76 @@
+use std::path::PathBuf;
+
+use atom_analyzer::analysis::layout::{self, Chunk, Layout, LayoutIssue};
+use atom_analyzer::qtfile;
+
+fn chunk(track_id: u32, chunk: u32, offset: u64, size: u64) -> Chunk {
+    Chunk {
+        track_id,
+        chunk,
+        offset,
+        size,
+    }
+}
+
+#[test]
+fn test_camouflage_vga_mov_layout() {
+    let file_name = PathBuf::from("tests/samples/camouflage_vga.mov");
+    let qt = qtfile::parse_file(file_name).unwrap();
+
+    let layout = layout::layout(&qt);
+    assert_eq!(layout.mdat, vec![(0x24, 0x6168)]);
+    assert_eq!(layout.chunks, vec![chunk(1, 1, 0x24, 0x6168)]);
+    assert!(layout.issues.is_empty());
+    assert_eq!(layout.ascii_map(8), "11111111");
+    assert_eq!(
+        layout.to_json(),
+        "{\"mdat\":[{\"offset\":36,\"size\":24936}],\
+         \"chunks\":[{\"track_id\":1,\"chunk\":1,\"offset\":36,\"size\":24936}],\
+         \"issues\":[]}"
+    );
+}
+
+#[test]
+fn test_layout_issues() {
+    let layout = Layout::new(
+        vec![(100, 100)],
+        vec![
+            chunk(2, 1, 130, 20),
+            chunk(1, 1, 110, 10),
+            chunk(1, 2, 145, 15),
+            chunk(2, 2, 180, 10),
+        ],
+    );
+
+    assert_eq!(layout.chunks[0], chunk(1, 1, 110, 10));
+    assert_eq!(
+        layout.issues,
+        vec![
+            LayoutIssue::Unreferenced {
+                offset: 100,
+                size: 10
+            },
+            LayoutIssue::Gap {
+                offset: 120,
+                size: 10
+            },
+            LayoutIssue::Overlap {
+                offset: 145,
+                size: 5,
+                chunks: (1, 2)
+            },
+            LayoutIssue::Gap {
+                offset: 160,
+                size: 20
+            },
+            LayoutIssue::Unreferenced {
+                offset: 190,
+                size: 10
+            },
+        ]
+    );
+    assert_eq!(layout.ascii_map(10), ".1.2!1..2.");
+    assert!(layout
+        .to_json()
+        .contains("{\"kind\":\"overlap\",\"offset\":145,\"size\":5,\"chunks\":[1,2]}"));
+}
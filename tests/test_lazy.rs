@@ -0,0 +1,164 @@
+use std::fs;
+use std::io::Cursor;
+use std::path::PathBuf;
+
+use atom_analyzer::atom::{self, ftyp, meta, stsz};
+use atom_analyzer::qtfile::{self, lazy};
+
+fn names(atoms: &[lazy::LazyAtom], depth: usize, out: &mut Vec<String>) {
+    for a in atoms {
+        out.push(format!(
+            "{}{}",
+            "  ".repeat(depth),
+            atom::type_to_string(a.atom_head.atom_type)
+        ));
+        names(&a.children, depth + 1, out);
+    }
+}
+
+#[test]
+fn test_camouflage_vga_mov_index() {
+    let file_name = PathBuf::from("tests/samples/camouflage_vga.mov");
+    let qt = lazy::open(file_name).unwrap();
+
+    let mut index = Vec::new();
+    names(qt.atoms(), 0, &mut index);
+
+    assert_eq!(
+        index,
+        vec![
+            "ftyp",
+            "wide",
+            "mdat",
+            "moov",
+            "  mvhd",
+            "  trak",
+            "    tkhd",
+            "    edts",
+            "      elst",
+            "    mdia",
+            "      mdhd",
+            "      hdlr",
+            "      minf",
+            "        vmhd",
+            "        hdlr",
+            "        dinf",
+            "          dref",
+            "            url ",
+            "        stbl",
+            "          stsd",
+            "          stts",
+            "          stss",
+            "          ctts",
+            "          stsc",
+            "          stsz",
+            "          stco",
+            "  udta",
+            "    \u{a9}swr",
+        ]
+    );
+
+    assert!(qt.atoms().iter().all(|a| !a.is_decoded()));
+}
+
+#[test]
+fn test_camouflage_vga_mov_decode_on_demand() {
+    let file_name = PathBuf::from("tests/samples/camouflage_vga.mov");
+    let qt = lazy::open(file_name.clone()).unwrap();
+    let full = qtfile::parse_file(file_name).unwrap();
+
+    assert_eq!(
+        qt.ftyp().unwrap().map(|f| &f.major_brand),
+        Some(&ftyp::Brand::QuickTimeMovieFile)
+    );
+
+    let path = "moov/trak/mdia/minf/stbl/stsz";
+    let stsz = qt.find::<stsz::StszAtom>(path).unwrap();
+
+    assert_eq!(stsz, full.find::<stsz::StszAtom>(path).unwrap());
+
+    let moov = &qt.select("moov").unwrap()[0];
+    assert!(!moov.is_decoded());
+    assert!(qt.select(path).unwrap()[0].is_decoded());
+
+    assert_eq!(qt.moov().unwrap(), full.moov());
+    assert!(moov.is_decoded());
+}
+
+fn atom(atom_type: &[u8; 4], body: &[u8]) -> Vec<u8> {
+    let mut data = (8 + body.len() as u32).to_be_bytes().to_vec();
+    data.extend_from_slice(atom_type);
+    data.extend_from_slice(body);
+    data
+}
+
+#[test]
+fn test_lazy_udta() {
+    let mut data = fs::read("tests/samples/camouflage_vga.mov").unwrap();
+
+    // a full box 'meta' holding a title, appended to 'udta' at 0x65e1 in the
+    // 'moov' at 0x618c, both of which end the file
+    let mut hdlr = vec![0; 8];
+    hdlr.extend_from_slice(b"mdir");
+    hdlr.extend_from_slice(&[0; 13]);
+    let title = atom(b"data", b"\0\0\0\x01\0\0\0\0Title");
+    let mut body = vec![0; 4];
+    body.extend(atom(b"hdlr", &hdlr));
+    body.extend(atom(b"ilst", &atom(b"\xa9nam", &title)));
+    let meta = atom(b"meta", &body);
+
+    data.extend_from_slice(&meta);
+    for offset in &[0x65e1, 0x618c] {
+        let mut size = [0; 4];
+        size.copy_from_slice(&data[*offset..offset + 4]);
+        let size = u32::from_be_bytes(size) + meta.len() as u32;
+        data[*offset..offset + 4].copy_from_slice(&size.to_be_bytes());
+    }
+
+    let qt = lazy::LazyQtFile::new(Cursor::new(data)).unwrap();
+
+    let udta = &qt.select("moov/udta").unwrap()[0];
+    assert_eq!(
+        udta.children
+            .iter()
+            .map(|a| atom::type_to_string(a.atom_head.atom_type))
+            .collect::<Vec<_>>(),
+        vec!["\u{a9}swr", "meta"]
+    );
+
+    let item = qt.select("moov/udta/meta/ilst/\u{a9}nam").unwrap();
+    assert_eq!(item.len(), 1);
+    assert_eq!(item[0].atom_head.atom_offset, 0x6602 + 8 + 4 + 33 + 8);
+    assert!(!item[0].is_decoded());
+
+    let meta = qt.find::<meta::MetaAtom>("moov/udta/meta").unwrap();
+    assert_eq!(meta[0].atom_version_and_flags, Some(0));
+    assert!(meta[0].ilst_atom.is_some());
+}
+
+#[test]
+fn test_lazy_tref_and_dref() {
+    let mut data = fs::read("tests/samples/camouflage_vga.mov").unwrap();
+
+    // a 'tref' referring to the timecode tracks 1 and 2, inserted after 'tkhd'
+    // in the 'trak' at 0x6200
+    let tref = atom(b"tref", &atom(b"tmcd", &[0, 0, 0, 1, 0, 0, 0, 2]));
+    data.splice(0x6264..0x6264, tref.iter().copied());
+    for offset in &[0x6200, 0x618c] {
+        let mut size = [0; 4];
+        size.copy_from_slice(&data[*offset..offset + 4]);
+        let size = u32::from_be_bytes(size) + tref.len() as u32;
+        data[*offset..offset + 4].copy_from_slice(&size.to_be_bytes());
+    }
+    assert!(qtfile::parse(&mut Cursor::new(&data)).is_ok());
+
+    let qt = lazy::LazyQtFile::new(Cursor::new(data)).unwrap();
+
+    let tmcd = qt.select("moov/trak/tref/tmcd").unwrap();
+    assert_eq!(tmcd.len(), 1);
+    assert!(tmcd[0].children.is_empty());
+
+    let url = qt.select("moov/trak/mdia/minf/dinf/dref/url ").unwrap();
+    assert_eq!(url.len(), 1);
+    assert_eq!(url[0].atom_head.atom_offset, 0x633d + tref.len() as u64);
+}
@@ -0,0 +1,192 @@
+use std::fs;
+use std::io::Cursor;
+
+use atom_analyzer::metadata::{Location, MetadataValue};
+use atom_analyzer::qtfile;
+
+fn atom(atom_type: &[u8; 4], body: &[u8]) -> Vec<u8> {
+    let mut data = (8 + body.len() as u32).to_be_bytes().to_vec();
+    data.extend_from_slice(atom_type);
+    data.extend_from_slice(body);
+    data
+}
+
+fn data(type_indicator: u32, value: &[u8]) -> Vec<u8> {
+    let mut body = type_indicator.to_be_bytes().to_vec();
+    body.extend_from_slice(&[0, 0, 0, 0]);
+    body.extend_from_slice(value);
+    atom(b"data", &body)
+}
+
+fn hdlr(handler: &[u8; 4]) -> Vec<u8> {
+    let mut body = vec![0; 8];
+    body.extend_from_slice(handler);
+    body.extend_from_slice(&[0; 12]);
+    atom(b"hdlr", &body)
+}
+
+/// Returns a QuickTime 'meta' with 'keys'
+fn mdta_meta() -> Vec<u8> {
+    let mut keys = vec![0, 0, 0, 0, 0, 0, 0, 3];
+    for key in &[
+        "com.apple.quicktime.make",
+        "com.apple.quicktime.location.ISO6709",
+        "com.apple.quicktime.camera.focal_length.35mm_equivalent",
+    ] {
+        keys.extend_from_slice(&(8 + key.len() as u32).to_be_bytes());
+        keys.extend_from_slice(b"mdta");
+        keys.extend_from_slice(key.as_bytes());
+    }
+
+    let mut ilst = atom(&1_u32.to_be_bytes(), &data(1, b"Apple"));
+    ilst.extend(atom(
+        &2_u32.to_be_bytes(),
+        &data(1, b"+35.6586+139.7454+040.000/"),
+    ));
+    ilst.extend(atom(
+        &3_u32.to_be_bytes(),
+        &data(23, &26.5_f32.to_be_bytes()),
+    ));
+    // no such key
+    ilst.extend(atom(&9_u32.to_be_bytes(), &data(22, &[1, 0])));
+
+    let mut body = hdlr(b"mdta");
+    body.extend(atom(b"keys", &keys));
+    body.extend(atom(b"ilst", &ilst));
+    atom(b"meta", &body)
+}
+
+/// Returns an iTunes-style 'meta' full box with FourCC keys
+fn mdir_meta() -> Vec<u8> {
+    let mut ilst = atom(b"\xa9nam", &data(1, b"Title"));
+    let mut covr = data(13, &[0xff, 0xd8, 0xff, 0xe0]);
+    covr.extend(data(14, b"\x89PNG"));
+    ilst.extend(atom(b"covr", &covr));
+    ilst.extend(atom(b"tmpo", &data(21, &[0, 120])));
+    ilst.extend(atom(b"rtng", &data(21, &[0xff])));
+    ilst.extend(atom(b"trkn", &data(0, &[0, 0, 0, 3, 0, 12, 0, 0])));
+    let mut freeform = atom(b"mean", b"\0\0\0\0com.apple.iTunes");
+    freeform.extend(atom(b"name", b"\0\0\0\0iTunSMPB"));
+    freeform.extend(data(1, b" 00000000"));
+    ilst.extend(atom(b"----", &freeform));
+
+    let mut body = vec![0, 0, 0, 0];
+    body.extend(hdlr(b"mdir"));
+    body.extend(atom(b"ilst", &ilst));
+    atom(b"meta", &body)
+}
+
+/// Returns the sample with `udta_meta` appended to 'moov/udta' and
+/// `moov_meta` to 'moov'
+fn sample_with_meta(udta_meta: &[u8], moov_meta: &[u8]) -> Vec<u8> {
+    let mut data = fs::read("tests/samples/camouflage_vga.mov").unwrap();
+
+    // 'moov' at 0x618c and its last child 'udta' at 0x65e1 end the file
+    let grow = |data: &mut Vec<u8>, offset: usize, n: usize| {
+        let size = u32::from_be_bytes([
+            data[offset],
+            data[offset + 1],
+            data[offset + 2],
+            data[offset + 3],
+        ]);
+        data[offset..offset + 4].copy_from_slice(&(size + n as u32).to_be_bytes());
+    };
+
+    data.extend_from_slice(udta_meta);
+    grow(&mut data, 0x65e1, udta_meta.len());
+    grow(&mut data, 0x618c, udta_meta.len());
+    data.extend_from_slice(moov_meta);
+    grow(&mut data, 0x618c, moov_meta.len());
+    data
+}
+
+#[test]
+fn test_metadata() {
+    let data = sample_with_meta(&mdir_meta(), &mdta_meta());
+    let qt = qtfile::parse(&mut Cursor::new(data)).unwrap();
+
+    let moov = qt.moov().unwrap();
+    let meta = moov.meta_atom.as_ref().unwrap();
+    assert_eq!(meta.atom_version_and_flags, None);
+    assert_eq!(meta.keys_atom.as_ref().unwrap().entry_count, 3);
+    let udta = moov.udta_atom.as_ref().unwrap();
+    assert_eq!(udta.user_data_atom.len(), 1);
+    assert_eq!(
+        udta.meta_atom.as_ref().unwrap().atom_version_and_flags,
+        Some(0)
+    );
+
+    let metadata = qt.metadata();
+    assert_eq!(metadata.entries.len(), 10);
+    assert_eq!(
+        metadata.get("com.apple.quicktime.make"),
+        Some(&MetadataValue::Utf8("Apple".to_string()))
+    );
+    assert_eq!(
+        metadata.get("com.apple.quicktime.location.ISO6709"),
+        Some(&MetadataValue::Location(Location {
+            latitude: 35.6586,
+            longitude: 139.7454,
+            altitude: Some(40.0),
+        }))
+    );
+    assert_eq!(
+        metadata.get("com.apple.quicktime.camera.focal_length.35mm_equivalent"),
+        Some(&MetadataValue::Float32(26.5))
+    );
+    assert_eq!(metadata.get("#9"), Some(&MetadataValue::Unsigned(256)));
+
+    assert_eq!(
+        metadata.get("\u{a9}nam"),
+        Some(&MetadataValue::Utf8("Title".to_string()))
+    );
+    assert_eq!(
+        metadata.entries["covr"],
+        vec![
+            MetadataValue::Jpeg(vec![0xff, 0xd8, 0xff, 0xe0]),
+            MetadataValue::Png(b"\x89PNG".to_vec()),
+        ]
+    );
+    assert_eq!(metadata.get("tmpo"), Some(&MetadataValue::Integer(120)));
+    assert_eq!(metadata.get("rtng"), Some(&MetadataValue::Integer(-1)));
+    assert_eq!(
+        metadata.get("trkn"),
+        Some(&MetadataValue::Other {
+            type_indicator: 0,
+            data: vec![0, 0, 0, 3, 0, 12, 0, 0],
+        })
+    );
+    assert_eq!(
+        metadata.get("com.apple.iTunes:iTunSMPB"),
+        Some(&MetadataValue::Utf8(" 00000000".to_string()))
+    );
+
+    assert_eq!(metadata.to_string().lines().next(), Some("#9: 256"));
+    assert!(metadata
+        .to_string()
+        .contains("com.apple.quicktime.location.ISO6709: +35.6586, +139.7454, 40 m\n"));
+    assert!(metadata
+        .to_string()
+        .contains("covr: JPEG image (4 bytes)\n"));
+}
+
+#[test]
+fn test_metadata_none() {
+    let qt = qtfile::parse_file("tests/samples/camouflage_vga.mov".into()).unwrap();
+
+    assert!(qt.metadata().is_empty());
+}
+
+#[test]
+fn test_location_from_iso6709() {
+    assert_eq!(
+        Location::from_iso6709("-33.8688+151.2093/"),
+        Some(Location {
+            latitude: -33.8688,
+            longitude: 151.2093,
+            altitude: None,
+        })
+    );
+    assert_eq!(Location::from_iso6709("35.6+139.7/"), None);
+    assert_eq!(Location::from_iso6709("+35.6/"), None);
+}
@@ -0,0 +1,339 @@
+use std::fs;
+use std::io::Cursor;
+
+use atom_analyzer::atom::{ilst, meta, moov, udta};
+use atom_analyzer::metadata::write::{is_sensitive, MetadataWriteError, MetadataWriter};
+use atom_analyzer::metadata::{Location, MetadataValue};
+use atom_analyzer::qtfile::{self, QtFile};
+
+const LOCATION_KEY: &str = "com.apple.quicktime.location.ISO6709";
+
+fn write(writer: &MetadataWriter, data: &[u8]) -> Vec<u8> {
+    let mut out = Vec::new();
+    writer.write(&mut Cursor::new(data), &mut out).unwrap();
+    out
+}
+
+fn parse(data: &[u8]) -> QtFile {
+    qtfile::parse(&mut Cursor::new(data)).unwrap()
+}
+
+/// Returns the sample rearranged as ftyp, moov, wide and mdat with the chunk
+/// offset at `chunk_offset`
+fn faststart_sample(chunk_offset: u32) -> Vec<u8> {
+    let data = fs::read("tests/samples/camouflage_vga.mov").unwrap();
+
+    // 'stco' holds its only entry at 0x65dd
+    let mut moov = data[0x618c..].to_vec();
+    moov[0x65dd - 0x618c..0x65dd - 0x618c + 4].copy_from_slice(&chunk_offset.to_be_bytes());
+
+    let mut faststart = data[..0x14].to_vec();
+    faststart.extend_from_slice(&moov);
+    faststart.extend_from_slice(&data[0x14..0x618c]);
+    faststart
+}
+
+#[test]
+fn test_set_and_strip() {
+    let data = fs::read("tests/samples/camouflage_vga.mov").unwrap();
+    let location = Location {
+        latitude: 35.6586,
+        longitude: 139.7454,
+        altitude: Some(40.0),
+    };
+
+    let mut writer = MetadataWriter::new();
+    writer
+        .set("\u{a9}nam", MetadataValue::Utf8("Title".to_string()))
+        .set("\u{a9}xyz", MetadataValue::Location(location.clone()))
+        .set("\u{a9}swr", MetadataValue::Utf8("editor".to_string()))
+        .set(
+            "com.apple.quicktime.title",
+            MetadataValue::Utf8("Title".to_string()),
+        )
+        .set(LOCATION_KEY, MetadataValue::Location(location.clone()))
+        .set("com.example.serial", MetadataValue::Unsigned(1234))
+        .set("com.example.take", MetadataValue::Integer(-3));
+    let edited = write(&writer, &data);
+
+    let qt = parse(&edited);
+    let moov = qt.moov().unwrap();
+    assert_eq!(moov.user_text(udta::TITLE), Some("Title"));
+    assert_eq!(
+        moov.user_text(udta::LOCATION),
+        Some("+35.6586+139.7454+040.000/")
+    );
+    assert_eq!(moov.user_text(udta::SOFTWARE), Some("editor"));
+    assert_eq!(moov.udta_atom.as_ref().unwrap().user_data_atom.len(), 3);
+
+    let metadata = qt.metadata();
+    assert_eq!(metadata.entries.len(), 4);
+    assert_eq!(
+        metadata.get(LOCATION_KEY),
+        Some(&MetadataValue::Location(location))
+    );
+    assert_eq!(
+        metadata.get("com.example.serial"),
+        Some(&MetadataValue::Unsigned(1234))
+    );
+    assert_eq!(
+        metadata.get("com.example.take"),
+        Some(&MetadataValue::Integer(-3))
+    );
+
+    // 'moov' follows 'mdat'
+    assert_eq!(qt.moov().unwrap().trak_atom[0].samples()[0].offset, 0x24);
+    assert_eq!(edited[..0x618c], data[..0x618c]);
+
+    let stripped = write(MetadataWriter::new().remove_sensitive(), &edited);
+    let qt = parse(&stripped);
+    let moov = qt.moov().unwrap();
+    assert_eq!(moov.user_text(udta::LOCATION), None);
+    assert_eq!(moov.user_text(udta::TITLE), Some("Title"));
+
+    let metadata = qt.metadata();
+    assert_eq!(
+        metadata.entries.keys().collect::<Vec<_>>(),
+        vec!["com.apple.quicktime.title", "com.example.take"]
+    );
+    assert_eq!(
+        metadata.get("com.example.take"),
+        Some(&MetadataValue::Integer(-3))
+    );
+
+    let stripped = write(
+        MetadataWriter::new()
+            .remove("com.example.take")
+            .remove("\u{a9}nam"),
+        &stripped,
+    );
+    let qt = parse(&stripped);
+    assert_eq!(qt.moov().unwrap().user_text(udta::TITLE), None);
+    assert_eq!(qt.moov().unwrap().user_text(udta::SOFTWARE), Some("editor"));
+    assert_eq!(qt.metadata().entries.len(), 1);
+
+    let stripped = write(MetadataWriter::new().remove_all(), &stripped);
+    let qt = parse(&stripped);
+    assert!(qt.metadata().is_empty());
+    assert!(qt
+        .moov()
+        .unwrap()
+        .udta_atom
+        .as_ref()
+        .unwrap()
+        .user_data_atom
+        .is_empty());
+}
+
+#[test]
+fn test_chunk_offsets_after_moov() {
+    let data = fs::read("tests/samples/camouflage_vga.mov").unwrap();
+    let moov_size = 0x6602 - 0x618c;
+    let faststart = faststart_sample(0x24 + moov_size);
+    let sample = parse(&faststart).moov().unwrap().trak_atom[0].samples()[0].clone();
+    assert_eq!(
+        faststart[sample.offset as usize..][..sample.size as usize],
+        data[0x24..][..sample.size as usize]
+    );
+
+    let mut writer = MetadataWriter::new();
+    writer.set(
+        "com.apple.quicktime.description",
+        MetadataValue::Utf8("a longer description".to_string()),
+    );
+    let grown = write(&writer, &faststart);
+    let qt = parse(&grown);
+    let samples = qt.moov().unwrap().trak_atom[0].samples();
+    let delta = grown.len() - faststart.len();
+
+    assert!(delta > 0);
+    assert_eq!(samples[0].offset, sample.offset + delta as u64);
+    assert_eq!(
+        grown[samples[0].offset as usize..][..sample.size as usize],
+        data[0x24..][..sample.size as usize]
+    );
+
+    // shrinking moves the chunks back
+    let shrunk = write(MetadataWriter::new().remove_all(), &grown);
+    let qt = parse(&shrunk);
+    let udta_size = qt
+        .moov()
+        .unwrap()
+        .udta_atom
+        .as_ref()
+        .unwrap()
+        .atom_head
+        .atom_size;
+    assert_eq!(udta_size, 8);
+    let offset = qt.moov().unwrap().trak_atom[0].samples()[0].offset;
+    assert!(shrunk.len() < grown.len());
+    assert_eq!(
+        offset,
+        samples[0].offset - (grown.len() - shrunk.len()) as u64
+    );
+    assert_eq!(
+        shrunk[offset as usize..][..sample.size as usize],
+        data[0x24..][..sample.size as usize]
+    );
+}
+
+#[test]
+fn test_chunk_offsets_to_co64() {
+    let faststart = faststart_sample(0xffff_fff0);
+
+    let mut writer = MetadataWriter::new();
+    writer.set("\u{a9}nam", MetadataValue::Utf8("Title".to_string()));
+    let edited = write(&writer, &faststart);
+
+    let qt = parse(&edited);
+    let stbl = qt.moov().unwrap().trak_atom[0].stbl_atom().unwrap();
+    let delta = (edited.len() - faststart.len()) as u64;
+
+    assert!(stbl.stco_atom.is_none());
+    assert_eq!(
+        stbl.co64_atom.as_ref().unwrap().chunk_offset_table,
+        vec![0xffff_fff0 + delta]
+    );
+    // '©nam' and 4 more bytes for the 64-bit entry
+    assert_eq!(delta, 8 + 4 + 5 + 4);
+}
+
+#[test]
+fn test_invalid_keys() {
+    let data = fs::read("tests/samples/camouflage_vga.mov").unwrap();
+    let mut out = Vec::new();
+
+    let mut writer = MetadataWriter::new();
+    writer.set("covr", MetadataValue::Jpeg(vec![0xff, 0xd8]));
+    match writer.write(&mut Cursor::new(&data), &mut out) {
+        Err(MetadataWriteError::InvalidKey(key)) => assert_eq!(key, "covr"),
+        r => panic!("{:?}", r),
+    }
+
+    let mut writer = MetadataWriter::new();
+    writer.set("\u{a9}nam", MetadataValue::Integer(1));
+    assert!(matches!(
+        writer.write(&mut Cursor::new(&data), &mut out),
+        Err(MetadataWriteError::NotText(_))
+    ));
+
+    match MetadataWriter::new().write(&mut Cursor::new(&data[..0x618c]), &mut out) {
+        Err(MetadataWriteError::MoovNotFound) => {}
+        r => panic!("{:?}", r),
+    }
+
+    assert!(is_sensitive(LOCATION_KEY));
+    assert!(is_sensitive("\u{a9}xyz"));
+    assert!(is_sensitive("com.sony.bprl.mxf.nrt.SerialNumber"));
+    assert!(!is_sensitive("com.apple.quicktime.title"));
+}
+
+#[test]
+fn test_malformed_containers() {
+    let data = fs::read("tests/samples/camouflage_vga.mov").unwrap();
+    let mut out = Vec::new();
+    let mut writer = MetadataWriter::new();
+    writer.set("\u{a9}nam", MetadataValue::Utf8("title".into()));
+
+    // 'mvhd' at 0x6194 running past the end of 'moov'
+    let mut broken = data.clone();
+    broken[0x6194..0x6198].copy_from_slice(&0xffffu32.to_be_bytes());
+    match writer.write(&mut Cursor::new(&broken), &mut out) {
+        Err(MetadataWriteError::NotContainer(atom_type)) => assert_eq!(atom_type, moov::ATOM_ID),
+        r => panic!("{:?}", r),
+    }
+
+    // a 'meta' appended to 'moov' at 0x618c whose only child overruns it
+    let mut broken = data;
+    broken.extend_from_slice(&[0, 0, 0, 16, b'm', b'e', b't', b'a']);
+    broken.extend_from_slice(&[0, 0, 1, 0, b'h', b'd', b'l', b'r']);
+    let moov_size = u32::from_be_bytes([
+        broken[0x618c],
+        broken[0x618d],
+        broken[0x618e],
+        broken[0x618f],
+    ]);
+    broken[0x618c..0x6190].copy_from_slice(&(moov_size + 16).to_be_bytes());
+
+    let mut writer = MetadataWriter::new();
+    writer.set(
+        LOCATION_KEY,
+        MetadataValue::Utf8("+35.6586+139.7454/".into()),
+    );
+    match writer.write(&mut Cursor::new(&broken), &mut out) {
+        Err(MetadataWriteError::NotContainer(atom_type)) => assert_eq!(atom_type, meta::ATOM_ID),
+        r => panic!("{:?}", r),
+    }
+
+    // the '©' user data in 'udta' is still written, but nothing is removed
+    let mut writer = MetadataWriter::new();
+    writer.set("\u{a9}nam", MetadataValue::Utf8("title".into()));
+    assert!(writer.write(&mut Cursor::new(&broken), &mut out).is_ok());
+    match MetadataWriter::new()
+        .remove_sensitive()
+        .write(&mut Cursor::new(&broken), &mut out)
+    {
+        Err(MetadataWriteError::NotContainer(atom_type)) => assert_eq!(atom_type, meta::ATOM_ID),
+        r => panic!("{:?}", r),
+    }
+
+    // a 'meta' appended to 'moov' whose 'ilst' overruns it, which leaves
+    // 'keys' alone
+    let data = fs::read("tests/samples/camouflage_vga.mov").unwrap();
+    let mut broken = data.clone();
+    broken.extend_from_slice(&[0, 0, 0, 32, b'm', b'e', b't', b'a']);
+    broken.extend_from_slice(&[0, 0, 0, 8, b'h', b'd', b'l', b'r']);
+    broken.extend_from_slice(&[0, 0, 0, 16, b'i', b'l', b's', b't']);
+    broken.extend_from_slice(&[0, 0, 1, 0, b'd', b'a', b't', b'a']);
+    let moov_size = u32::from_be_bytes([
+        broken[0x618c],
+        broken[0x618d],
+        broken[0x618e],
+        broken[0x618f],
+    ]);
+    broken[0x618c..0x6190].copy_from_slice(&(moov_size + 32).to_be_bytes());
+
+    let mut writer = MetadataWriter::new();
+    writer.set(
+        LOCATION_KEY,
+        MetadataValue::Utf8("+35.6586+139.7454/".into()),
+    );
+    match writer.write(&mut Cursor::new(&broken), &mut out) {
+        Err(MetadataWriteError::NotContainer(atom_type)) => assert_eq!(atom_type, ilst::ATOM_ID),
+        r => panic!("{:?}", r),
+    }
+
+    // '©swr' at 0x65e9 running past the end of 'udta'
+    let mut broken = data.clone();
+    broken[0x65e9..0x65ed].copy_from_slice(&0xffffu32.to_be_bytes());
+    let mut writer = MetadataWriter::new();
+    writer.set("\u{a9}nam", MetadataValue::Utf8("title".into()));
+    match writer.write(&mut Cursor::new(&broken), &mut out) {
+        Err(MetadataWriteError::NotContainer(atom_type)) => assert_eq!(atom_type, udta::ATOM_ID),
+        r => panic!("{:?}", r),
+    }
+
+    // stripping a file whose 'moov' is broken after '©xyz' was set
+    let mut writer = MetadataWriter::new();
+    writer.set(
+        "\u{a9}xyz",
+        MetadataValue::Utf8("+35.6586+139.7454/".into()),
+    );
+    let mut broken = write(&writer, &data);
+    broken[0x6194..0x6198].copy_from_slice(&0xffffu32.to_be_bytes());
+    match MetadataWriter::new()
+        .remove_sensitive()
+        .write(&mut Cursor::new(&broken), &mut out)
+    {
+        Err(MetadataWriteError::NotContainer(atom_type)) => assert_eq!(atom_type, moov::ATOM_ID),
+        r => panic!("{:?}", r),
+    }
+
+    // '©' user data is stored with a 16-bit size
+    let mut writer = MetadataWriter::new();
+    writer.set("\u{a9}nam", MetadataValue::Utf8("a".repeat(70000)));
+    match writer.write(&mut Cursor::new(&data), &mut out) {
+        Err(MetadataWriteError::TextTooLong(key)) => assert_eq!(key, "\u{a9}nam"),
+        r => panic!("{:?}", r),
+    }
+}
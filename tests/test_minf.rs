@@ -0,0 +1,201 @@
+use std::fs;
+use std::io::Cursor;
+
+use atom_analyzer::atom::{self, minf::MediaInfo, minf::MinfAtom, Atom, AtomParseError};
+use atom_analyzer::qtfile::{self, QtFileError};
+
+/// Returns the children of the sample's 'minf' as 'vmhd', 'hdlr', 'dinf' and 'stbl'
+fn minf_children() -> Vec<Vec<u8>> {
+    let data = fs::read("tests/samples/camouflage_vga.mov").unwrap();
+    // 'minf' at 0x62dd with its first child at 0x62e5
+    let mut children = Vec::new();
+    let mut offset = 0x62e5;
+    while offset < 0x62dd + 0x304 {
+        let size = u32::from_be_bytes([
+            data[offset],
+            data[offset + 1],
+            data[offset + 2],
+            data[offset + 3],
+        ]) as usize;
+        children.push(data[offset..offset + size].to_vec());
+        offset += size;
+    }
+    children
+}
+
+/// Returns a 'minf' made of `children`
+fn minf_of(children: &[&[u8]]) -> Vec<u8> {
+    let size: usize = children.iter().map(|c| c.len()).sum();
+    let mut minf = Vec::new();
+    minf.extend_from_slice(&((8 + size) as u32).to_be_bytes());
+    minf.extend_from_slice(b"minf");
+    for child in children {
+        minf.extend_from_slice(child);
+    }
+    minf
+}
+
+/// Returns the 'minf' of the sample with `header` in place of its 'vmhd'
+fn minf_with_header(header: &[u8]) -> Vec<u8> {
+    let c = minf_children();
+    minf_of(&[header, &c[1], &c[2], &c[3]])
+}
+
+fn parse_minf(data: Vec<u8>) -> MinfAtom {
+    let minf = atom::parse(&mut Cursor::new(data)).unwrap();
+    *minf.downcast::<MinfAtom>().unwrap()
+}
+
+#[test]
+fn test_minf_media_headers() {
+    let smhd = [
+        0, 0, 0, 16, b's', b'm', b'h', b'd', 0, 0, 0, 0, 0xff, 0, 0, 0,
+    ];
+    let mut gmhd = vec![0, 0, 0, 32, b'g', b'm', b'h', b'd'];
+    gmhd.extend_from_slice(&[0, 0, 0, 24, b'g', b'm', b'i', b'n', 0, 0, 0, 0]);
+    gmhd.extend_from_slice(&[0, 0x40, 0x80, 0, 0x80, 0, 0x80, 0, 0, 0, 0, 0]);
+    let nmhd = [0, 0, 0, 12, b'n', b'm', b'h', b'd', 0, 0, 0, 0];
+    let mut hmhd = vec![0, 0, 0, 28, b'h', b'm', b'h', b'd', 0, 0, 0, 0];
+    hmhd.extend_from_slice(&[
+        0x05, 0xdc, 0x02, 0x00, 0, 1, 0, 0, 0, 0, 0x80, 0, 0, 0, 0, 0,
+    ]);
+    let sthd = [0, 0, 0, 12, b's', b't', b'h', b'd', 0, 0, 0, 0];
+
+    let minf = parse_minf(minf_with_header(&smhd));
+    match &minf.media_info {
+        MediaInfo::SoundMediaInfo { smhd_atom, .. } => {
+            assert_eq!(smhd_atom.balance, -1);
+        }
+        m => panic!("{:?}", m),
+    }
+    assert!(minf.media_info.hdlr_atom().is_some());
+    assert_eq!(minf.media_info.dinf_atom().fourcc(), "dinf");
+    assert_eq!(
+        minf.stbl_atom()
+            .stsz_atom
+            .as_ref()
+            .unwrap()
+            .number_of_entries,
+        30
+    );
+    assert_eq!(
+        minf.children()
+            .iter()
+            .map(|a| a.fourcc())
+            .collect::<Vec<_>>(),
+        vec!["smhd", "hdlr", "dinf", "stbl"]
+    );
+
+    let minf = parse_minf(minf_with_header(&gmhd));
+    match &minf.media_info {
+        MediaInfo::BaseMediaInfo { gmhd_atom, .. } => {
+            let gmin = gmhd_atom.gmin_atom.as_ref().unwrap();
+            assert_eq!(gmin.graphics_mode, 0x40);
+            assert_eq!(gmin.opcolor, [0x8000, 0x8000, 0x8000]);
+        }
+        m => panic!("{:?}", m),
+    }
+    assert_eq!(minf.stbl_atom().fourcc(), "stbl");
+
+    let minf = parse_minf(minf_with_header(&nmhd));
+    assert!(matches!(minf.media_info, MediaInfo::NullMediaInfo { .. }));
+    assert_eq!(minf.stbl_atom().fourcc(), "stbl");
+
+    let minf = parse_minf(minf_with_header(&hmhd));
+    match &minf.media_info {
+        MediaInfo::HintMediaInfo { hmhd_atom, .. } => {
+            assert_eq!(hmhd_atom.max_pdu_size, 1500);
+            assert_eq!(hmhd_atom.avg_pdu_size, 512);
+            assert_eq!(hmhd_atom.max_bitrate, 0x10000);
+            assert_eq!(hmhd_atom.avg_bitrate, 0x8000);
+        }
+        m => panic!("{:?}", m),
+    }
+    assert_eq!(minf.stbl_atom().fourcc(), "stbl");
+
+    let minf = parse_minf(minf_with_header(&sthd));
+    assert!(matches!(
+        minf.media_info,
+        MediaInfo::SubtitleMediaInfo { .. }
+    ));
+    assert_eq!(minf.stbl_atom().fourcc(), "stbl");
+
+    let unknown = [0, 0, 0, 8, b'x', b'm', b'h', b'd'];
+    let minf = parse_minf(minf_with_header(&unknown));
+    assert!(matches!(minf.media_info, MediaInfo::Unknown { .. }));
+    assert!(minf.media_info.header().is_none());
+    assert_eq!(minf.unknown_atoms.len(), 1);
+    assert_eq!(minf.unknown_atoms[0].fourcc(), "xmhd");
+    assert_eq!(minf.stbl_atom().fourcc(), "stbl");
+}
+
+#[test]
+fn test_minf_order() {
+    let c = minf_children();
+
+    // header last, and a recognized header wins over an unknown child, which
+    // is kept; the children stay in file order
+    let unknown = [0, 0, 0, 8, b'x', b'm', b'h', b'd'];
+    let minf = parse_minf(minf_of(&[&c[3], &unknown, &c[2], &c[1], &c[0]]));
+    match &minf.media_info {
+        MediaInfo::VideoMediaInfo {
+            vmhd_atom,
+            hdlr_atom,
+            ..
+        } => {
+            assert_eq!(vmhd_atom.fourcc(), "vmhd");
+            assert!(hdlr_atom.is_some());
+        }
+        m => panic!("{:?}", m),
+    }
+    assert_eq!(
+        minf.children()
+            .iter()
+            .map(|a| a.fourcc())
+            .collect::<Vec<_>>(),
+        vec!["stbl", "xmhd", "dinf", "hdlr", "vmhd"]
+    );
+
+    // 'hdlr' is optional
+    let minf = parse_minf(minf_of(&[&c[2], &c[0], &c[3]]));
+    assert!(matches!(minf.media_info, MediaInfo::VideoMediaInfo { .. }));
+    assert!(minf.media_info.hdlr_atom().is_none());
+}
+
+#[test]
+fn test_minf_missing_atoms() {
+    let c = minf_children();
+    let parse = |data: Vec<u8>| atom::parse(&mut Cursor::new(data)).map(|_| ());
+
+    match parse(minf_of(&[&c[1], &c[2], &c[3]])) {
+        Err(AtomParseError::MediaHeaderNotFound(0)) => {}
+        r => panic!("{:?}", r),
+    }
+    match parse(minf_of(&[&c[0], &c[1], &c[3]])) {
+        Err(AtomParseError::RequiredAtomNotFound(id)) => assert_eq!(id, atom::dinf::ATOM_ID),
+        r => panic!("{:?}", r),
+    }
+    match parse(minf_of(&[&c[0], &c[1], &c[2]])) {
+        Err(AtomParseError::RequiredAtomNotFound(id)) => assert_eq!(id, atom::stbl::ATOM_ID),
+        r => panic!("{:?}", r),
+    }
+}
+
+#[test]
+fn test_minf_missing_atoms_in_file() {
+    let data = fs::read("tests/samples/camouflage_vga.mov").unwrap();
+
+    // 'stbl' at 0x6349 and 'dinf' at 0x6325 renamed, which fails the whole
+    // parse rather than leaving 'mdia' without 'minf'
+    for (offset, atom_type) in &[(0x6349, atom::stbl::ATOM_ID), (0x6325, atom::dinf::ATOM_ID)] {
+        let mut data = data.clone();
+        data[offset + 4..offset + 8].copy_from_slice(b"xxxx");
+
+        match qtfile::parse(&mut Cursor::new(data)) {
+            Err(QtFileError::AtomParseError(AtomParseError::RequiredAtomNotFound(id))) => {
+                assert_eq!(id, *atom_type)
+            }
+            r => panic!("{:?}", r),
+        }
+    }
+}
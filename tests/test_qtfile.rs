@@ -1,11 +1,13 @@
+use std::fs;
+use std::io::Cursor;
 use std::path::PathBuf;
 
 use fixed::{
     types::extra::{U16, U8},
-    FixedU16, FixedU32,
+    FixedI32, FixedU16, FixedU32,
 };
 
-use atom_analyzer::atom::{self, ftyp, mdat, moov, wide};
+use atom_analyzer::atom::{self, ftyp, mdat, wide};
 use atom_analyzer::element::{qtfile_datetime, qtfile_matrix};
 use atom_analyzer::qtfile;
 
@@ -17,13 +19,9 @@ fn test_camouflage_vga_mov_manual() {
     let file_name = PathBuf::from("tests/samples/camouflage_vga.mov");
     let mut qt = qtfile::parse_file(file_name).unwrap().into_iter();
 
-    let ftyp_atom = qt.next().unwrap().clone();
-    let ftyp = ftyp_atom.borrow();
-
-    assert!(ftyp.is::<ftyp::FtypAtom>());
     assert_eq!(
-        ftyp.downcast_ref::<ftyp::FtypAtom>(),
-        Some(&ftyp::FtypAtom {
+        qt.next(),
+        Some(qtfile::TopLevelAtom::Ftyp(ftyp::FtypAtom {
             atom_head: atom::AtomHead {
                 atom_offset: 0,
                 atom_size: 20,
@@ -32,45 +30,36 @@ fn test_camouflage_vga_mov_manual() {
             major_brand: ftyp::Brand::QuickTimeMovieFile,
             minor_version: 0x00000200,
             compatible_brands: vec![ftyp::Brand::QuickTimeMovieFile]
-        })
+        }))
     );
 
-    let wide_atom = qt.next().unwrap().clone();
-    let wide = wide_atom.borrow();
-
-    assert!(wide.is::<wide::WideAtom>());
     assert_eq!(
-        wide.downcast_ref::<wide::WideAtom>(),
-        Some(&wide::WideAtom {
+        qt.next(),
+        Some(qtfile::TopLevelAtom::Wide(wide::WideAtom {
             atom_head: atom::AtomHead {
                 atom_offset: 20,
                 atom_size: 8,
                 atom_type: atom::wide::ATOM_ID,
             },
-        }),
+        })),
     );
 
-    let mdat_atom = qt.next().unwrap().clone();
-    let mdat = mdat_atom.borrow();
-
-    assert!(mdat.is::<mdat::MdatAtom>());
     assert_eq!(
-        mdat.downcast_ref::<mdat::MdatAtom>(),
-        Some(&mdat::MdatAtom {
+        qt.next(),
+        Some(qtfile::TopLevelAtom::Mdat(mdat::MdatAtom {
             atom_head: atom::AtomHead {
                 atom_offset: 28,
                 atom_size: 0x6170,
                 atom_type: atom::mdat::ATOM_ID,
             },
-        }),
+            header_size: 8,
+        })),
     );
 
-    let moov_atom = qt.next().unwrap().clone();
-    let moov = moov_atom.borrow();
-
-    assert!(moov.is::<moov::MoovAtom>());
-
-    let moov = moov.downcast_ref::<moov::MoovAtom>().unwrap();
+    let moov = match qt.next() {
+        Some(qtfile::TopLevelAtom::Moov(moov)) => moov,
+        _ => panic!(),
+    };
 
     assert_eq!(
         moov.atom_head,
@@ -175,7 +164,7 @@ fn test_camouflage_vga_mov_manual() {
                 edit_list_table: vec![atom::elst::EditListTableEntry {
                     track_duration: 1000,
                     media_time: 1024,
-                    media_rate: FixedU32::<U16>::from_num(1),
+                    media_rate: FixedI32::<U16>::from_num(1),
                 }],
             })),
         })),
@@ -201,7 +190,15 @@ fn test_camouflage_vga_mov_manual() {
                 atom_offset: 0x6290,
                 atom_size: 0x20,
                 atom_type: atom::mdhd::ATOM_ID,
-            }
+            },
+            atom_version: 0,
+            atom_flags: [0, 0, 0],
+            creation_time: qtfile_datetime::QtFileDateTime::new(0),
+            modification_time: qtfile_datetime::QtFileDateTime::new(0),
+            time_scale: 15360,
+            duration: 15360,
+            language: 0x7fff,
+            quality: 0,
         }),
     );
 
@@ -255,12 +252,17 @@ fn test_camouflage_vga_mov_manual() {
                 atom_size: 0x14,
                 atom_type: atom::vmhd::ATOM_ID,
             },
+            atom_version: 0,
+            atom_flags: [0, 0, 1],
+            graphics_mode: 0,
+            opcolor: [0, 0, 0],
         }),
     );
+    assert!(vmhd_atom.is_no_lean_ahead());
 
     assert_eq!(
         hdlr_atom,
-        &Box::new(atom::hdlr::HdlrAtom {
+        &Some(Box::new(atom::hdlr::HdlrAtom {
             atom_head: atom::AtomHead {
                 atom_offset: 0x62f9,
                 atom_size: 0x2c,
@@ -274,12 +276,12 @@ fn test_camouflage_vga_mov_manual() {
             component_flags: 0,
             component_flags_mask: 0,
             component_name: "\u{b}DataHandler".into()
-        }),
+        })),
     );
 
     assert_eq!(
         dinf_atom,
-        &Some(Box::new(atom::dinf::DinfAtom {
+        &Box::new(atom::dinf::DinfAtom {
             atom_head: atom::AtomHead {
                 atom_offset: 0x6325,
                 atom_size: 0x24,
@@ -294,21 +296,25 @@ fn test_camouflage_vga_mov_manual() {
                 atom_version: 0,
                 atom_flags: [0, 0, 0],
                 number_of_entries: 1,
-                data_references: vec![atom::dref::DataReferenceType::Url {
+                data_references: vec![atom::dref::DataReferenceAtom {
                     atom_head: atom::AtomHead {
                         atom_offset: 0x633d,
                         atom_size: 0x0c,
-                        atom_type: 0x7572_6c20, // @todo
+                        atom_type: 0x7572_6c20, // 'url '
                     },
-                    url: "\u{0}\u{0}\u{0}\u{1}".into()
+                    atom_version: 0,
+                    atom_flags: [0, 0, 1],
+                    data_reference: atom::dref::DataReferenceType::Url { url: "".into() },
                 }],
             }),
-        }),),
+        }),
     );
 
+    assert!(dinf_atom.dref_atom.data_references[0].is_self_contained());
+
     assert_eq!(
         stbl_atom,
-        &Some(Box::new(atom::stbl::StblAtom {
+        &Box::new(atom::stbl::StblAtom {
             atom_head: atom::AtomHead {
                 atom_offset: 0x6349,
                 atom_size: 0x298,
@@ -328,7 +334,9 @@ fn test_camouflage_vga_mov_manual() {
                     data_format: 0x6176_6331,
                     reserved: [0, 0, 0, 0, 0, 0],
                     data_reference_index: 1,
-                    data: vec![]
+                    data: fs::read("tests/samples/camouflage_vga.mov").unwrap()
+                        [0x6361 + 16..0x6361 + 0x98]
+                        .to_vec()
                 }]
             })),
             stts_atom: Some(Box::new(atom::stts::SttsAtom {
@@ -435,7 +443,41 @@ fn test_camouflage_vga_mov_manual() {
                 atom_flags: [0, 0, 0],
                 number_of_entries: 1,
                 chunk_offset_table: vec![0x24],
-            }))
-        })),
+            })),
+            co64_atom: None,
+        }),
     );
 }
+
+#[test]
+fn test_qtfile_is_send_and_sync() {
+    fn assert_send_sync<T: Send + Sync>() {}
+
+    assert_send_sync::<qtfile::QtFile>();
+}
+
+#[test]
+fn test_camouflage_vga_mov_accessors() {
+    let file_name = PathBuf::from("tests/samples/camouflage_vga.mov");
+    let qt = qtfile::parse_file(file_name).unwrap();
+
+    assert_eq!(
+        qt.ftyp().map(|f| &f.major_brand),
+        Some(&ftyp::Brand::QuickTimeMovieFile)
+    );
+    assert_eq!(qt.moov().map(|m| m.trak_atom.len()), Some(1));
+    assert_eq!(qt.mdat().count(), 1);
+}
+
+#[test]
+fn test_mdat_extended_size() {
+    let mut data = vec![0, 0, 0, 1, b'm', b'd', b'a', b't'];
+    // an extended size which would fit in 32 bits
+    data.extend_from_slice(&20u64.to_be_bytes());
+    data.extend_from_slice(&[1, 2, 3, 4]);
+
+    let qt = qtfile::parse(&mut Cursor::new(data)).unwrap();
+    let mdat = qt.mdat().next().unwrap();
+    assert_eq!(mdat.header_size, 16);
+    assert_eq!(mdat.data_range(), (16, 20));
+}
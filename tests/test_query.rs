@@ -0,0 +1,57 @@
+use std::path::PathBuf;
+
+use atom_analyzer::atom::{self, hdlr, stsz};
+use atom_analyzer::qtfile;
+use atom_analyzer::query::QueryError;
+
+#[test]
+fn test_camouflage_vga_mov_find() {
+    let file_name = PathBuf::from("tests/samples/camouflage_vga.mov");
+    let qt = qtfile::parse_file(file_name).unwrap();
+
+    let hdlr = qt.find::<hdlr::HdlrAtom>("moov/trak[0]/mdia/hdlr").unwrap();
+    assert_eq!(hdlr.len(), 1);
+    assert_eq!(
+        hdlr[0].component_sub_type,
+        hdlr::ComponentSubType::VideoMedia
+    );
+
+    let hdlr = qt.find::<hdlr::HdlrAtom>("/moov/trak/mdia/minf/*").unwrap();
+    assert_eq!(hdlr.len(), 1);
+    assert_eq!(hdlr[0].component_type, hdlr::ComponentType::Dhlr);
+
+    let stsz = qt
+        .find::<stsz::StszAtom>("moov/trak/mdia/minf/stbl/stsz")
+        .unwrap();
+    assert_eq!(stsz[0].number_of_entries, 30);
+
+    assert!(qt
+        .find::<hdlr::HdlrAtom>("moov/trak[0]/mdia/mdhd")
+        .unwrap()
+        .is_empty());
+}
+
+#[test]
+fn test_camouflage_vga_mov_select() {
+    let file_name = PathBuf::from("tests/samples/camouflage_vga.mov");
+    let qt = qtfile::parse_file(file_name).unwrap();
+
+    assert_eq!(qt.select("*").unwrap().len(), 4);
+    assert_eq!(qt.select("*[2]").unwrap().len(), 1);
+    assert_eq!(qt.select("moov/trak[*]/*").unwrap().len(), 3);
+    assert!(qt.select("moov/trak[1]").unwrap().is_empty());
+    assert!(qt.select("free").unwrap().is_empty());
+
+    let mdat = qt.select("mdat").unwrap();
+    assert!(mdat[0].is::<atom::mdat::MdatAtom>());
+
+    assert_eq!(qt.select("/").err(), Some(QueryError::EmptyPath));
+    assert_eq!(
+        qt.select("moov/trak[x]").err(),
+        Some(QueryError::InvalidSegment("trak[x]".into()))
+    );
+    assert_eq!(
+        qt.select("moov/tr").err(),
+        Some(QueryError::InvalidSegment("tr".into()))
+    );
+}
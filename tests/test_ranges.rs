@@ -0,0 +1,114 @@
+use std::fs;
+use std::io::Cursor;
+
+use atom_analyzer::qtfile;
+use atom_analyzer::validate::ranges::{self, SampleRangeIssue, SampleRef};
+
+fn parse(data: Vec<u8>) -> qtfile::QtFile {
+    qtfile::parse(&mut Cursor::new(data)).unwrap()
+}
+
+fn sample(track_id: u32, index: usize) -> SampleRef {
+    SampleRef { track_id, index }
+}
+
+#[test]
+fn test_camouflage_vga_mov_sample_ranges() {
+    let data = fs::read("tests/samples/camouflage_vga.mov").unwrap();
+    let qt = parse(data);
+
+    assert_eq!(qt.file_size(), 0x6602);
+    assert!(ranges::check(&qt).is_empty());
+}
+
+#[test]
+fn test_sample_ranges_outside_mdat() {
+    let mut data = fs::read("tests/samples/camouflage_vga.mov").unwrap();
+
+    // chunk offset: 0x24 -> 0x30, which pushes the last sample out of 'mdat'
+    data[0x65dd..0x65e1].copy_from_slice(&0x30_u32.to_be_bytes());
+
+    assert_eq!(
+        ranges::check(&parse(data.clone())),
+        vec![SampleRangeIssue::OutsideMdat {
+            sample: sample(1, 29),
+            offset: 0x6173 + 0x0c,
+            size: 0x19,
+        }]
+    );
+
+    // chunk offset: 0x24 -> 0x6600
+    data[0x65dd..0x65e1].copy_from_slice(&0x6600_u32.to_be_bytes());
+
+    let issues = ranges::check(&parse(data));
+    assert_eq!(issues.len(), 30);
+    assert_eq!(
+        issues[0],
+        SampleRangeIssue::PastEof {
+            sample: sample(1, 0),
+            offset: 0x6600,
+            size: 0x5c82,
+        }
+    );
+}
+
+#[test]
+fn test_sample_ranges_overlap() {
+    let mut data = fs::read("tests/samples/camouflage_vga.mov").unwrap();
+
+    // a copy of the 'trak' as track 2 right after it
+    let mut trak = data[0x6200..0x65e1].to_vec();
+    trak[0x1c..0x20].copy_from_slice(&2_u32.to_be_bytes());
+    data.splice(0x65e1..0x65e1, trak);
+    let moov_size = 0x6602 - 0x618c + 0x3e1_u32;
+    data[0x618c..0x6190].copy_from_slice(&moov_size.to_be_bytes());
+
+    let issues = ranges::check(&parse(data));
+    assert_eq!(issues.len(), 30);
+    assert_eq!(
+        issues[0],
+        SampleRangeIssue::Overlap {
+            first: sample(1, 0),
+            second: sample(2, 0),
+            offset: 0x24,
+            size: 0x5c82,
+        }
+    );
+    assert_eq!(
+        issues[0].to_string(),
+        "track 1 sample 0 and track 2 sample 0 share 23682 bytes at 0x24"
+    );
+}
+
+#[test]
+fn test_sample_ranges_past_largest_offset() {
+    let mut data = fs::read("tests/samples/camouflage_vga.mov").unwrap();
+
+    // 'stco' at 0x65cd becomes a 'co64' of 4 more bytes whose chunk offset
+    // leaves no room for the first sample, so the sample ranges overflow
+    let mut co64 = 0x18_u32.to_be_bytes().to_vec();
+    co64.extend_from_slice(b"co64");
+    co64.extend_from_slice(&[0, 0, 0, 0, 0, 0, 0, 1]);
+    co64.extend_from_slice(&(u64::MAX - 0x10).to_be_bytes());
+    data.splice(0x65cd..0x65e1, co64);
+    for offset in &[0x6349, 0x62dd, 0x6288, 0x6200, 0x618c] {
+        let mut size = [0; 4];
+        size.copy_from_slice(&data[*offset..offset + 4]);
+        let size = u32::from_be_bytes(size) + 4;
+        data[*offset..offset + 4].copy_from_slice(&size.to_be_bytes());
+    }
+
+    let issues = ranges::check(&parse(data));
+    assert_eq!(issues.len(), 30);
+    assert_eq!(
+        issues[0],
+        SampleRangeIssue::PastEof {
+            sample: sample(1, 0),
+            offset: u64::MAX - 0x10,
+            size: 0x5c82,
+        }
+    );
+    assert!(issues
+        .iter()
+        .all(|i| matches!(i, SampleRangeIssue::PastEof { .. })));
+}
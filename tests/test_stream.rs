@@ -0,0 +1,128 @@
+use std::fs;
+use std::io::{self, Read};
+use std::path::PathBuf;
+
+use atom_analyzer::atom::{self, free, mdat};
+use atom_analyzer::qtfile::{self, stream, QtFileError, TopLevelAtom};
+
+/// A reader which hands out at most 7 bytes at a time and cannot seek
+struct Pipe {
+    data: Vec<u8>,
+    position: usize,
+}
+
+impl Read for Pipe {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let len = buf.len().min(7).min(self.data.len() - self.position);
+        buf[..len].copy_from_slice(&self.data[self.position..self.position + len]);
+        self.position += len;
+        Ok(len)
+    }
+}
+
+impl Pipe {
+    fn new(data: Vec<u8>) -> Self {
+        Pipe { data, position: 0 }
+    }
+}
+
+#[test]
+fn test_camouflage_vga_mov_stream() {
+    let file_name = PathBuf::from("tests/samples/camouflage_vga.mov");
+    let data = fs::read(&file_name).unwrap();
+
+    assert_eq!(
+        stream::parse_stream(Pipe::new(data.clone())).unwrap(),
+        qtfile::parse_file(file_name).unwrap()
+    );
+
+    let mut parser = stream::StreamParser::new(Pipe::new(data));
+
+    for _ in 0..3 {
+        parser.next().unwrap().unwrap();
+        assert!(!parser.moov_received());
+    }
+
+    assert!(matches!(parser.next(), Some(Ok(TopLevelAtom::Moov(_)))));
+    assert!(parser.moov_received());
+    assert_eq!(parser.bytes_read(), 0x6602);
+    assert!(parser.next().is_none());
+}
+
+#[test]
+fn test_stream_mdat_to_end() {
+    let mut data = vec![0, 0, 0, 8, b'f', b'r', b'e', b'e'];
+    data.extend_from_slice(&[0, 0, 0, 0, b'm', b'd', b'a', b't']);
+    data.extend_from_slice(&[0xff; 100]);
+
+    let atoms = stream::StreamParser::new(Pipe::new(data))
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+
+    assert_eq!(
+        atoms,
+        vec![
+            TopLevelAtom::Free(free::FreeAtom {
+                atom_head: atom::AtomHead {
+                    atom_offset: 0,
+                    atom_size: 8,
+                    atom_type: free::ATOM_ID,
+                },
+            }),
+            TopLevelAtom::Mdat(mdat::MdatAtom {
+                atom_head: atom::AtomHead {
+                    atom_offset: 8,
+                    atom_size: 108,
+                    atom_type: mdat::ATOM_ID,
+                },
+                header_size: 8,
+            }),
+        ]
+    );
+}
+
+#[test]
+fn test_stream_truncated() {
+    let data = vec![0, 0, 0, 16, b'm', b'd', b'a', b't', 0, 0];
+
+    let mut parser = stream::StreamParser::new(Pipe::new(data));
+
+    assert!(matches!(parser.next(), Some(Err(QtFileError::IoError(_)))));
+    assert!(parser.next().is_none());
+}
+
+#[test]
+fn test_stream_truncated_with_huge_size() {
+    // 'uuid', 'free' and a 64-bit 'moov' claim far more than the stream holds
+    let mut uuid = vec![0xff, 0xff, 0xff, 0xf0, b'u', b'u', b'i', b'd'];
+    uuid.extend_from_slice(&[0; 16]);
+    let mut free = vec![0xff, 0xff, 0xff, 0xf0, b'f', b'r', b'e', b'e'];
+    free.extend_from_slice(&[0; 16]);
+    let mut moov = vec![0, 0, 0, 1, b'm', b'o', b'o', b'v'];
+    moov.extend_from_slice(&(1_u64 << 60).to_be_bytes());
+    moov.extend_from_slice(&[0; 16]);
+
+    for data in vec![uuid, free, moov] {
+        let mut parser = stream::StreamParser::new(Pipe::new(data));
+
+        assert!(matches!(parser.next(), Some(Err(QtFileError::IoError(_)))));
+        assert!(parser.next().is_none());
+    }
+}
+
+#[test]
+fn test_stream_mdat_extended_size() {
+    let mut data = vec![0, 0, 0, 1, b'm', b'd', b'a', b't'];
+    // an extended size which would fit in 32 bits
+    data.extend_from_slice(&20u64.to_be_bytes());
+    data.extend_from_slice(&[1, 2, 3, 4]);
+
+    let atoms = stream::StreamParser::new(Pipe::new(data))
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+
+    match &atoms[..] {
+        [TopLevelAtom::Mdat(mdat)] => assert_eq!(mdat.data_range(), (16, 20)),
+        _ => panic!(),
+    }
+}
@@ -0,0 +1,496 @@
+use std::fs;
+use std::io::{self, Cursor};
+
+use atom_analyzer::atom::stsd::SampleDescription;
+use atom_analyzer::atom::AtomParseError;
+use atom_analyzer::qtfile;
+use atom_analyzer::subtitles::cea608::{self, Cea608Decoder};
+use atom_analyzer::subtitles::cea708::{self, Cea708Decoder};
+use atom_analyzer::subtitles::tx3g::{StyleRecord, TextSample, TextSampleEntry};
+use atom_analyzer::subtitles::{self, stpp, wvtt, Cue, SubtitleFormat};
+
+fn atom(atom_type: &[u8; 4], body: &[u8]) -> Vec<u8> {
+    let mut data = (8 + body.len() as u32).to_be_bytes().to_vec();
+    data.extend_from_slice(atom_type);
+    data.extend_from_slice(body);
+    data
+}
+
+fn full_atom(atom_type: &[u8; 4], values: &[u32]) -> Vec<u8> {
+    let mut body = vec![0; 4];
+    for v in values {
+        body.extend_from_slice(&v.to_be_bytes());
+    }
+    atom(atom_type, &body)
+}
+
+/// Returns a track of ID 2 with the edit list `edits`, one `format` entry
+/// holding `entry` and samples of 0.5 seconds each in one chunk at
+/// `chunk_offset`
+fn subtitle_trak(
+    data: &[u8],
+    format: &[u8; 4],
+    entry: &[u8],
+    samples: &[Vec<u8>],
+    edits: &[(u32, i32)],
+    chunk_offset: u32,
+) -> Vec<u8> {
+    // 'tkhd' at 0x6208 with the track ID at 20
+    let mut tkhd = data[0x6208..0x6264].to_vec();
+    tkhd[20..24].copy_from_slice(&2_u32.to_be_bytes());
+
+    // 'hdlr' at 0x62b0 with the component subtype at 16
+    let mut hdlr = data[0x62b0..0x62dd].to_vec();
+    hdlr[16..20].copy_from_slice(b"sbtl");
+
+    let mut description = (16 + entry.len() as u32).to_be_bytes().to_vec();
+    description.extend_from_slice(format);
+    description.extend_from_slice(&[0, 0, 0, 0, 0, 0, 0, 1]);
+    description.extend_from_slice(entry);
+    let mut stsd = vec![0, 0, 0, 0, 0, 0, 0, 1];
+    stsd.extend(description);
+
+    let mut stsz = vec![0, samples.len() as u32];
+    stsz.extend(samples.iter().map(|s| s.len() as u32));
+
+    let mut stbl = atom(b"stsd", &stsd);
+    stbl.extend(full_atom(b"stts", &[1, samples.len() as u32, 7680]));
+    stbl.extend(full_atom(b"stsc", &[1, 1, samples.len() as u32, 1]));
+    stbl.extend(full_atom(b"stsz", &stsz));
+    stbl.extend(full_atom(b"stco", &[1, chunk_offset]));
+
+    let mut minf = full_atom(b"nmhd", &[]);
+    // 'dinf' at 0x6325
+    minf.extend_from_slice(&data[0x6325..0x6349]);
+    minf.extend(atom(b"stbl", &stbl));
+
+    // 'mdhd' at 0x6290 has the time scale 15360 at 20 and the duration at 24
+    let mut mdia = data[0x6290..0x62b0].to_vec();
+    mdia[24..28].copy_from_slice(&(samples.len() as u32 * 7680).to_be_bytes());
+    mdia.extend(hdlr);
+    mdia.extend(atom(b"minf", &minf));
+
+    let mut trak = tkhd;
+    if !edits.is_empty() {
+        let mut elst = vec![edits.len() as u32];
+        for (track_duration, media_time) in edits {
+            elst.extend_from_slice(&[*track_duration, *media_time as u32, 0x0001_0000]);
+        }
+        trak.extend(atom(b"edts", &full_atom(b"elst", &elst)));
+    }
+    trak.extend(atom(b"mdia", &mdia));
+    atom(b"trak", &trak)
+}
+
+/// Returns the sample with a subtitle track of the edit list `edits`
+/// appended to 'moov' and its samples after 'moov'
+fn sample_with_edited_subtitles(
+    format: &[u8; 4],
+    entry: &[u8],
+    samples: &[Vec<u8>],
+    edits: &[(u32, i32)],
+) -> Vec<u8> {
+    let mut data = fs::read("tests/samples/camouflage_vga.mov").unwrap();
+
+    let trak_size = subtitle_trak(&data, format, entry, samples, edits, 0).len();
+    let chunk_offset = (data.len() + trak_size) as u32;
+    let trak = subtitle_trak(&data, format, entry, samples, edits, chunk_offset);
+
+    // 'moov' at 0x618c ends the file
+    let size = u32::from_be_bytes([data[0x618c], data[0x618d], data[0x618e], data[0x618f]]);
+    data[0x618c..0x6190].copy_from_slice(&(size + trak.len() as u32).to_be_bytes());
+    data.extend(trak);
+    for sample in samples {
+        data.extend_from_slice(sample);
+    }
+    data
+}
+
+/// Returns the sample with a subtitle track without an edit list appended to
+/// 'moov' and its samples after 'moov'
+fn sample_with_subtitles(format: &[u8; 4], entry: &[u8], samples: &[Vec<u8>]) -> Vec<u8> {
+    sample_with_edited_subtitles(format, entry, samples, &[])
+}
+
+/// Returns a cue timed in the movie time scale 1000
+fn cue(start_time: u64, end_time: u64, text: &str) -> Cue {
+    Cue {
+        start_time,
+        end_time,
+        time_scale: 1000,
+        id: None,
+        settings: None,
+        text: text.to_string(),
+    }
+}
+
+/// Returns a 'tx3g' sample entry with the font "Serif"
+fn tx3g_entry() -> Vec<u8> {
+    let mut entry = vec![0, 0, 0, 0, 1, 0xff, 0, 0, 0, 0xff];
+    entry.extend_from_slice(&[0, 0, 0, 0, 0, 60, 1, 0x40]);
+    entry.extend_from_slice(&[0, 0, 0, 0, 0, 1, 0, 18, 0xff, 0xff, 0xff, 0xff]);
+    entry.extend(atom(b"ftab", b"\x00\x01\x00\x01\x05Serif"));
+    entry
+}
+
+#[test]
+fn test_tx3g() {
+    let mut styled = b"\x00\x05Hello".to_vec();
+    styled.extend(atom(
+        b"styl",
+        &[0, 1, 0, 0, 0, 5, 0, 1, 2, 18, 0xff, 0, 0, 0xff],
+    ));
+    styled.extend(atom(b"hclr", &[0, 0xff, 0, 0xff]));
+    let samples = vec![
+        styled.clone(),
+        styled,
+        vec![0, 0],
+        b"\x00\x05World".to_vec(),
+    ];
+    let data = sample_with_subtitles(b"tx3g", &tx3g_entry(), &samples);
+
+    let qt = qtfile::parse(&mut Cursor::new(&data)).unwrap();
+    let tracks = subtitles::subtitle_tracks(&qt);
+    assert_eq!(tracks.len(), 1);
+    assert_eq!(
+        subtitles::subtitle_format(tracks[0]),
+        Some(SubtitleFormat::Tx3g)
+    );
+
+    let entry = TextSampleEntry::new(tracks[0].sample_description(1).unwrap()).unwrap();
+    assert_eq!(entry.vertical_justification, -1);
+    assert_eq!(entry.default_text_box.bottom, 60);
+    assert_eq!(entry.default_style.font_size, 18);
+    assert_eq!(entry.font_table[0].font_name, "Serif");
+
+    let cues = subtitles::subtitles(tracks[0], 1000, &mut Cursor::new(&data)).unwrap();
+    assert_eq!(cues, vec![cue(0, 1000, "Hello"), cue(1500, 2000, "World")]);
+
+    assert_eq!(
+        subtitles::to_srt(&cues),
+        "1\n00:00:00,000 --> 00:00:01,000\nHello\n\n\
+         2\n00:00:01,500 --> 00:00:02,000\nWorld\n\n"
+    );
+}
+
+#[test]
+fn test_text_sample() {
+    let mut data = b"\x00\x02Hi".to_vec();
+    data.extend(atom(
+        b"styl",
+        &[0, 1, 0, 0, 0, 2, 0, 1, 3, 18, 0xff, 0, 0, 0xff],
+    ));
+    data.extend(atom(b"krok", &[0, 0, 0, 0]));
+    let sample = TextSample::parse(&data);
+
+    assert_eq!(sample.text, "Hi");
+    assert_eq!(sample.modifiers.len(), 2);
+    let styles = sample.styles();
+    assert_eq!(
+        styles[0].face_style_flags,
+        StyleRecord::BOLD | StyleRecord::ITALIC
+    );
+    assert_eq!(styles[0].text_color, [0xff, 0, 0, 0xff]);
+}
+
+#[test]
+fn test_wvtt() {
+    let mut vttc = atom(b"iden", b"intro");
+    vttc.extend(atom(b"sttg", b"line:0"));
+    vttc.extend(atom(b"payl", b"Hello"));
+    let mut two = atom(b"vttc", &vttc);
+    two.extend(atom(b"vttc", &atom(b"payl", b"World")));
+    let samples = vec![atom(b"vttc", &vttc), two, atom(b"vtte", &[])];
+
+    let entry = atom(b"vttC", b"WEBVTT");
+    let data = sample_with_subtitles(b"wvtt", &entry, &samples);
+    let qt = qtfile::parse(&mut Cursor::new(&data)).unwrap();
+    let trak = subtitles::subtitle_tracks(&qt)[0];
+
+    let entry = wvtt::WebVttSampleEntry::new(trak.sample_description(1).unwrap());
+    assert_eq!(entry.config.as_deref(), Some("WEBVTT"));
+    assert_eq!(entry.label, None);
+
+    let cues = subtitles::subtitles(trak, 1000, &mut Cursor::new(&data)).unwrap();
+    assert_eq!(cues.len(), 2);
+    assert_eq!(cues[0].id.as_deref(), Some("intro"));
+    assert_eq!((cues[0].start_time, cues[0].end_time), (0, 1000));
+    assert_eq!((cues[1].start_time, cues[1].end_time), (500, 1000));
+
+    assert_eq!(
+        subtitles::to_webvtt(&cues),
+        "WEBVTT\n\n\
+         intro\n00:00:00.000 --> 00:00:01.000 line:0\nHello\n\n\
+         00:00:00.500 --> 00:00:01.000\nWorld\n\n"
+    );
+}
+
+/// Returns `text` as pairs of standard characters with odd parity
+fn characters(text: &str) -> Vec<[u8; 2]> {
+    let parity = |b: u8| if b.count_ones() % 2 == 0 { b | 0x80 } else { b };
+    let mut bytes = text.bytes().map(parity).collect::<Vec<_>>();
+    if bytes.len() % 2 == 1 {
+        bytes.push(0x80);
+    }
+    bytes.chunks(2).map(|c| [c[0], c[1]]).collect()
+}
+
+#[test]
+fn test_cea608() {
+    let mut decoder = Cea608Decoder::new(30);
+    let mut push = |time: u64, pairs: &[[u8; 2]]| {
+        for pair in pairs {
+            decoder.push(time, *pair);
+        }
+    };
+
+    // pop-on: RCL, PAC row 15, text, PAC row 14, text and EOC, each sent twice
+    push(0, &[[0x94, 0x20], [0x94, 0x20], [0x94, 0x70], [0x94, 0x70]]);
+    push(1, &characters("Hello"));
+    push(1, &[[0x13, 0xd0], [0x13, 0xd0]]);
+    push(1, &characters("world"));
+    push(1, &[[0x91, 0x37], [0x91, 0x37]]);
+    push(2, &[[0x94, 0x2f], [0x94, 0x2f]]);
+    // channel 2 text is ignored
+    push(3, &[[0x1c, 0x20], [0x1c, 0x20]]);
+    push(3, &characters("other"));
+    // EDM
+    push(4, &[[0x94, 0x2c], [0x94, 0x2c]]);
+
+    // roll-up of 3 rows with carriage returns
+    push(5, &[[0x94, 0x26], [0x94, 0x26]]);
+    push(5, &characters("one"));
+    push(6, &[[0x94, 0xad], [0x94, 0xad]]);
+    push(6, &characters("two"));
+    push(7, &[[0x94, 0xad], [0x94, 0xad]]);
+
+    let cues = decoder.finish(9);
+    assert_eq!(
+        cues.iter()
+            .map(|c| (c.start_time, c.end_time, c.text.as_str()))
+            .collect::<Vec<_>>(),
+        vec![(2, 4, "Hello\nworld♪"), (6, 7, "one"), (7, 9, "one\ntwo"),]
+    );
+}
+
+#[test]
+fn test_cc_data_pairs() {
+    let mut cdat = atom(b"cdat", &[0x94, 0x2f, 0x94, 0x2f]);
+    cdat.extend(atom(b"cdt2", &[0x15, 0x2f]));
+    assert_eq!(cea608::c608_pairs(&cdat), vec![[0x94, 0x2f], [0x94, 0x2f]]);
+
+    // a CDP with a time code section and three triplets: field 1, an
+    // invalid field 1 and field 2
+    let mut cdp = vec![0x96, 0x69, 0, 0x4f, 0xc3, 0, 1];
+    cdp.extend_from_slice(&[0x71, 0, 0, 0, 0]);
+    cdp.extend_from_slice(&[0x72, 0xe3]);
+    cdp.extend_from_slice(&[0xfc, 0xc1, 0xc2, 0xf8, 0x80, 0x80, 0xfd, 0x80, 0x80]);
+    assert_eq!(cea608::c708_pairs(&atom(b"ccdp", &cdp)), vec![[0xc1, 0xc2]]);
+    assert_eq!(
+        cea708::cc_data(&atom(b"ccdp", &cdp)),
+        vec![(0, [0xc1, 0xc2]), (1, [0x80, 0x80])]
+    );
+}
+
+/// Returns the triplets of a DTVCC packet of the service blocks `blocks`
+fn dtvcc_packet(blocks: &[(u8, &[u8])]) -> Vec<(u8, [u8; 2])> {
+    let mut packet = Vec::new();
+    for (service, data) in blocks {
+        packet.push(service << 5 | data.len() as u8);
+        packet.extend_from_slice(data);
+    }
+    // the header and the blocks fill pairs of bytes
+    if packet.len() % 2 == 0 {
+        packet.push(0);
+    }
+
+    let mut triplets = vec![(3, [((packet.len() + 1) / 2) as u8, packet[0]])];
+    triplets.extend(packet[1..].chunks(2).map(|c| (2, [c[0], c[1]])));
+    triplets
+}
+
+/// Returns a 'ccdp' atom of `triplets`
+fn ccdp(triplets: &[(u8, [u8; 2])]) -> Vec<u8> {
+    let mut cdp = vec![0x96, 0x69, 0, 0x4f, 0x43, 0, 1];
+    cdp.extend_from_slice(&[0x72, 0xe0 | triplets.len() as u8]);
+    for (cc_type, data) in triplets {
+        cdp.extend_from_slice(&[0xfc | cc_type, data[0], data[1]]);
+    }
+    atom(b"ccdp", &cdp)
+}
+
+/// DF0 defines a hidden window 0 of two rows, and DF1 a visible window 1 of
+/// one row
+const DF0_HIDDEN: [u8; 7] = [0x98, 0x00, 0, 0, 0x01, 0, 0];
+const DF1_VISIBLE: [u8; 7] = [0x99, 0x20, 0, 0, 0x00, 0, 0];
+
+#[test]
+fn test_cea708() {
+    let mut decoder = Cea708Decoder::new(30);
+    let mut push = |time: u64, blocks: &[(u8, &[u8])]| {
+        for (cc_type, data) in dtvcc_packet(blocks) {
+            decoder.push(time, cc_type, data);
+        }
+    };
+
+    // pop-on: text written to a hidden window, then DSW and HDW
+    let mut text = DF0_HIDDEN.to_vec();
+    text.extend_from_slice(b"Hello\rworld\x7f");
+    push(0, &[(1, &text)]);
+    push(1, &[(1, &[0x89, 0x01])]);
+    // another service is ignored
+    push(2, &[(2, &[0x9a, 0x20, 0, 0, 0, 0, 0, b'x'])]);
+    push(3, &[(1, &[0x8a, 0x01])]);
+
+    // paint-on: characters appended to a visible window extend the cue
+    let mut text = DF1_VISIBLE.to_vec();
+    text.extend_from_slice(b"a");
+    push(4, &[(1, &text)]);
+    push(5, &[(1, &[b'b', 0x10, 0x35, 0xe9])]);
+    // a carriage return scrolls the single row up
+    push(6, &[(1, &[0x0d, b'c'])]);
+    // DLW
+    push(7, &[(1, &[0x8c, 0x02])]);
+
+    let cues = decoder.finish(9);
+    assert_eq!(
+        cues.iter()
+            .map(|c| (c.start_time, c.end_time, c.text.as_str()))
+            .collect::<Vec<_>>(),
+        vec![(1, 3, "Hello\nworld♪"), (4, 6, "ab•é"), (6, 7, "c")]
+    );
+}
+
+#[test]
+fn test_cea708_track() {
+    // a DTVCC packet split across two samples
+    let triplets = dtvcc_packet(&[(1, &[&DF1_VISIBLE[..], b"Hello"].concat())]);
+    let (first, second) = triplets.split_at(2);
+    let samples = vec![
+        ccdp(first),
+        ccdp(second),
+        ccdp(&dtvcc_packet(&[(1, &[0x8c, 0x02])])),
+    ];
+    let data = sample_with_subtitles(b"c708", &[], &samples);
+    let qt = qtfile::parse(&mut Cursor::new(&data)).unwrap();
+    let trak = subtitles::subtitle_tracks(&qt)[0];
+    assert_eq!(
+        subtitles::subtitles(trak, 1000, &mut Cursor::new(&data)).unwrap(),
+        vec![cue(500, 1000, "Hello")]
+    );
+
+    // without service 1 the CEA-608 data is decoded
+    let mut pairs = vec![[0x94, 0x29], [0x94, 0x29]];
+    pairs.extend(characters("Hi"));
+    let samples = vec![ccdp(&pairs.iter().map(|p| (0, *p)).collect::<Vec<_>>())];
+    let data = sample_with_subtitles(b"c708", &[], &samples);
+    let qt = qtfile::parse(&mut Cursor::new(&data)).unwrap();
+    let trak = subtitles::subtitle_tracks(&qt)[0];
+    assert_eq!(
+        subtitles::subtitles(trak, 1000, &mut Cursor::new(&data)).unwrap(),
+        vec![cue(0, 500, "Hi")]
+    );
+}
+
+#[test]
+fn test_ttml() {
+    let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<tt xmlns="http://www.w3.org/ns/ttml" ttp:tickRate="10000000" xml:lang="en">
+  <body><div>
+    <p begin="00:00:01.500" end="00:00:03.000">Hello<br/>
+      <span tts:fontStyle="italic">big</span>   world</p>
+    <p begin="40000000t" dur="1s">Tom &amp; Jerry &#x263A;</p>
+    <p>untimed</p>
+  </div></body>
+</tt>"#;
+
+    let paragraphs = stpp::parse_document(xml);
+    assert_eq!(
+        paragraphs,
+        vec![
+            stpp::TtmlParagraph {
+                begin: Some(1.5),
+                end: Some(3.0),
+                text: "Hello\nbig world".to_string(),
+            },
+            stpp::TtmlParagraph {
+                begin: Some(4.0),
+                end: Some(5.0),
+                text: "Tom & Jerry \u{263a}".to_string(),
+            },
+            stpp::TtmlParagraph {
+                begin: None,
+                end: None,
+                text: "untimed".to_string(),
+            },
+        ]
+    );
+
+    assert_eq!(stpp::parse_time("00:00:01:15", 30.0, 1.0), Some(1.5));
+    assert_eq!(stpp::parse_time("1500ms", 30.0, 1.0), Some(1.5));
+    assert_eq!(stpp::parse_time("0.5m", 30.0, 1.0), Some(30.0));
+    assert_eq!(stpp::parse_time("1x", 30.0, 1.0), None);
+
+    let entry = stpp::XmlSubtitleSampleEntry::new(&SampleDescription {
+        sample_description_size: 16 + 38,
+        data_format: 0x7374_7070,
+        reserved: [0; 6],
+        data_reference_index: 1,
+        data: b"http://www.w3.org/ns/ttml\0\0image/png\0".to_vec(),
+    });
+    assert_eq!(entry.namespace, "http://www.w3.org/ns/ttml");
+    assert_eq!(entry.schema_location, "");
+    assert_eq!(entry.auxiliary_mime_types, "image/png");
+}
+
+#[test]
+fn test_edited_subtitles() {
+    // an empty edit of 0.5 seconds, then the media from 0.5 seconds for one
+    // second, which skips the first sample
+    let edits = [(500, -1), (1000, 7680)];
+    let samples = vec![
+        b"\x00\x01A".to_vec(),
+        b"\x00\x01B".to_vec(),
+        b"\x00\x01C".to_vec(),
+    ];
+    let data = sample_with_edited_subtitles(b"tx3g", &tx3g_entry(), &samples, &edits);
+    let qt = qtfile::parse(&mut Cursor::new(&data)).unwrap();
+    let trak = subtitles::subtitle_tracks(&qt)[0];
+
+    assert_eq!(
+        subtitles::subtitles(trak, 1000, &mut Cursor::new(&data)).unwrap(),
+        vec![cue(500, 1000, "B"), cue(1000, 1500, "C")]
+    );
+
+    // document times of TTML move with the edit and are cut to the sample
+    let document = br#"<tt xmlns="http://www.w3.org/ns/ttml"><body><div>
+    <p begin="00:00:00.600" end="00:00:00.800">one</p>
+    <p begin="00:00:00.900" end="00:00:01.200">two</p>
+    <p begin="00:00:01.500" end="00:00:02.000">later</p>
+  </div></body></tt>"#;
+    let samples = vec![Vec::new(), document.to_vec()];
+    let edits = [(250, -1), (1000, 0)];
+    let data = sample_with_edited_subtitles(b"stpp", b"\0\0\0", &samples, &edits);
+    let qt = qtfile::parse(&mut Cursor::new(&data)).unwrap();
+    let trak = subtitles::subtitle_tracks(&qt)[0];
+
+    assert_eq!(
+        subtitles::subtitles(trak, 1000, &mut Cursor::new(&data)).unwrap(),
+        vec![cue(850, 1050, "one"), cue(1150, 1250, "two")]
+    );
+}
+
+#[test]
+fn test_truncated_sample() {
+    let samples = vec![b"\x00\x06Hello!".to_vec()];
+    let mut data = sample_with_subtitles(b"tx3g", &tx3g_entry(), &samples);
+    let qt = qtfile::parse(&mut Cursor::new(&data)).unwrap();
+    let trak = subtitles::subtitle_tracks(&qt)[0];
+
+    // the only sample ends the file
+    data.truncate(data.len() - 1);
+    assert!(matches!(
+        subtitles::subtitles(trak, 1000, &mut Cursor::new(&data)),
+        Err(AtomParseError::IoError(e)) if e.kind() == io::ErrorKind::UnexpectedEof
+    ));
+}
@@ -0,0 +1,214 @@
+use std::fs;
+use std::io::Cursor;
+
+use atom_analyzer::atom::{self, gmhd::GmhdAtom, tmcd::TimecodeDescription};
+use atom_analyzer::qtfile::{self, lazy};
+
+/// Returns the sample with its 'avc1' entry turned into a 29.97 fps drop
+/// frame 'tmcd' entry named "tape"
+fn tmcd_sample() -> Vec<u8> {
+    let mut data = fs::read("tests/samples/camouflage_vga.mov").unwrap();
+
+    // the 'stsd' entry at 0x6361 holds 0x88 bytes after its 16-byte header
+    let mut entry = Vec::new();
+    entry.extend_from_slice(&0_u32.to_be_bytes());
+    entry.extend_from_slice(
+        &(TimecodeDescription::DROP_FRAME | TimecodeDescription::MAX_24_HOUR).to_be_bytes(),
+    );
+    entry.extend_from_slice(&30000_u32.to_be_bytes());
+    entry.extend_from_slice(&1001_u32.to_be_bytes());
+    entry.extend_from_slice(&[30, 0]);
+    entry.extend_from_slice(&[0, 0, 0, 16, b'n', b'a', b'm', b'e', 0, 4, 0, 0]);
+    entry.extend_from_slice(b"tape");
+    let padding = 0x88 - entry.len() - 8;
+    entry.extend_from_slice(&((8 + padding) as u32).to_be_bytes());
+    entry.extend_from_slice(b"free");
+    entry.resize(0x88, 0);
+
+    data[0x6365..0x6369].copy_from_slice(b"tmcd");
+    data[0x6371..0x6371 + 0x88].copy_from_slice(&entry);
+    data
+}
+
+#[test]
+fn test_timecode_description() {
+    let qt = qtfile::parse(&mut Cursor::new(tmcd_sample())).unwrap();
+    let trak = qt.moov().unwrap().track(1).unwrap();
+
+    let description = trak.timecode_description(1).unwrap().unwrap();
+    assert_eq!(
+        description,
+        TimecodeDescription {
+            reserved: 0,
+            flags: 3,
+            time_scale: 30000,
+            frame_duration: 1001,
+            number_of_frames: 30,
+            name: Some("tape".to_string()),
+        }
+    );
+    assert!(description.is_drop_frame());
+    assert!(description.is_max_24_hour());
+    assert!(!description.is_counter());
+    assert_eq!(trak.timecode_description(2).unwrap(), None);
+
+    // the untouched sample has no timecode
+    let qt = qtfile::parse_file("tests/samples/camouflage_vga.mov".into()).unwrap();
+    let trak = qt.moov().unwrap().track(1).unwrap();
+    assert_eq!(trak.timecode_description(1).unwrap(), None);
+    let mut r = Cursor::new(fs::read("tests/samples/camouflage_vga.mov").unwrap());
+    assert_eq!(trak.start_timecode(&mut r).unwrap(), None);
+}
+
+#[test]
+fn test_timecode_at() {
+    let mut data = tmcd_sample();
+    let qt = qtfile::parse(&mut Cursor::new(data.clone())).unwrap();
+    let trak = qt.moov().unwrap().track(1).unwrap();
+    let samples = trak.samples();
+
+    // 01:00:00;00 in the first sample, 10:00:00;00 where the edit starts
+    let first = samples[0].offset as usize;
+    data[first..first + 4].copy_from_slice(&107_892_u32.to_be_bytes());
+    let third = samples[2].offset as usize;
+    data[third..third + 4].copy_from_slice(&1_078_920_u32.to_be_bytes());
+    let mut r = Cursor::new(data);
+
+    let timecode = trak.timecode_at(&mut r, 0).unwrap().unwrap();
+    assert_eq!(timecode.to_string(), "01:00:00;00");
+    // 1/30 s later is still the same frame at 29.97 fps
+    assert_eq!(
+        trak.timecode_at(&mut r, 511).unwrap().unwrap().to_string(),
+        "01:00:00;00"
+    );
+    assert_eq!(
+        trak.start_timecode(&mut r).unwrap().unwrap().to_string(),
+        "10:00:00;00"
+    );
+    assert_eq!(trak.timecode_at(&mut r, 15360).unwrap(), None);
+}
+
+#[test]
+fn test_timecode_frames() {
+    let mut description = TimecodeDescription {
+        reserved: 0,
+        flags: TimecodeDescription::DROP_FRAME,
+        time_scale: 30000,
+        frame_duration: 1001,
+        number_of_frames: 30,
+        name: None,
+    };
+
+    assert_eq!(description.timecode(1799).to_string(), "00:00:59;29");
+    assert_eq!(description.timecode(1800).to_string(), "00:01:00;02");
+    assert_eq!(description.timecode(17982).to_string(), "00:10:00;00");
+    assert_eq!(
+        description.timecode(17982 * 6 * 25).to_string(),
+        "25:00:00;00"
+    );
+
+    description.flags |= TimecodeDescription::MAX_24_HOUR;
+    assert_eq!(
+        description.timecode(17982 * 6 * 25).to_string(),
+        "01:00:00;00"
+    );
+
+    description.flags = 0;
+    description.time_scale = 25;
+    description.frame_duration = 1;
+    description.number_of_frames = 25;
+    assert_eq!(description.timecode(90_000).to_string(), "01:00:00:00");
+    assert_eq!(description.timecode(1524).to_string(), "00:01:00:24");
+}
+
+/// Returns a 'gmhd' holding 'tmcd' with a 'tcmi' of 12-point white Helvetica
+fn gmhd_tmcd() -> Vec<u8> {
+    let mut tcmi = vec![0, 0, 0, 0];
+    tcmi.extend_from_slice(&[0, 0, 0, 0, 0, 12, 0, 0]);
+    tcmi.extend_from_slice(&[0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0, 0, 0, 0, 0, 0]);
+    tcmi.push(9);
+    tcmi.extend_from_slice(b"Helvetica");
+
+    let mut data = Vec::new();
+    data.extend_from_slice(&(8 + 8 + 8 + tcmi.len() as u32).to_be_bytes());
+    data.extend_from_slice(b"gmhd");
+    data.extend_from_slice(&(8 + 8 + tcmi.len() as u32).to_be_bytes());
+    data.extend_from_slice(b"tmcd");
+    data.extend_from_slice(&(8 + tcmi.len() as u32).to_be_bytes());
+    data.extend_from_slice(b"tcmi");
+    data.extend_from_slice(&tcmi);
+    data
+}
+
+#[test]
+fn test_gmhd_tmcd_tcmi() {
+    let gmhd = atom::parse(&mut Cursor::new(gmhd_tmcd()))
+        .unwrap()
+        .downcast::<GmhdAtom>()
+        .unwrap();
+    let tcmi = gmhd.tmcd_atom.as_ref().unwrap().tcmi_atom.as_ref().unwrap();
+
+    assert!(gmhd.gmin_atom.is_none());
+    assert_eq!(tcmi.text_size, 12);
+    assert_eq!(tcmi.text_color, [0xffff, 0xffff, 0xffff]);
+    assert_eq!(tcmi.background_color, [0, 0, 0]);
+    assert_eq!(tcmi.font_name, "Helvetica");
+}
+
+#[test]
+fn test_timecode_lazy() {
+    let mut data = tmcd_sample();
+
+    // 'gmhd' in place of the 'vmhd' at 0x62e5 in 'minf', 'mdia', 'trak' and
+    // 'moov', then a 'tref' to timecode track 1 after 'tkhd'
+    let gmhd = gmhd_tmcd();
+    data.splice(0x62e5..0x62f9, gmhd.iter().copied());
+    let grow = |data: &mut Vec<u8>, offsets: &[usize], delta: usize| {
+        for offset in offsets {
+            let mut size = [0; 4];
+            size.copy_from_slice(&data[*offset..offset + 4]);
+            let size = u32::from_be_bytes(size) + delta as u32;
+            data[*offset..offset + 4].copy_from_slice(&size.to_be_bytes());
+        }
+    };
+    grow(
+        &mut data,
+        &[0x62dd, 0x6288, 0x6200, 0x618c],
+        gmhd.len() - 0x14,
+    );
+
+    let tref = [0, 0, 0, 20, b't', b'r', b'e', b'f'];
+    let tmcd = [0, 0, 0, 12, b't', b'm', b'c', b'd', 0, 0, 0, 1];
+    data.splice(0x6264..0x6264, tref.iter().chain(&tmcd).copied());
+    grow(&mut data, &[0x6200, 0x618c], tref.len() + tmcd.len());
+
+    // a 'tmcd' outside 'gmhd' holds no atoms either, here as user data
+    // appended to the 'udta' which ends 'moov' and the file
+    let udta = data.len() - 0x21;
+    assert_eq!(&data[udta + 4..udta + 8], b"udta");
+    data.extend_from_slice(&[0, 0, 0, 16, b't', b'm', b'c', b'd', 0, 0, 0, 1, 0, 0, 0, 2]);
+    grow(&mut data, &[udta, 0x618c], 16);
+
+    let full = qtfile::parse(&mut Cursor::new(&data)).unwrap();
+    let qt = lazy::LazyQtFile::new(Cursor::new(data)).unwrap();
+
+    let reference = qt.select("moov/trak/tref/tmcd").unwrap();
+    assert_eq!(reference.len(), 1);
+    assert!(reference[0].children.is_empty());
+
+    let tcmi = qt.select("moov/trak/mdia/minf/gmhd/tmcd/tcmi").unwrap();
+    assert_eq!(tcmi.len(), 1);
+
+    let gmhd = qt.find::<GmhdAtom>("moov/trak/mdia/minf/gmhd").unwrap();
+    let expected = full.moov().unwrap().trak_atom[0]
+        .mdia_atom
+        .minf_atom
+        .as_ref()
+        .unwrap()
+        .media_info
+        .header()
+        .unwrap()
+        .downcast_ref::<GmhdAtom>()
+        .unwrap();
+    assert_eq!(gmhd[0], expected);
+}
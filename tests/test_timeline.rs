@@ -0,0 +1,179 @@
+use std::path::PathBuf;
+
+use fixed::{types::extra::U16, FixedI32, FixedU32};
+
+use atom_analyzer::atom::elst::EditListTableEntry;
+use atom_analyzer::atom::minf::MediaInfo;
+use atom_analyzer::atom::stbl::Sample;
+use atom_analyzer::atom::trak::{Edit, PresentedSample, TrakAtom};
+use atom_analyzer::qtfile::{self, TopLevelAtom};
+
+fn camouflage_vga_trak() -> TrakAtom {
+    let file_name = PathBuf::from("tests/samples/camouflage_vga.mov");
+    let moov = qtfile::parse_file(file_name)
+        .unwrap()
+        .into_iter()
+        .find_map(|a| match a {
+            TopLevelAtom::Moov(moov) => Some(moov),
+            _ => None,
+        })
+        .unwrap();
+
+    assert_eq!(moov.mvhd_atom.as_ref().unwrap().time_scale, 1000);
+
+    moov.trak_atom.into_iter().next().unwrap()
+}
+
+#[test]
+fn test_camouflage_vga_mov_samples() {
+    let trak = camouflage_vga_trak();
+    let samples = trak.samples();
+
+    assert_eq!(trak.media_time_scale(), 15360);
+    assert_eq!(samples.len(), 30);
+    assert_eq!(
+        samples[0],
+        Sample {
+            index: 0,
+            chunk: 1,
+            offset: 0x24,
+            size: 23682,
+            decode_time: 0,
+            composition_time: 1024,
+            duration: 512,
+            is_sync: true,
+            sample_description_id: 1,
+        }
+    );
+    assert_eq!(samples[1].offset, 0x24 + 23682);
+    assert_eq!(samples[1].composition_time, 3072);
+    assert!(!samples[1].is_sync);
+    assert_eq!(samples[29].decode_time, 14848);
+}
+
+#[test]
+fn test_camouflage_vga_mov_timeline() {
+    let trak = camouflage_vga_trak();
+
+    assert_eq!(
+        trak.edits(1000),
+        vec![Edit {
+            movie_time: 0,
+            movie_duration: 1000,
+            media_time: Some(1024),
+            media_rate: FixedU32::<U16>::from_num(1),
+        }]
+    );
+
+    let presented = trak.presentation(1000);
+    assert_eq!(presented.len(), 30);
+    assert_eq!(
+        presented[..4],
+        [
+            PresentedSample {
+                index: 0,
+                movie_time: 0,
+                movie_duration: 33
+            },
+            PresentedSample {
+                index: 3,
+                movie_time: 33,
+                movie_duration: 33
+            },
+            PresentedSample {
+                index: 2,
+                movie_time: 66,
+                movie_duration: 34
+            },
+            PresentedSample {
+                index: 4,
+                movie_time: 100,
+                movie_duration: 33
+            },
+        ]
+    );
+    assert_eq!(
+        presented.iter().map(|s| s.movie_duration).sum::<u64>(),
+        1000
+    );
+
+    assert_eq!(trak.sample_at(1000, 0).unwrap().index, 0);
+    assert_eq!(trak.sample_at(1000, 140).unwrap().index, 1);
+    assert_eq!(trak.sample_at(1000, 999).unwrap().index, 29);
+    assert!(trak.sample_at(1000, 1000).is_none());
+}
+
+#[test]
+fn test_empty_and_dwell_edits() {
+    let mut trak = camouflage_vga_trak();
+    let elst = trak.edts_atom.as_mut().unwrap().elst_atom.as_mut().unwrap();
+
+    elst.edit_list_table = vec![
+        EditListTableEntry {
+            track_duration: 500,
+            media_time: -1,
+            media_rate: FixedI32::<U16>::from_num(1),
+        },
+        EditListTableEntry {
+            track_duration: 250,
+            media_time: 3072,
+            media_rate: FixedI32::<U16>::from_num(0),
+        },
+        EditListTableEntry {
+            track_duration: 100,
+            media_time: 1024,
+            media_rate: FixedI32::<U16>::from_num(1),
+        },
+    ];
+    elst.number_of_entries = 3;
+
+    let edits = trak.edits(1000);
+    assert_eq!(edits[0].media_time, None);
+    assert!(edits[1].is_dwell());
+    assert_eq!(edits[2].movie_time, 750);
+
+    let presented = trak.presentation(1000);
+    assert_eq!(
+        presented[0],
+        PresentedSample {
+            index: 1,
+            movie_time: 500,
+            movie_duration: 250
+        }
+    );
+    assert_eq!(presented[1].index, 0);
+    assert_eq!(presented[1].movie_time, 750);
+    assert_eq!(
+        presented.last().unwrap().movie_time + presented.last().unwrap().movie_duration,
+        850
+    );
+
+    assert!(trak.sample_at(1000, 499).is_none());
+    assert_eq!(trak.sample_at(1000, 500).unwrap().index, 1);
+    assert_eq!(trak.sample_at(1000, 749).unwrap().index, 1);
+    assert_eq!(trak.sample_at(1000, 750).unwrap().index, 0);
+    assert!(trak.sample_at(1000, 850).is_none());
+}
+
+#[test]
+fn test_seek() {
+    let mut trak = camouflage_vga_trak();
+
+    let sample = trak.seek(1000, 0.5).unwrap();
+    assert_eq!(sample.index, 0);
+    assert_eq!(sample.offset, 0x24);
+    assert_eq!(sample.decode_time, 0);
+    assert_eq!(sample.composition_time, 1024);
+    assert!(trak.seek(1000, 1.0).is_none());
+    assert!(trak.seek(1000, -1.0).is_none());
+
+    match &mut trak.mdia_atom.minf_atom.as_mut().unwrap().media_info {
+        MediaInfo::VideoMediaInfo {
+            stbl_atom: stbl, ..
+        } => stbl.stss_atom.as_mut().unwrap().sync_sample_table = vec![1, 11],
+        _ => panic!("no 'stbl' atom"),
+    }
+
+    assert_eq!(trak.seek(1000, 0.5).unwrap().index, 10);
+    assert_eq!(trak.seek(1000, 0.25).unwrap().index, 0);
+}
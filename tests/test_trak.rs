@@ -0,0 +1,223 @@
+use std::fs;
+use std::io::Cursor;
+
+use atom_analyzer::atom::imap::InputType;
+use atom_analyzer::atom::load::LoadAtom;
+use atom_analyzer::atom::tkhd::TkhdAtom;
+use atom_analyzer::atom::Atom;
+use atom_analyzer::qtfile::{self, QtFile};
+
+fn atom(atom_type: &[u8; 4], body: &[u8]) -> Vec<u8> {
+    let mut data = (8 + body.len() as u32).to_be_bytes().to_vec();
+    data.extend_from_slice(atom_type);
+    data.extend_from_slice(body);
+    data
+}
+
+/// Returns a QT atom with the ID `atom_id` holding `body`
+fn qt_atom(atom_type: &[u8; 4], atom_id: u32, child_count: u16, body: &[u8]) -> Vec<u8> {
+    let mut data = atom_id.to_be_bytes().to_vec();
+    data.extend_from_slice(&[0, 0]);
+    data.extend_from_slice(&child_count.to_be_bytes());
+    data.extend_from_slice(&[0, 0, 0, 0]);
+    data.extend_from_slice(body);
+    atom(atom_type, &data)
+}
+
+/// Returns the sample with `before` and `after` around 'edts'
+fn sample_with(before: &[u8], after: &[u8]) -> QtFile {
+    let mut data = fs::read("tests/samples/camouflage_vga.mov").unwrap();
+
+    // 'edts' is from 0x6264 to 0x6288 in the 'trak' at 0x6200 in the 'moov'
+    // at 0x618c
+    data.splice(0x6288..0x6288, after.iter().copied());
+    data.splice(0x6264..0x6264, before.iter().copied());
+    for offset in &[0x6200, 0x618c] {
+        let mut size = [0; 4];
+        size.copy_from_slice(&data[*offset..offset + 4]);
+        let size = u32::from_be_bytes(size) + (before.len() + after.len()) as u32;
+        data[*offset..offset + 4].copy_from_slice(&size.to_be_bytes());
+    }
+
+    qtfile::parse(&mut Cursor::new(data)).unwrap()
+}
+
+#[test]
+fn test_tkhd_flags() {
+    let qt = qtfile::parse(&mut Cursor::new(
+        fs::read("tests/samples/camouflage_vga.mov").unwrap(),
+    ))
+    .unwrap();
+    let tkhd = &qt.moov().unwrap().trak_atom[0].tkhd_atom;
+
+    assert_eq!(tkhd.flags(), TkhdAtom::ENABLED | TkhdAtom::IN_MOVIE);
+    assert!(tkhd.is_enabled());
+    assert!(tkhd.is_in_movie());
+    assert!(!tkhd.is_in_preview());
+    assert!(!tkhd.is_in_poster());
+
+    let stbl = qt.moov().unwrap().trak_atom[0].stbl_atom().unwrap();
+    assert_eq!(stbl.stsd_atom.as_ref().unwrap().flags(), 0);
+}
+
+#[test]
+fn test_track_atoms() {
+    // a clipping region of 0..400 x 0..640 with no scan lines
+    let crgn = atom(b"crgn", &[0, 10, 0, 0, 0, 0, 1, 0x90, 2, 0x80]);
+    let mut before = atom(b"clip", &crgn);
+
+    // a matte described as 2x1 'raw ' followed by its 2 pixels
+    let mut description = 18_u32.to_be_bytes().to_vec();
+    description.extend_from_slice(b"raw \0\0\0\0\0\0\0\x01\x12\x34");
+    let mut kmat = vec![0, 0, 0, 0];
+    kmat.extend(description);
+    kmat.extend_from_slice(&[0xff, 0x00]);
+    before.extend(atom(b"matt", &atom(b"kmat", &kmat)));
+
+    let mut load = 0_u32.to_be_bytes().to_vec();
+    load.extend_from_slice(&(-1_i32).to_be_bytes());
+    load.extend_from_slice(&LoadAtom::PRELOAD_ALWAYS.to_be_bytes());
+    load.extend_from_slice(&LoadAtom::HIGH_QUALITY.to_be_bytes());
+    let mut after = atom(b"load", &load);
+
+    let mut input = qt_atom(b"\0\0ty", 1, 0, &2_u32.to_be_bytes());
+    input.extend(qt_atom(b"obid", 1, 0, &7_u32.to_be_bytes()));
+    let mut imap = qt_atom(b"\0\0in", 1, 2, &input);
+    imap.extend(qt_atom(b"\0\0in", 2, 1, &qt_atom(b"\0\0ty", 1, 0, b"vide")));
+    after.extend(atom(b"imap", &imap));
+
+    let qt = sample_with(&before, &after);
+    let trak = &qt.moov().unwrap().trak_atom[0];
+
+    let crgn = trak
+        .clip_atom
+        .as_ref()
+        .and_then(|c| c.crgn_atom.as_ref())
+        .unwrap();
+    assert_eq!(crgn.region_size, 10);
+    assert_eq!(crgn.region_bounding_box.to_string(), "(0, 0)-(640, 400)");
+    assert!(crgn.is_rectangular());
+
+    let kmat = trak
+        .matt_atom
+        .as_ref()
+        .and_then(|m| m.kmat_atom.as_ref())
+        .unwrap();
+    assert_eq!(kmat.matte_image_description.data_format, 0x7261_7720);
+    assert_eq!(kmat.matte_image_description.data, vec![0x12, 0x34]);
+    assert_eq!(kmat.matte_data, vec![0xff, 0x00]);
+
+    let load = trak.load_atom.as_ref().unwrap();
+    assert_eq!(load.preload_duration, -1);
+    assert!(load.is_preload_always());
+    assert!(!load.is_track_enabled_preload());
+    assert!(load.is_high_quality());
+    assert!(!load.is_double_buffer());
+
+    let imap = trak.imap_atom.as_ref().unwrap();
+    assert_eq!(
+        imap.track_input_atom
+            .iter()
+            .map(|i| (i.atom_id, i.input_type, i.object_id))
+            .collect::<Vec<_>>(),
+        vec![
+            (1, Some(InputType::Clip), Some(7)),
+            (2, Some(InputType::Image), None),
+        ]
+    );
+
+    assert_eq!(
+        trak.children()
+            .iter()
+            .map(|a| a.fourcc())
+            .collect::<Vec<_>>(),
+        vec!["tkhd", "clip", "matt", "edts", "load", "imap", "mdia"]
+    );
+}
+
+#[test]
+fn test_mdhd_version_1() {
+    let data = fs::read("tests/samples/camouflage_vga.mov").unwrap();
+
+    // 'mdhd' at 0x6290 in the 'mdia' at 0x6288 becomes version 1 with 64-bit
+    // times and duration, the creation time past any representable date
+    let mut mdhd = vec![0, 0, 0, 0x2c, b'm', b'd', b'h', b'd', 1, 0, 0, 0];
+    mdhd.extend_from_slice(&u64::MAX.to_be_bytes());
+    mdhd.extend_from_slice(&2_u64.to_be_bytes());
+    mdhd.extend_from_slice(&15360_u32.to_be_bytes());
+    mdhd.extend_from_slice(&((1_u64 << 32) + 15360).to_be_bytes());
+    mdhd.extend_from_slice(&[0x7f, 0xff, 0, 0]);
+
+    let mut modified = data[..0x6290].to_vec();
+    modified.extend_from_slice(&mdhd);
+    modified.extend_from_slice(&data[0x62b0..]);
+    for offset in &[0x6288, 0x6200, 0x618c] {
+        let mut size = [0; 4];
+        size.copy_from_slice(&modified[*offset..offset + 4]);
+        let size = u32::from_be_bytes(size) + 12;
+        modified[*offset..offset + 4].copy_from_slice(&size.to_be_bytes());
+    }
+
+    let qt = qtfile::parse(&mut Cursor::new(modified)).unwrap();
+    let trak = &qt.moov().unwrap().trak_atom[0];
+    let mdhd = &trak.mdia_atom.mdhd_atom;
+
+    assert_eq!(mdhd.atom_version, 1);
+    assert_eq!(mdhd.time_scale, 15360);
+    assert_eq!(mdhd.duration, (1 << 32) + 15360);
+    assert_eq!(mdhd.language, 0x7fff);
+    assert!(format!("{:?}", mdhd.creation_time).ends_with("(0xffffffffffffffff)"));
+    assert_eq!(mdhd.modification_time.to_string(), "1904-01-01 00:00:02");
+    assert_eq!(trak.media_time_scale(), 15360);
+
+    // the siblings after 'mdhd' are still found
+    assert!(trak.mdia_atom.hdlr_atom.is_some());
+    assert_eq!(trak.samples().len(), 30);
+}
+
+#[test]
+fn test_elst_version_1() {
+    let data = fs::read("tests/samples/camouflage_vga.mov").unwrap();
+
+    // 'elst' at 0x626c in the 'edts' at 0x6264 becomes version 1 with 64-bit
+    // durations and media times, followed by a backward edit
+    let mut elst = vec![
+        0, 0, 0, 0x38, b'e', b'l', b's', b't', 1, 0, 0, 0, 0, 0, 0, 2,
+    ];
+    elst.extend_from_slice(&((1_u64 << 32) + 1000).to_be_bytes());
+    elst.extend_from_slice(&((1_i64 << 32) + 1024).to_be_bytes());
+    elst.extend_from_slice(&[0, 1, 0, 0]);
+    elst.extend_from_slice(&500_u64.to_be_bytes());
+    elst.extend_from_slice(&(-1_i64).to_be_bytes());
+    elst.extend_from_slice(&[0xff, 0xff, 0, 0]);
+
+    let mut modified = data[..0x626c].to_vec();
+    modified.extend_from_slice(&elst);
+    modified.extend_from_slice(&data[0x6288..]);
+    for offset in &[0x6264, 0x6200, 0x618c] {
+        let mut size = [0; 4];
+        size.copy_from_slice(&modified[*offset..offset + 4]);
+        let size = u32::from_be_bytes(size) + 0x1c;
+        modified[*offset..offset + 4].copy_from_slice(&size.to_be_bytes());
+    }
+
+    let qt = qtfile::parse(&mut Cursor::new(modified)).unwrap();
+    let trak = &qt.moov().unwrap().trak_atom[0];
+    let elst = trak.edts_atom.as_ref().unwrap().elst_atom.as_ref().unwrap();
+
+    assert_eq!(elst.atom_version, 1);
+    assert_eq!(elst.edit_list_table[0].track_duration, (1 << 32) + 1000);
+    assert_eq!(elst.edit_list_table[0].media_time, (1 << 32) + 1024);
+    assert_eq!(elst.edit_list_table[0].media_rate, 1);
+    assert_eq!(elst.edit_list_table[1].media_time, -1);
+    assert_eq!(elst.edit_list_table[1].media_rate, -1);
+
+    let edits = trak.edits(1000);
+    assert_eq!(edits[0].media_time, Some((1 << 32) + 1024));
+    assert_eq!(edits[1].movie_time, (1 << 32) + 1000);
+    assert_eq!(edits[1].media_time, None);
+    assert_eq!(edits[1].media_rate, 0);
+
+    // the siblings after 'edts' are still found
+    assert_eq!(trak.samples().len(), 30);
+}
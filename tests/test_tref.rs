@@ -0,0 +1,110 @@
+use std::fs;
+use std::io::Cursor;
+
+use atom_analyzer::atom::tref::{TrackReferenceError, TrackReferenceType};
+use atom_analyzer::qtfile::{self, QtFile};
+use atom_analyzer::validate;
+
+fn atom(atom_type: &[u8; 4], body: &[u8]) -> Vec<u8> {
+    let mut data = (8 + body.len() as u32).to_be_bytes().to_vec();
+    data.extend_from_slice(atom_type);
+    data.extend_from_slice(body);
+    data
+}
+
+fn reference(atom_type: &[u8; 4], track_ids: &[u32]) -> Vec<u8> {
+    atom(
+        atom_type,
+        &track_ids
+            .iter()
+            .flat_map(|id| id.to_be_bytes())
+            .collect::<Vec<_>>(),
+    )
+}
+
+/// Returns the sample with `tref` holding `references` after 'tkhd'
+fn sample_with_tref(references: &[u8]) -> QtFile {
+    let mut data = fs::read("tests/samples/camouflage_vga.mov").unwrap();
+    let tref = atom(b"tref", references);
+
+    // 'tkhd' ends at 0x6264 in the 'trak' at 0x6200 in the 'moov' at 0x618c
+    data.splice(0x6264..0x6264, tref.iter().copied());
+    for offset in &[0x6200, 0x618c] {
+        let mut size = [0; 4];
+        size.copy_from_slice(&data[*offset..offset + 4]);
+        let size = u32::from_be_bytes(size) + tref.len() as u32;
+        data[*offset..offset + 4].copy_from_slice(&size.to_be_bytes());
+    }
+
+    qtfile::parse(&mut Cursor::new(data)).unwrap()
+}
+
+#[test]
+fn test_tref() {
+    let mut references = reference(b"sync", &[1]);
+    references.extend(reference(b"tmcd", &[3, 1]));
+    references.extend(reference(b"xxxx", &[]));
+    let qt = sample_with_tref(&references);
+
+    let moov = qt.moov().unwrap();
+    let trak = &moov.trak_atom[0];
+    let tref = trak.tref_atom.as_ref().unwrap();
+
+    assert_eq!(
+        tref.track_reference_type_atom
+            .iter()
+            .map(|r| (r.reference_type, r.track_ids.clone()))
+            .collect::<Vec<_>>(),
+        vec![
+            (TrackReferenceType::Synchronization, vec![1]),
+            (TrackReferenceType::Timecode, vec![3, 1]),
+            (TrackReferenceType::Unknown(0x7878_7878), vec![]),
+        ]
+    );
+    assert_eq!(
+        trak.referenced_track_ids(TrackReferenceType::Timecode),
+        vec![3, 1]
+    );
+    assert!(trak
+        .referenced_track_ids(TrackReferenceType::Chapter)
+        .is_empty());
+
+    let dangling = TrackReferenceError::DanglingReference {
+        atom_offset: 0x6264 + 8 + 12,
+        track_id: 1,
+        reference_type: TrackReferenceType::Timecode,
+        referenced_track_id: 3,
+    };
+    assert_eq!(moov.track_references(trak), Err(dangling));
+    assert_eq!(
+        moov.dangling_references()
+            .iter()
+            .map(|e| e.to_string())
+            .collect::<Vec<_>>(),
+        vec![format!(
+            "track 1 refers to missing track 3 by 'tmcd' at {}",
+            0x6264 + 8 + 12
+        )]
+    );
+
+    let findings = validate::validate(&qt);
+    assert!(findings
+        .iter()
+        .any(|f| f.rule_id == "track-reference" && f.atom_offset == 0x6264 + 8 + 12));
+}
+
+#[test]
+fn test_track_references() {
+    let qt = sample_with_tref(&reference(b"vdep", &[1]));
+    let moov = qt.moov().unwrap();
+    let references = moov.track_references(&moov.trak_atom[0]).unwrap();
+
+    assert_eq!(references.len(), 1);
+    assert_eq!(
+        references[0].reference_type,
+        TrackReferenceType::AuxiliaryDepthVideo
+    );
+    assert_eq!(references[0].track.tkhd_atom.track_id, 1);
+    assert!(moov.dangling_references().is_empty());
+    assert_eq!(references[0].reference_type.to_string(), "vdep");
+}
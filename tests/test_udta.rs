@@ -0,0 +1,95 @@
+use std::io::Cursor;
+use std::path::PathBuf;
+
+use atom_analyzer::atom::udta::{self, InternationalText, UserData};
+use atom_analyzer::qtfile;
+
+#[test]
+fn test_camouflage_vga_mov_udta() {
+    let file_name = PathBuf::from("tests/samples/camouflage_vga.mov");
+    let qt = qtfile::parse_file(file_name).unwrap();
+    let moov = qt.moov().unwrap();
+
+    let udta = moov.udta_atom.as_ref().unwrap();
+    assert_eq!(udta.user_data_atom.len(), 1);
+    assert_eq!(udta.user_data_atom[0].atom_head.atom_offset, 0x65e9);
+    assert_eq!(
+        udta.user_data_atom[0].user_data,
+        UserData::Text(vec![InternationalText {
+            language: 0x55c4,
+            text: "Lavf58.29.100".to_string(),
+        }])
+    );
+    assert_eq!(
+        udta.user_data_atom[0].user_data,
+        udta.get(udta::SOFTWARE).unwrap().user_data
+    );
+    assert_eq!(moov.user_text(udta::SOFTWARE), Some("Lavf58.29.100"));
+    assert_eq!(moov.user_text(udta::TITLE), None);
+
+    assert!(moov.trak_atom[0].udta_atom.is_none());
+    assert_eq!(moov.trak_atom[0].user_text(udta::SOFTWARE), None);
+}
+
+/// Returns a 'udta' atom holding `entries` of type and payload
+fn udta_of(entries: &[(&[u8; 4], Vec<u8>)]) -> Vec<u8> {
+    let mut body = Vec::new();
+    for (atom_type, payload) in entries {
+        body.extend_from_slice(&(8 + payload.len() as u32).to_be_bytes());
+        body.extend_from_slice(&atom_type[..]);
+        body.extend_from_slice(payload);
+    }
+    // terminator
+    body.extend_from_slice(&[0, 0, 0, 0]);
+
+    let mut data = (8 + body.len() as u32).to_be_bytes().to_vec();
+    data.extend_from_slice(b"udta");
+    data.extend_from_slice(&body);
+    data
+}
+
+#[test]
+fn test_udta_entries() {
+    let mut xyz = vec![0, 12, 0x15, 0xc7];
+    xyz.extend_from_slice(b"+35.6-139.7/");
+    let mut nam = vec![0, 5, 0, 0];
+    nam.extend_from_slice(b"Hello");
+    nam.extend_from_slice(&[0, 7, 0, 14]);
+    nam.extend_from_slice(b"Bonjour");
+    // a size running past the payload
+    let day = vec![0, 20, 0x15, 0xc7, b'2', b'0'];
+    let hinf = vec![1, 2, 3, 4];
+
+    let data = udta_of(&[
+        (b"\xa9xyz", xyz),
+        (b"\xa9nam", nam),
+        (b"\xa9day", day.clone()),
+        (b"hinf", hinf.clone()),
+    ]);
+    let udta = atom_analyzer::atom::parse(&mut Cursor::new(data))
+        .unwrap()
+        .downcast::<udta::UdtaAtom>()
+        .unwrap();
+
+    assert_eq!(udta.user_data_atom.len(), 4);
+    assert_eq!(udta.text(udta::LOCATION), Some("+35.6-139.7/"));
+    match &udta.get(udta::LOCATION).unwrap().user_data {
+        UserData::Text(texts) => assert_eq!(texts[0].language_code().unwrap(), "eng"),
+        d => panic!("{:?}", d),
+    }
+    match &udta.get(udta::TITLE).unwrap().user_data {
+        UserData::Text(texts) => {
+            assert_eq!(texts.len(), 2);
+            assert_eq!(texts[1].text, "Bonjour");
+            assert_eq!(texts[1].language, 14);
+            assert_eq!(texts[1].language_code(), None);
+        }
+        d => panic!("{:?}", d),
+    }
+    assert_eq!(udta.get(udta::DATE).unwrap().user_data, UserData::Raw(day));
+    assert_eq!(udta.text(udta::DATE), None);
+    assert_eq!(
+        udta.get(0x6869_6e66).unwrap().user_data,
+        UserData::Raw(hinf)
+    );
+}
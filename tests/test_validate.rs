@@ -0,0 +1,201 @@
+use std::fs;
+use std::io::Cursor;
+use std::path::PathBuf;
+
+use atom_analyzer::atom::{self, stsc::SampleToChunk};
+use atom_analyzer::qtfile::{self, QtFile};
+use atom_analyzer::validate::{self, rules, Finding, Rule, Severity, Validator};
+
+fn sample() -> Vec<u8> {
+    fs::read("tests/samples/camouflage_vga.mov").unwrap()
+}
+
+/// Inserts `bytes` at `offset` and grows the atoms at `parents` to hold them
+fn insert(data: &mut Vec<u8>, offset: usize, bytes: &[u8], parents: &[usize]) {
+    data.splice(offset..offset, bytes.iter().copied());
+    for parent in parents {
+        let mut size = [0; 4];
+        size.copy_from_slice(&data[*parent..parent + 4]);
+        let size = u32::from_be_bytes(size) + bytes.len() as u32;
+        data[*parent..parent + 4].copy_from_slice(&size.to_be_bytes());
+    }
+}
+
+/// Returns the findings of `rule` alone in `data`
+fn check(rule: &dyn Rule, data: Vec<u8>) -> Vec<(&'static str, Severity, u64)> {
+    let qt = qtfile::parse(&mut Cursor::new(data)).unwrap();
+    let mut findings = Vec::new();
+    rule.check(&qt, &mut findings);
+
+    findings
+        .into_iter()
+        .map(|f| (f.rule_id, f.severity, f.atom_offset))
+        .collect()
+}
+
+#[test]
+fn test_camouflage_vga_mov_is_valid() {
+    let file_name = PathBuf::from("tests/samples/camouflage_vga.mov");
+    let qt = qtfile::parse_file(file_name).unwrap();
+
+    assert_eq!(validate::validate(&qt), vec![]);
+}
+
+struct AlwaysFails;
+
+impl Rule for AlwaysFails {
+    fn id(&self) -> &'static str {
+        "always-fails"
+    }
+
+    fn check(&self, _qt: &QtFile, findings: &mut Vec<Finding>) {
+        findings.push(Finding::new(
+            Severity::Info,
+            self.id(),
+            0x10,
+            "failed".into(),
+        ));
+    }
+}
+
+#[test]
+fn test_custom_rule() {
+    let file_name = PathBuf::from("tests/samples/camouflage_vga.mov");
+    let qt = qtfile::parse_file(file_name).unwrap();
+
+    let mut validator = Validator::new();
+    validator.add_rule(Box::new(AlwaysFails));
+
+    let findings = validator.validate(&qt);
+
+    assert_eq!(findings.len(), 1);
+    assert_eq!(
+        format!("{}", findings[0]),
+        "info[always-fails] at 0x0000000000000010: failed"
+    );
+}
+
+#[test]
+fn test_stsc_sample_count() {
+    let stsc = atom::stsc::StscAtom {
+        atom_head: atom::AtomHead {
+            atom_offset: 0,
+            atom_size: 0x28,
+            atom_type: atom::stsc::ATOM_ID,
+        },
+        atom_version: 0,
+        atom_flags: [0, 0, 0],
+        number_of_entries: 2,
+        sample_to_chunk_table: vec![SampleToChunk::new(1, 3, 1), SampleToChunk::new(3, 2, 1)],
+    };
+
+    assert_eq!(stsc.sample_count(2), 6);
+    assert_eq!(stsc.sample_count(4), 10);
+}
+
+#[test]
+fn test_ftyp_not_first() {
+    let data = sample();
+    // 'wide' of 8 bytes at 0x14 moves before 'ftyp'
+    let mut moved = data[0x14..0x1c].to_vec();
+    moved.extend_from_slice(&data[..0x14]);
+    moved.extend_from_slice(&data[0x1c..]);
+
+    assert_eq!(
+        check(&rules::FtypFirst, moved),
+        vec![("ftyp-first", Severity::Error, 0x8)]
+    );
+}
+
+#[test]
+fn test_sync_samples_not_ascending() {
+    let mut data = sample();
+    // 'stss' at 0x6411 holds one entry at 0x6421, inside 'stbl', 'minf',
+    // 'mdia', 'trak' and 'moov'
+    data[0x641d..0x6425].copy_from_slice(&[0, 0, 0, 2, 0, 0, 0, 5]);
+    insert(
+        &mut data,
+        0x6425,
+        &3_u32.to_be_bytes(),
+        &[0x6411, 0x6349, 0x62dd, 0x6288, 0x6200, 0x618c],
+    );
+
+    assert_eq!(
+        check(&rules::SyncSamples, data),
+        vec![("sync-sample", Severity::Error, 0x6411)]
+    );
+}
+
+#[test]
+fn test_duplicate_track_ids() {
+    let mut data = sample();
+    // a copy of the 'trak' at 0x6200 is appended to the 'moov' ending the file
+    let trak = data[0x6200..0x65e1].to_vec();
+    let tail = data.len();
+    insert(&mut data, tail, &trak, &[0x618c]);
+
+    assert_eq!(
+        check(&rules::TrackIds, data),
+        vec![("track-id", Severity::Error, tail as u64 + 8)]
+    );
+}
+
+#[test]
+fn test_chunk_offset_outside_mdat() {
+    let mut data = sample();
+    // the only chunk offset, at 0x65dd, points in the head of 'mdat' at 0x1c
+    data[0x65dd..0x65e1].copy_from_slice(&0x20_u32.to_be_bytes());
+
+    assert_eq!(
+        check(&rules::ChunkOffsetsInMdat, data),
+        vec![("chunk-offset", Severity::Error, 0x65cd)]
+    );
+}
+
+#[test]
+fn test_edit_list_duration_mismatch() {
+    let mut data = sample();
+    // the track duration of the only edit at 0x627c is 1000
+    data[0x627c..0x6280].copy_from_slice(&500_u32.to_be_bytes());
+
+    assert_eq!(
+        check(&rules::EditListDuration, data),
+        vec![("edit-list-duration", Severity::Warning, 0x6208)]
+    );
+}
+
+#[test]
+fn test_required_atoms_missing() {
+    let mut data = sample();
+    // 'mvhd' at 0x6194 in 'moov', 'hdlr' at 0x62b0 in 'mdia' and 'stsd' at
+    // 0x6351 in 'stbl' are renamed to unknown types
+    data[0x6198..0x619c].copy_from_slice(b"xvhd");
+    data[0x62b4..0x62b8].copy_from_slice(b"xdlr");
+    data[0x6355..0x6359].copy_from_slice(b"xtsd");
+
+    assert_eq!(
+        check(&rules::RequiredAtoms, data),
+        vec![
+            ("required-atom", Severity::Error, 0x618c),
+            ("required-atom", Severity::Error, 0x6288),
+            ("required-atom", Severity::Error, 0x6349),
+        ]
+    );
+}
+
+#[test]
+fn test_sample_counts_mismatch() {
+    let mut data = sample();
+    // 'stts' at 0x63f9 and 'stsc' at 0x6525 each describe the 30 samples of
+    // 'stsz' in one entry, with the sample counts at 0x6409 and 0x6539
+    data[0x6409..0x640d].copy_from_slice(&29_u32.to_be_bytes());
+    data[0x6539..0x653d].copy_from_slice(&31_u32.to_be_bytes());
+
+    assert_eq!(
+        check(&rules::SampleCounts, data),
+        vec![
+            ("sample-count", Severity::Error, 0x63f9),
+            ("sample-count", Severity::Error, 0x6525),
+        ]
+    );
+}
@@ -0,0 +1,82 @@
+use std::path::PathBuf;
+
+use atom_analyzer::atom::{Atom, AtomVisitor};
+use atom_analyzer::qtfile;
+
+#[test]
+fn test_camouflage_vga_mov_walk() {
+    let file_name = PathBuf::from("tests/samples/camouflage_vga.mov");
+    let qt = qtfile::parse_file(file_name).unwrap();
+
+    let mut atoms = Vec::new();
+    qt.walk(&mut |a: &dyn Atom, depth: usize| {
+        atoms.push(format!("{}{}", "  ".repeat(depth), a.fourcc()))
+    });
+
+    assert_eq!(
+        atoms,
+        vec![
+            "ftyp",
+            "wide",
+            "mdat",
+            "moov",
+            "  mvhd",
+            "  trak",
+            "    tkhd",
+            "    edts",
+            "      elst",
+            "    mdia",
+            "      mdhd",
+            "      hdlr",
+            "      minf",
+            "        vmhd",
+            "        hdlr",
+            "        dinf",
+            "          dref",
+            "            url ",
+            "        stbl",
+            "          stsd",
+            "          stts",
+            "          stss",
+            "          ctts",
+            "          stsc",
+            "          stsz",
+            "          stco",
+            "  udta",
+            "    \u{a9}swr",
+        ]
+    );
+}
+
+/// Counts atoms and tracks the nesting
+#[derive(Default)]
+struct Stats {
+    count: usize,
+    open: usize,
+    max_depth: usize,
+}
+
+impl AtomVisitor for Stats {
+    fn visit(&mut self, _atom: &dyn Atom, depth: usize) {
+        self.count += 1;
+        self.open += 1;
+        self.max_depth = self.max_depth.max(depth);
+    }
+
+    fn leave(&mut self, _atom: &dyn Atom, _depth: usize) {
+        self.open -= 1;
+    }
+}
+
+#[test]
+fn test_camouflage_vga_mov_visitor() {
+    let file_name = PathBuf::from("tests/samples/camouflage_vga.mov");
+    let qt = qtfile::parse_file(file_name).unwrap();
+
+    let mut stats = Stats::default();
+    qt.walk(&mut stats);
+
+    assert_eq!(stats.count, 28);
+    assert_eq!(stats.open, 0);
+    assert_eq!(stats.max_depth, 6);
+}